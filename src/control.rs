@@ -0,0 +1,125 @@
+// Interrupt lines and run state shared by the processors
+use crate::error::Error;
+
+// What the processor does on its next step
+#[derive(Clone, Copy)]
+pub(crate) enum Entry {
+    Nmi,         // Service the pending NMI
+    Irq,         // Service the IRQ
+    Instruction, // Fetch and execute the next instruction
+}
+
+// Run state of a processor before a step, put back when the step fails
+#[derive(Clone, Copy)]
+pub(crate) struct Saved {
+    halted: bool,
+    waiting: bool,
+    nmi_pending: bool,
+}
+
+// Interrupt lines of a processor, whether it stopped on a WAI or STP
+// and the first error of the step being run
+pub(crate) struct Control {
+    irq: bool,            // Level of the IRQ line
+    nmi: bool,            // Level of the NMI line
+    nmi_pending: bool,    // NMI edge seen but not serviced yet
+    pub halted: bool,     // Stopped by a JAM or STP opcode until reset
+    pub waiting: bool,    // Stopped by a WAI opcode until an interrupt
+    fault: Option<Error>, // First error of the step being run
+}
+
+impl Control {
+    pub fn new() -> Self {
+        Self {
+            irq: false,
+            nmi: false,
+            nmi_pending: false,
+            halted: false,
+            waiting: false,
+            fault: None,
+        }
+    }
+
+    // Forget the pending NMI and get running again, the lines keep their levels
+    // Called after the reset vector is read, an unmapped vector reads as zero
+    // and the reset itself cannot fail
+    pub fn reset(&mut self) {
+        self.nmi_pending = false;
+        self.halted = false;
+        self.waiting = false;
+        self.fault = None;
+    }
+
+    // Assert or deassert the IRQ line
+    // The IRQ is level triggered and is serviced as long as it is asserted
+    // and the interrupt disable flag is clear
+    pub fn set_irq(&mut self, asserted: bool) {
+        self.irq = asserted;
+    }
+
+    // Assert or deassert the NMI line
+    // The NMI is edge triggered, only going from deasserted to asserted
+    // requests an interrupt
+    pub fn set_nmi(&mut self, asserted: bool) {
+        if asserted && !self.nmi {
+            self.nmi_pending = true;
+        }
+        self.nmi = asserted;
+    }
+
+    // Check if the processor is waiting for an interrupt and none is requested
+    pub fn is_asleep(&self) -> bool {
+        self.waiting && !self.nmi_pending && !self.irq
+    }
+
+    // Check if a run can go on, the processor is neither halted nor asleep
+    pub fn is_running(&self) -> bool {
+        !self.halted && !self.is_asleep()
+    }
+
+    // Decide what the next step does, or nothing when the processor is halted or asleep
+    pub fn next_entry(&mut self, interrupt_disabled: bool) -> Option<Entry> {
+        if !self.is_running() {
+            return None;
+        }
+
+        // An interrupt request ends a WAI even when the IRQ is masked,
+        // execution then simply continues after the WAI
+        self.waiting = false;
+
+        if self.nmi_pending {
+            self.nmi_pending = false;
+            Some(Entry::Nmi)
+        } else if self.irq && !interrupt_disabled {
+            Some(Entry::Irq)
+        } else {
+            Some(Entry::Instruction)
+        }
+    }
+
+    // Record an error of the step being run, only the first one is reported
+    pub fn fail(&mut self, error: Error) {
+        self.fault.get_or_insert(error);
+    }
+
+    // Take the error of the step that just ran, if it failed
+    pub fn take_fault(&mut self) -> Option<Error> {
+        self.fault.take()
+    }
+
+    // Save the run state before a step
+    pub fn save(&self) -> Saved {
+        Saved {
+            halted: self.halted,
+            waiting: self.waiting,
+            nmi_pending: self.nmi_pending,
+        }
+    }
+
+    // Put back the run state of a failed step, an NMI edge seen during the step stays pending
+    pub fn restore(&mut self, saved: Saved) {
+        self.halted = saved.halted;
+        self.waiting = saved.waiting;
+        self.nmi_pending |= saved.nmi_pending;
+    }
+}
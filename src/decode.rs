@@ -0,0 +1,938 @@
+// Decode tables
+// Every opcode decodes to an operation and the addressing mode of its operand,
+// each variant of the processor has its own table of all 256 opcodes
+// The tables are shared by the processor and the public instruction info and disassembler
+
+use crate::{
+    opcodes::{cmos::*, illegal::*, *},
+    processor::Variant,
+};
+use AddressingMode::*;
+use Operation::*;
+
+// Information about an opcode on a variant of the processor
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Instruction {
+    pub opcode: u8,
+    pub mnemonic: &'static str,
+    pub mode: AddressingMode,
+    pub length: u8,               // Bytes including the opcode
+    pub cycles: u8,               // Base cycles, without the penalties
+    pub page_cross_penalty: bool, // Takes an extra cycle when the indexing crosses a page
+    pub documented: bool,         // Listed in the datasheet of the variant
+    pub emulated: bool,           // Run by the processor, the unstable NMOS opcodes are not
+}
+
+impl Instruction {
+    // Look up the given opcode on the given variant, every opcode is known
+    // The undocumented opcodes of the NMOS chips are included, also the unstable ones
+    pub fn decode(variant: Variant, opcode: u8) -> Self {
+        let (operation, mode) = match variant {
+            Variant::Nmos6502 | Variant::Ricoh2A03 => NMOS_INFO_TABLE[opcode as usize],
+            Variant::Rockwell65C02 => ROCKWELL_TABLE[opcode as usize],
+            Variant::Wdc65C02 => WDC_TABLE[opcode as usize],
+        };
+
+        let cmos = matches!(variant, Variant::Rockwell65C02 | Variant::Wdc65C02);
+        let documented = match operation {
+            Jam | NopSingle | NopLong => false,
+            Nop => opcode == NOP,
+            _ if cmos => true,
+            _ => NMOS_TABLE[opcode as usize].0 != Unknown,
+        };
+
+        Self {
+            opcode,
+            mnemonic: operation.mnemonic(),
+            mode,
+            // BRK skips the byte after it
+            length: if operation == Brk { 2 } else { mode.length() },
+            cycles: base_cycles(variant, opcode),
+            page_cross_penalty: page_cross_penalty(operation, mode, cmos),
+            documented,
+            emulated: !operation.is_unstable(),
+        }
+    }
+}
+
+// Disassemble the instruction at the start of the given bytes, which are at the given address
+// Returns the text and the length of the instruction, or None when the bytes end
+// before the instruction does
+pub fn disassemble(variant: Variant, address: u16, bytes: &[u8]) -> Option<(String, u8)> {
+    let instruction = Instruction::decode(variant, *bytes.first()?);
+    let operands = bytes.get(1..instruction.mode.length() as usize)?;
+
+    let byte = || operands[0];
+    let word = || u16::from_le_bytes([operands[0], operands[1]]);
+    // Branch targets are relative to the end of the instruction
+    let target = |offset: u8| {
+        let next = address.wrapping_add(instruction.mode.length() as u16);
+        next.wrapping_add(offset as i8 as u16)
+    };
+
+    let operand = match instruction.mode {
+        Implied => String::new(),
+        Accumulator => " A".to_string(),
+        Immediate => format!(" #${:02X}", byte()),
+        ZeroPage => format!(" ${:02X}", byte()),
+        ZeroPageX => format!(" ${:02X},X", byte()),
+        ZeroPageY => format!(" ${:02X},Y", byte()),
+        Absolute => format!(" ${:04X}", word()),
+        AbsoluteX => format!(" ${:04X},X", word()),
+        AbsoluteY => format!(" ${:04X},Y", word()),
+        Indirect => format!(" (${:04X})", word()),
+        IndirectX => format!(" (${:02X},X)", byte()),
+        IndirectY => format!(" (${:02X}),Y", byte()),
+        ZeroPageIndirect => format!(" (${:02X})", byte()),
+        AbsoluteXIndirect => format!(" (${:04X},X)", word()),
+        Relative => format!(" ${:04X}", target(operands[0])),
+        ZeroPageRelative => format!(" ${:02X},${:04X}", operands[0], target(operands[1])),
+    };
+
+    Some((
+        format!("{}{}", instruction.mnemonic, operand),
+        instruction.length,
+    ))
+}
+
+// Operations, one for every instruction no matter its addressing mode
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Operation {
+    // Load/store
+    Lda,
+    Ldx,
+    Ldy,
+    Sta,
+    Stx,
+    Sty,
+    Stz,
+
+    // Transfer
+    Tax,
+    Tay,
+    Txa,
+    Tya,
+    Tsx,
+    Txs,
+
+    // Stack
+    Pha,
+    Php,
+    Pla,
+    Plp,
+    Phx,
+    Phy,
+    Plx,
+    Ply,
+
+    // Logical
+    And,
+    Eor,
+    Ora,
+    Bit,
+    Trb,
+    Tsb,
+
+    // Arithmetic
+    Adc,
+    Sbc,
+    Cmp,
+    Cpx,
+    Cpy,
+
+    // Increments/decrements
+    Inc,
+    Inx,
+    Iny,
+    Dec,
+    Dex,
+    Dey,
+
+    // Shifts
+    Asl,
+    Lsr,
+    Rol,
+    Ror,
+
+    // Jumps
+    Jmp,
+    Jsr,
+    Rts,
+
+    // Branches
+    Bpl,
+    Bmi,
+    Bvc,
+    Bvs,
+    Bcc,
+    Bcs,
+    Bne,
+    Beq,
+    Bra,
+
+    // Status flags
+    Clc,
+    Cld,
+    Cli,
+    Clv,
+    Sec,
+    Sed,
+    Sei,
+
+    // System
+    Brk,
+    Nop,
+    Rti,
+    Wai,
+    Stp,
+
+    // Bit instructions, with the bit number
+    Rmb(u8),
+    Smb(u8),
+    Bbr(u8),
+    Bbs(u8),
+
+    // Undocumented opcodes
+    Lax,
+    Sax,
+    Dcp,
+    Isc,
+    Slo,
+    Rla,
+    Sre,
+    Rra,
+    Anc,
+    Alr,
+    Arr,
+    Sbx,
+    Jam,
+
+    // Unstable undocumented opcodes, only decoded for the instruction info
+    Ane,
+    Lxa,
+    Sha,
+    Shx,
+    Shy,
+    Tas,
+    Las,
+
+    // No operations of the CMOS variants that do not fit an addressing mode
+    NopSingle, // Takes a single cycle and does not read the next byte
+    NopLong,   // Takes the operand of an absolute address and 8 cycles
+
+    // Opcode not known by the variant
+    Unknown,
+}
+
+// Addressing modes
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AddressingMode {
+    Implied,
+    Accumulator,
+    Immediate,
+    ZeroPage,
+    ZeroPageX,
+    ZeroPageY,
+    Absolute,
+    AbsoluteX,
+    AbsoluteY,
+    Indirect,          // JMP ($1234)
+    IndirectX,         // LDA ($12,X)
+    IndirectY,         // LDA ($12),Y
+    ZeroPageIndirect,  // LDA ($12), only on the CMOS variants
+    AbsoluteXIndirect, // JMP ($1234,X), only on the CMOS variants
+    Relative,          // Branches
+    ZeroPageRelative,  // BBR and BBS, a zero page address and a branch offset
+}
+
+impl Operation {
+    // Get the assembler mnemonic
+    fn mnemonic(self) -> &'static str {
+        match self {
+            Lda => "LDA",
+            Ldx => "LDX",
+            Ldy => "LDY",
+            Sta => "STA",
+            Stx => "STX",
+            Sty => "STY",
+            Stz => "STZ",
+            Tax => "TAX",
+            Tay => "TAY",
+            Txa => "TXA",
+            Tya => "TYA",
+            Tsx => "TSX",
+            Txs => "TXS",
+            Pha => "PHA",
+            Php => "PHP",
+            Pla => "PLA",
+            Plp => "PLP",
+            Phx => "PHX",
+            Phy => "PHY",
+            Plx => "PLX",
+            Ply => "PLY",
+            And => "AND",
+            Eor => "EOR",
+            Ora => "ORA",
+            Bit => "BIT",
+            Trb => "TRB",
+            Tsb => "TSB",
+            Adc => "ADC",
+            Sbc => "SBC",
+            Cmp => "CMP",
+            Cpx => "CPX",
+            Cpy => "CPY",
+            Inc => "INC",
+            Inx => "INX",
+            Iny => "INY",
+            Dec => "DEC",
+            Dex => "DEX",
+            Dey => "DEY",
+            Asl => "ASL",
+            Lsr => "LSR",
+            Rol => "ROL",
+            Ror => "ROR",
+            Jmp => "JMP",
+            Jsr => "JSR",
+            Rts => "RTS",
+            Bpl => "BPL",
+            Bmi => "BMI",
+            Bvc => "BVC",
+            Bvs => "BVS",
+            Bcc => "BCC",
+            Bcs => "BCS",
+            Bne => "BNE",
+            Beq => "BEQ",
+            Bra => "BRA",
+            Clc => "CLC",
+            Cld => "CLD",
+            Cli => "CLI",
+            Clv => "CLV",
+            Sec => "SEC",
+            Sed => "SED",
+            Sei => "SEI",
+            Brk => "BRK",
+            Nop | NopSingle | NopLong => "NOP",
+            Rti => "RTI",
+            Wai => "WAI",
+            Stp => "STP",
+            Rmb(bit) => [
+                "RMB0", "RMB1", "RMB2", "RMB3", "RMB4", "RMB5", "RMB6", "RMB7",
+            ][bit as usize],
+            Smb(bit) => [
+                "SMB0", "SMB1", "SMB2", "SMB3", "SMB4", "SMB5", "SMB6", "SMB7",
+            ][bit as usize],
+            Bbr(bit) => [
+                "BBR0", "BBR1", "BBR2", "BBR3", "BBR4", "BBR5", "BBR6", "BBR7",
+            ][bit as usize],
+            Bbs(bit) => [
+                "BBS0", "BBS1", "BBS2", "BBS3", "BBS4", "BBS5", "BBS6", "BBS7",
+            ][bit as usize],
+            Lax => "LAX",
+            Sax => "SAX",
+            Dcp => "DCP",
+            Isc => "ISC",
+            Slo => "SLO",
+            Rla => "RLA",
+            Sre => "SRE",
+            Rra => "RRA",
+            Anc => "ANC",
+            Alr => "ALR",
+            Arr => "ARR",
+            Sbx => "SBX",
+            Jam => "JAM",
+            Ane => "ANE",
+            Lxa => "LXA",
+            Sha => "SHA",
+            Shx => "SHX",
+            Shy => "SHY",
+            Tas => "TAS",
+            Las => "LAS",
+            Unknown => "???",
+        }
+    }
+
+    // Check if the operation is one of the unstable undocumented opcodes, which are not emulated
+    pub(crate) fn is_unstable(self) -> bool {
+        matches!(self, Ane | Lxa | Sha | Shx | Shy | Tas | Las)
+    }
+}
+
+impl AddressingMode {
+    // Get the bytes of an instruction with this addressing mode, including the opcode
+    pub fn length(self) -> u8 {
+        match self {
+            Implied | Accumulator => 1,
+            Immediate | ZeroPage | ZeroPageX | ZeroPageY => 2,
+            IndirectX | IndirectY | ZeroPageIndirect | Relative => 2,
+            Absolute | AbsoluteX | AbsoluteY | Indirect | AbsoluteXIndirect => 3,
+            ZeroPageRelative => 3,
+        }
+    }
+}
+
+// Operation and addressing mode of every opcode
+pub(crate) type DecodeTable = [(Operation, AddressingMode); 256];
+
+// Rows of a decode table
+type Rows = [(u8, Operation, AddressingMode)];
+
+// Opcodes of the NMOS 6502, shared by all variants
+const DOCUMENTED: &Rows = &[
+    // Load accumulator
+    (LDA_IM, Lda, Immediate),
+    (LDA_ZP, Lda, ZeroPage),
+    (LDA_ZPX, Lda, ZeroPageX),
+    (LDA_ABS, Lda, Absolute),
+    (LDA_ABSX, Lda, AbsoluteX),
+    (LDA_ABSY, Lda, AbsoluteY),
+    (LDA_INDX, Lda, IndirectX),
+    (LDA_INDY, Lda, IndirectY),
+    // Load X
+    (LDX_IM, Ldx, Immediate),
+    (LDX_ZP, Ldx, ZeroPage),
+    (LDX_ZPY, Ldx, ZeroPageY),
+    (LDX_ABS, Ldx, Absolute),
+    (LDX_ABSY, Ldx, AbsoluteY),
+    // Load Y
+    (LDY_IM, Ldy, Immediate),
+    (LDY_ZP, Ldy, ZeroPage),
+    (LDY_ZPX, Ldy, ZeroPageX),
+    (LDY_ABS, Ldy, Absolute),
+    (LDY_ABSX, Ldy, AbsoluteX),
+    // Store accumulator
+    (STA_ZP, Sta, ZeroPage),
+    (STA_ZPX, Sta, ZeroPageX),
+    (STA_ABS, Sta, Absolute),
+    (STA_ABSX, Sta, AbsoluteX),
+    (STA_ABSY, Sta, AbsoluteY),
+    (STA_INDX, Sta, IndirectX),
+    (STA_INDY, Sta, IndirectY),
+    // Store X
+    (STX_ZP, Stx, ZeroPage),
+    (STX_ZPY, Stx, ZeroPageY),
+    (STX_ABS, Stx, Absolute),
+    // Store Y
+    (STY_ZP, Sty, ZeroPage),
+    (STY_ZPX, Sty, ZeroPageX),
+    (STY_ABS, Sty, Absolute),
+    // Transfer accumulator to X
+    (TAX, Tax, Implied),
+    // Transfer accumulator to Y
+    (TAY, Tay, Implied),
+    // Transfer X to accumulator
+    (TXA, Txa, Implied),
+    // Transfer Y to accumulator
+    (TYA, Tya, Implied),
+    // Transfer stack pointer to X
+    (TSX, Tsx, Implied),
+    // Transfer X to stack pointer
+    (TXS, Txs, Implied),
+    // Push accumulator on stack
+    (PHA, Pha, Implied),
+    // Push processor status on stack
+    (PHP, Php, Implied),
+    // Pull accumulator from stack
+    (PLA, Pla, Implied),
+    // Pull processor status from stack
+    (PLP, Plp, Implied),
+    // Logical AND
+    (AND_IM, And, Immediate),
+    (AND_ZP, And, ZeroPage),
+    (AND_ZPX, And, ZeroPageX),
+    (AND_ABS, And, Absolute),
+    (AND_ABSX, And, AbsoluteX),
+    (AND_ABSY, And, AbsoluteY),
+    (AND_INDX, And, IndirectX),
+    (AND_INDY, And, IndirectY),
+    // Logical EOR
+    (EOR_IM, Eor, Immediate),
+    (EOR_ZP, Eor, ZeroPage),
+    (EOR_ZPX, Eor, ZeroPageX),
+    (EOR_ABS, Eor, Absolute),
+    (EOR_ABSX, Eor, AbsoluteX),
+    (EOR_ABSY, Eor, AbsoluteY),
+    (EOR_INDX, Eor, IndirectX),
+    (EOR_INDY, Eor, IndirectY),
+    // Logical ORA
+    (ORA_IM, Ora, Immediate),
+    (ORA_ZP, Ora, ZeroPage),
+    (ORA_ZPX, Ora, ZeroPageX),
+    (ORA_ABS, Ora, Absolute),
+    (ORA_ABSX, Ora, AbsoluteX),
+    (ORA_ABSY, Ora, AbsoluteY),
+    (ORA_INDX, Ora, IndirectX),
+    (ORA_INDY, Ora, IndirectY),
+    // Bit test
+    (BIT_ZP, Bit, ZeroPage),
+    (BIT_ABS, Bit, Absolute),
+    // Add with carry
+    (ADC_IM, Adc, Immediate),
+    (ADC_ZP, Adc, ZeroPage),
+    (ADC_ZPX, Adc, ZeroPageX),
+    (ADC_ABS, Adc, Absolute),
+    (ADC_ABSX, Adc, AbsoluteX),
+    (ADC_ABSY, Adc, AbsoluteY),
+    (ADC_INDX, Adc, IndirectX),
+    (ADC_INDY, Adc, IndirectY),
+    // Subtract with carry
+    (SBC_IM, Sbc, Immediate),
+    (SBC_ZP, Sbc, ZeroPage),
+    (SBC_ZPX, Sbc, ZeroPageX),
+    (SBC_ABS, Sbc, Absolute),
+    (SBC_ABSX, Sbc, AbsoluteX),
+    (SBC_ABSY, Sbc, AbsoluteY),
+    (SBC_INDX, Sbc, IndirectX),
+    (SBC_INDY, Sbc, IndirectY),
+    // Compare accumulator
+    (CMP_IM, Cmp, Immediate),
+    (CMP_ZP, Cmp, ZeroPage),
+    (CMP_ZPX, Cmp, ZeroPageX),
+    (CMP_ABS, Cmp, Absolute),
+    (CMP_ABSX, Cmp, AbsoluteX),
+    (CMP_ABSY, Cmp, AbsoluteY),
+    (CMP_INDX, Cmp, IndirectX),
+    (CMP_INDY, Cmp, IndirectY),
+    // Compare X register
+    (CPX_IM, Cpx, Immediate),
+    (CPX_ZP, Cpx, ZeroPage),
+    (CPX_ABS, Cpx, Absolute),
+    // Compare Y register
+    (CPY_IM, Cpy, Immediate),
+    (CPY_ZP, Cpy, ZeroPage),
+    (CPY_ABS, Cpy, Absolute),
+    // Increment memory
+    (INC_ZP, Inc, ZeroPage),
+    (INC_ZPX, Inc, ZeroPageX),
+    (INC_ABS, Inc, Absolute),
+    (INC_ABSX, Inc, AbsoluteX),
+    // Increment X register
+    (INX, Inx, Implied),
+    // Increment Y register
+    (INY, Iny, Implied),
+    // Decrement memory
+    (DEC_ZP, Dec, ZeroPage),
+    (DEC_ZPX, Dec, ZeroPageX),
+    (DEC_ABS, Dec, Absolute),
+    (DEC_ABSX, Dec, AbsoluteX),
+    // Decrement X register
+    (DEX, Dex, Implied),
+    // Decrement Y register
+    (DEY, Dey, Implied),
+    // Arithmetic shift left
+    (ASL_ACC, Asl, Accumulator),
+    (ASL_ZP, Asl, ZeroPage),
+    (ASL_ZPX, Asl, ZeroPageX),
+    (ASL_ABS, Asl, Absolute),
+    (ASL_ABSX, Asl, AbsoluteX),
+    // Logical shift right
+    (LSR_ACC, Lsr, Accumulator),
+    (LSR_ZP, Lsr, ZeroPage),
+    (LSR_ZPX, Lsr, ZeroPageX),
+    (LSR_ABS, Lsr, Absolute),
+    (LSR_ABSX, Lsr, AbsoluteX),
+    // Rotate left
+    (ROL_ACC, Rol, Accumulator),
+    (ROL_ZP, Rol, ZeroPage),
+    (ROL_ZPX, Rol, ZeroPageX),
+    (ROL_ABS, Rol, Absolute),
+    (ROL_ABSX, Rol, AbsoluteX),
+    // Rotate right
+    (ROR_ACC, Ror, Accumulator),
+    (ROR_ZP, Ror, ZeroPage),
+    (ROR_ZPX, Ror, ZeroPageX),
+    (ROR_ABS, Ror, Absolute),
+    (ROR_ABSX, Ror, AbsoluteX),
+    // Jump to another location
+    (JMP_ABS, Jmp, Absolute),
+    (JMP_IND, Jmp, Indirect),
+    // Jump to subroutine
+    (JSR, Jsr, Absolute),
+    // Return from subroutine
+    (RTS, Rts, Implied),
+    // Branch if positive
+    (BPL, Bpl, Relative),
+    // Branch if minus
+    (BMI, Bmi, Relative),
+    // Branch if overflow clear
+    (BVC, Bvc, Relative),
+    // Branch if overflow set
+    (BVS, Bvs, Relative),
+    // Branch if carry clear
+    (BCC, Bcc, Relative),
+    // Branch if carry set
+    (BCS, Bcs, Relative),
+    // Branch if not equal
+    (BNE, Bne, Relative),
+    // Branch if equal
+    (BEQ, Beq, Relative),
+    // Clear carry flag
+    (CLC, Clc, Implied),
+    // Clear decimal mode flag
+    (CLD, Cld, Implied),
+    // Clear interrupt disable flag
+    (CLI, Cli, Implied),
+    // Clear overflow flag
+    (CLV, Clv, Implied),
+    // Set carry flag
+    (SEC, Sec, Implied),
+    // Set decimal mode flag
+    (SED, Sed, Implied),
+    // Set interrupt disable flag
+    (SEI, Sei, Implied),
+    // Force interrupt
+    (BRK, Brk, Implied),
+    // No operation
+    (NOP, Nop, Implied),
+    // Return from interrupt
+    (RTI, Rti, Implied),
+];
+
+// Halting opcodes of the NMOS 6502, always decoded
+const JAM: &Rows = &[
+    // Halt the processor until it is reset, always emulated
+    (JAM_02, Jam, Implied),
+    (JAM_12, Jam, Implied),
+    (JAM_22, Jam, Implied),
+    (JAM_32, Jam, Implied),
+    (JAM_42, Jam, Implied),
+    (JAM_52, Jam, Implied),
+    (JAM_62, Jam, Implied),
+    (JAM_72, Jam, Implied),
+    (JAM_92, Jam, Implied),
+    (JAM_B2, Jam, Implied),
+    (JAM_D2, Jam, Implied),
+    (JAM_F2, Jam, Implied),
+];
+
+// Undocumented opcodes of the NMOS 6502, decoded when enabled
+const UNDOCUMENTED: &Rows = &[
+    // Load accumulator and X register
+    (LAX_ZP, Lax, ZeroPage),
+    (LAX_ZPY, Lax, ZeroPageY),
+    (LAX_ABS, Lax, Absolute),
+    (LAX_ABSY, Lax, AbsoluteY),
+    (LAX_INDX, Lax, IndirectX),
+    (LAX_INDY, Lax, IndirectY),
+    // Store accumulator AND X register
+    (SAX_ZP, Sax, ZeroPage),
+    (SAX_ZPY, Sax, ZeroPageY),
+    (SAX_ABS, Sax, Absolute),
+    (SAX_INDX, Sax, IndirectX),
+    // Decrement memory and compare
+    (DCP_ZP, Dcp, ZeroPage),
+    (DCP_ZPX, Dcp, ZeroPageX),
+    (DCP_ABS, Dcp, Absolute),
+    (DCP_ABSX, Dcp, AbsoluteX),
+    (DCP_ABSY, Dcp, AbsoluteY),
+    (DCP_INDX, Dcp, IndirectX),
+    (DCP_INDY, Dcp, IndirectY),
+    // Increment memory and subtract with carry
+    (ISC_ZP, Isc, ZeroPage),
+    (ISC_ZPX, Isc, ZeroPageX),
+    (ISC_ABS, Isc, Absolute),
+    (ISC_ABSX, Isc, AbsoluteX),
+    (ISC_ABSY, Isc, AbsoluteY),
+    (ISC_INDX, Isc, IndirectX),
+    (ISC_INDY, Isc, IndirectY),
+    // Arithmetic shift left memory and logical ORA
+    (SLO_ZP, Slo, ZeroPage),
+    (SLO_ZPX, Slo, ZeroPageX),
+    (SLO_ABS, Slo, Absolute),
+    (SLO_ABSX, Slo, AbsoluteX),
+    (SLO_ABSY, Slo, AbsoluteY),
+    (SLO_INDX, Slo, IndirectX),
+    (SLO_INDY, Slo, IndirectY),
+    // Rotate left memory and logical AND
+    (RLA_ZP, Rla, ZeroPage),
+    (RLA_ZPX, Rla, ZeroPageX),
+    (RLA_ABS, Rla, Absolute),
+    (RLA_ABSX, Rla, AbsoluteX),
+    (RLA_ABSY, Rla, AbsoluteY),
+    (RLA_INDX, Rla, IndirectX),
+    (RLA_INDY, Rla, IndirectY),
+    // Logical shift right memory and logical EOR
+    (SRE_ZP, Sre, ZeroPage),
+    (SRE_ZPX, Sre, ZeroPageX),
+    (SRE_ABS, Sre, Absolute),
+    (SRE_ABSX, Sre, AbsoluteX),
+    (SRE_ABSY, Sre, AbsoluteY),
+    (SRE_INDX, Sre, IndirectX),
+    (SRE_INDY, Sre, IndirectY),
+    // Rotate right memory and add with carry
+    (RRA_ZP, Rra, ZeroPage),
+    (RRA_ZPX, Rra, ZeroPageX),
+    (RRA_ABS, Rra, Absolute),
+    (RRA_ABSX, Rra, AbsoluteX),
+    (RRA_ABSY, Rra, AbsoluteY),
+    (RRA_INDX, Rra, IndirectX),
+    (RRA_INDY, Rra, IndirectY),
+    // Logical AND and copy bit 7 to carry
+    (ANC_IM_0B, Anc, Immediate),
+    (ANC_IM_2B, Anc, Immediate),
+    // Logical AND and logical shift right accumulator
+    (ALR_IM, Alr, Immediate),
+    // Logical AND and rotate right accumulator
+    (ARR_IM, Arr, Immediate),
+    // Accumulator AND X register minus value into X register
+    (SBX_IM, Sbx, Immediate),
+    // Subtract with carry, same as SBC_IM
+    (USBC_IM, Sbc, Immediate),
+    // No operation, implied
+    (NOP_1A, Nop, Implied),
+    (NOP_3A, Nop, Implied),
+    (NOP_5A, Nop, Implied),
+    (NOP_7A, Nop, Implied),
+    (NOP_DA, Nop, Implied),
+    (NOP_FA, Nop, Implied),
+    // No operation, reads an immediate operand
+    (NOP_IM_80, Nop, Immediate),
+    (NOP_IM_82, Nop, Immediate),
+    (NOP_IM_89, Nop, Immediate),
+    (NOP_IM_C2, Nop, Immediate),
+    (NOP_IM_E2, Nop, Immediate),
+    // No operation, reads a zero page operand
+    (NOP_ZP_04, Nop, ZeroPage),
+    (NOP_ZP_44, Nop, ZeroPage),
+    (NOP_ZP_64, Nop, ZeroPage),
+    // No operation, reads a zero page, X operand
+    (NOP_ZPX_14, Nop, ZeroPageX),
+    (NOP_ZPX_34, Nop, ZeroPageX),
+    (NOP_ZPX_54, Nop, ZeroPageX),
+    (NOP_ZPX_74, Nop, ZeroPageX),
+    (NOP_ZPX_D4, Nop, ZeroPageX),
+    (NOP_ZPX_F4, Nop, ZeroPageX),
+    // No operation, reads an absolute operand
+    (NOP_ABS_0C, Nop, Absolute),
+    // No operation, reads an absolute, X operand
+    (NOP_ABSX_1C, Nop, AbsoluteX),
+    (NOP_ABSX_3C, Nop, AbsoluteX),
+    (NOP_ABSX_5C, Nop, AbsoluteX),
+    (NOP_ABSX_7C, Nop, AbsoluteX),
+    (NOP_ABSX_DC, Nop, AbsoluteX),
+    (NOP_ABSX_FC, Nop, AbsoluteX),
+];
+
+// Unstable undocumented opcodes of the NMOS 6502, never run by the processor
+const UNSTABLE: &Rows = &[
+    // AND X register with accumulator and immediate
+    (ANE_IM, Ane, Immediate),
+    // Load accumulator and X register with immediate
+    (LXA_IM, Lxa, Immediate),
+    // Store accumulator AND X register AND high byte of address
+    (SHA_INDY, Sha, IndirectY),
+    (SHA_ABSY, Sha, AbsoluteY),
+    // Store X register AND high byte of address
+    (SHX_ABSY, Shx, AbsoluteY),
+    // Store Y register AND high byte of address
+    (SHY_ABSX, Shy, AbsoluteX),
+    // Transfer accumulator AND X register to stack pointer, then store like SHA
+    (TAS_ABSY, Tas, AbsoluteY),
+    // Load accumulator, X register and stack pointer with memory AND stack pointer
+    (LAS_ABSY, Las, AbsoluteY),
+];
+
+// Opcodes added by the 65C02
+const CMOS: &Rows = &[
+    // Load/store with the (zero page) addressing mode
+    (LDA_ZPI, Lda, ZeroPageIndirect),
+    (STA_ZPI, Sta, ZeroPageIndirect),
+    // Store zero
+    (STZ_ZP, Stz, ZeroPage),
+    (STZ_ZPX, Stz, ZeroPageX),
+    (STZ_ABS, Stz, Absolute),
+    (STZ_ABSX, Stz, AbsoluteX),
+    // Push X register on stack
+    (PHX, Phx, Implied),
+    // Push Y register on stack
+    (PHY, Phy, Implied),
+    // Pull X register from stack
+    (PLX, Plx, Implied),
+    // Pull Y register from stack
+    (PLY, Ply, Implied),
+    // Logical operations with the (zero page) addressing mode
+    (AND_ZPI, And, ZeroPageIndirect),
+    (EOR_ZPI, Eor, ZeroPageIndirect),
+    (ORA_ZPI, Ora, ZeroPageIndirect),
+    // Bit test
+    (BIT_IM, Bit, Immediate),
+    (BIT_ZPX, Bit, ZeroPageX),
+    (BIT_ABSX, Bit, AbsoluteX),
+    // Test and reset bits
+    (TRB_ZP, Trb, ZeroPage),
+    (TRB_ABS, Trb, Absolute),
+    // Test and set bits
+    (TSB_ZP, Tsb, ZeroPage),
+    (TSB_ABS, Tsb, Absolute),
+    // Arithmetic with the (zero page) addressing mode
+    (ADC_ZPI, Adc, ZeroPageIndirect),
+    (SBC_ZPI, Sbc, ZeroPageIndirect),
+    (CMP_ZPI, Cmp, ZeroPageIndirect),
+    // Increment accumulator
+    (INC_ACC, Inc, Accumulator),
+    // Decrement accumulator
+    (DEC_ACC, Dec, Accumulator),
+    // Jump indexed indirect
+    (JMP_INDX, Jmp, AbsoluteXIndirect),
+    // Branch always
+    (BRA, Bra, Relative),
+    // Reset memory bit
+    (RMB0, Rmb(0), ZeroPage),
+    (RMB1, Rmb(1), ZeroPage),
+    (RMB2, Rmb(2), ZeroPage),
+    (RMB3, Rmb(3), ZeroPage),
+    (RMB4, Rmb(4), ZeroPage),
+    (RMB5, Rmb(5), ZeroPage),
+    (RMB6, Rmb(6), ZeroPage),
+    (RMB7, Rmb(7), ZeroPage),
+    // Set memory bit
+    (SMB0, Smb(0), ZeroPage),
+    (SMB1, Smb(1), ZeroPage),
+    (SMB2, Smb(2), ZeroPage),
+    (SMB3, Smb(3), ZeroPage),
+    (SMB4, Smb(4), ZeroPage),
+    (SMB5, Smb(5), ZeroPage),
+    (SMB6, Smb(6), ZeroPage),
+    (SMB7, Smb(7), ZeroPage),
+    // Branch on bit reset
+    (BBR0, Bbr(0), ZeroPageRelative),
+    (BBR1, Bbr(1), ZeroPageRelative),
+    (BBR2, Bbr(2), ZeroPageRelative),
+    (BBR3, Bbr(3), ZeroPageRelative),
+    (BBR4, Bbr(4), ZeroPageRelative),
+    (BBR5, Bbr(5), ZeroPageRelative),
+    (BBR6, Bbr(6), ZeroPageRelative),
+    (BBR7, Bbr(7), ZeroPageRelative),
+    // Branch on bit set
+    (BBS0, Bbs(0), ZeroPageRelative),
+    (BBS1, Bbs(1), ZeroPageRelative),
+    (BBS2, Bbs(2), ZeroPageRelative),
+    (BBS3, Bbs(3), ZeroPageRelative),
+    (BBS4, Bbs(4), ZeroPageRelative),
+    (BBS5, Bbs(5), ZeroPageRelative),
+    (BBS6, Bbs(6), ZeroPageRelative),
+    (BBS7, Bbs(7), ZeroPageRelative),
+    // Unused opcodes that take up the operands of an addressing mode
+    (0x02, Nop, Immediate),
+    (0x22, Nop, Immediate),
+    (0x42, Nop, Immediate),
+    (0x62, Nop, Immediate),
+    (0x82, Nop, Immediate),
+    (0xC2, Nop, Immediate),
+    (0xE2, Nop, Immediate),
+    (0x44, Nop, ZeroPage),
+    (0x54, Nop, ZeroPageX),
+    (0xD4, Nop, ZeroPageX),
+    (0xF4, Nop, ZeroPageX),
+    (0x5C, NopLong, Absolute),
+    (0xDC, Nop, Absolute),
+    (0xFC, Nop, Absolute),
+];
+
+// Opcodes added by the WDC 65C02
+const WDC: &Rows = &[
+    // Wait for interrupt
+    (WAI, Wai, Implied),
+    // Stop the processor
+    (STP, Stp, Implied),
+];
+
+// Tables the variants build on, the unused opcodes are unknown on the NMOS chips
+// and single cycle no operations on the CMOS chips
+const NMOS_BASE: DecodeTable = table(table([(Unknown, Implied); 256], DOCUMENTED), JAM);
+const CMOS_BASE: DecodeTable = table(table([(NopSingle, Implied); 256], DOCUMENTED), CMOS);
+
+// Decode table of the NMOS 6502 and the Ricoh 2A03
+pub(crate) static NMOS_TABLE: DecodeTable = NMOS_BASE;
+
+// Decode table of the NMOS 6502 and the Ricoh 2A03 with the undocumented opcodes enabled
+pub(crate) static NMOS_UNDOCUMENTED_TABLE: DecodeTable = table(NMOS_BASE, UNDOCUMENTED);
+
+// Table of the instruction info of the NMOS 6502 and the Ricoh 2A03, with every opcode
+static NMOS_INFO_TABLE: DecodeTable = table(table(NMOS_BASE, UNDOCUMENTED), UNSTABLE);
+
+// Decode table of the Rockwell 65C02, where WAI and STP are still unused
+pub(crate) static ROCKWELL_TABLE: DecodeTable = CMOS_BASE;
+
+// Decode table of the WDC 65C02
+pub(crate) static WDC_TABLE: DecodeTable = table(CMOS_BASE, WDC);
+
+// Base cycles of every opcode on the NMOS 6502, the JAM opcodes halt the processor
+// Indexed reads crossing a page and taken branches add cycles on top of these
+#[rustfmt::skip]
+const NMOS_CYCLES: [u8; 256] = [
+    7, 6, 0, 8, 3, 3, 5, 5, 3, 2, 2, 2, 4, 4, 6, 6, // 0x
+    2, 5, 0, 8, 4, 4, 6, 6, 2, 4, 2, 7, 4, 4, 7, 7, // 1x
+    6, 6, 0, 8, 3, 3, 5, 5, 4, 2, 2, 2, 4, 4, 6, 6, // 2x
+    2, 5, 0, 8, 4, 4, 6, 6, 2, 4, 2, 7, 4, 4, 7, 7, // 3x
+    6, 6, 0, 8, 3, 3, 5, 5, 3, 2, 2, 2, 3, 4, 6, 6, // 4x
+    2, 5, 0, 8, 4, 4, 6, 6, 2, 4, 2, 7, 4, 4, 7, 7, // 5x
+    6, 6, 0, 8, 3, 3, 5, 5, 4, 2, 2, 2, 5, 4, 6, 6, // 6x
+    2, 5, 0, 8, 4, 4, 6, 6, 2, 4, 2, 7, 4, 4, 7, 7, // 7x
+    2, 6, 2, 6, 3, 3, 3, 3, 2, 2, 2, 2, 4, 4, 4, 4, // 8x
+    2, 6, 0, 6, 4, 4, 4, 4, 2, 5, 2, 5, 5, 5, 5, 5, // 9x
+    2, 6, 2, 6, 3, 3, 3, 3, 2, 2, 2, 2, 4, 4, 4, 4, // Ax
+    2, 5, 0, 5, 4, 4, 4, 4, 2, 4, 2, 4, 4, 4, 4, 4, // Bx
+    2, 6, 2, 8, 3, 3, 5, 5, 2, 2, 2, 2, 4, 4, 6, 6, // Cx
+    2, 5, 0, 8, 4, 4, 6, 6, 2, 4, 2, 7, 4, 4, 7, 7, // Dx
+    2, 6, 2, 8, 3, 3, 5, 5, 2, 2, 2, 2, 4, 4, 6, 6, // Ex
+    2, 5, 0, 8, 4, 4, 6, 6, 2, 4, 2, 7, 4, 4, 7, 7, // Fx
+];
+
+// Base cycles of every opcode on the 65C02
+// The CMOS chips also take an extra cycle for ADC and SBC in decimal mode, and for shifts
+// with absolute, X addressing only when a page is crossed
+#[rustfmt::skip]
+const CMOS_CYCLES: [u8; 256] = [
+    7, 6, 2, 1, 5, 3, 5, 5, 3, 2, 2, 1, 6, 4, 6, 5, // 0x
+    2, 5, 5, 1, 5, 4, 6, 5, 2, 4, 2, 1, 6, 4, 6, 5, // 1x
+    6, 6, 2, 1, 3, 3, 5, 5, 4, 2, 2, 1, 4, 4, 6, 5, // 2x
+    2, 5, 5, 1, 4, 4, 6, 5, 2, 4, 2, 1, 4, 4, 6, 5, // 3x
+    6, 6, 2, 1, 3, 3, 5, 5, 3, 2, 2, 1, 3, 4, 6, 5, // 4x
+    2, 5, 5, 1, 4, 4, 6, 5, 2, 4, 3, 1, 8, 4, 6, 5, // 5x
+    6, 6, 2, 1, 3, 3, 5, 5, 4, 2, 2, 1, 6, 4, 6, 5, // 6x
+    2, 5, 5, 1, 4, 4, 6, 5, 2, 4, 4, 1, 6, 4, 6, 5, // 7x
+    2, 6, 2, 1, 3, 3, 3, 5, 2, 2, 2, 1, 4, 4, 4, 5, // 8x
+    2, 6, 5, 1, 4, 4, 4, 5, 2, 5, 2, 1, 4, 5, 5, 5, // 9x
+    2, 6, 2, 1, 3, 3, 3, 5, 2, 2, 2, 1, 4, 4, 4, 5, // Ax
+    2, 5, 5, 1, 4, 4, 4, 5, 2, 4, 2, 1, 4, 4, 4, 5, // Bx
+    2, 6, 2, 1, 3, 3, 5, 5, 2, 2, 2, 3, 4, 4, 6, 5, // Cx
+    2, 5, 5, 1, 4, 4, 6, 5, 2, 4, 3, 3, 4, 4, 7, 5, // Dx
+    2, 6, 2, 1, 3, 3, 5, 5, 2, 2, 2, 1, 4, 4, 6, 5, // Ex
+    2, 5, 5, 1, 4, 4, 6, 5, 2, 4, 4, 1, 4, 4, 7, 5, // Fx
+];
+
+// Get the base cycles of the given opcode on the given variant
+pub(crate) fn base_cycles(variant: Variant, opcode: u8) -> u8 {
+    match variant {
+        Variant::Nmos6502 | Variant::Ricoh2A03 => NMOS_CYCLES[opcode as usize],
+        // WAI and STP are single cycle no operations on the Rockwell chips
+        Variant::Rockwell65C02 if matches!(opcode, WAI | STP) => 1,
+        Variant::Rockwell65C02 | Variant::Wdc65C02 => CMOS_CYCLES[opcode as usize],
+    }
+}
+
+// Check if the operation takes an extra cycle when the indexing of its addressing mode
+// crosses a page, which are the reads and on the CMOS chips also the shifts
+fn page_cross_penalty(operation: Operation, mode: AddressingMode, cmos: bool) -> bool {
+    let reads = matches!(
+        operation,
+        Lda | Ldx | Ldy | And | Eor | Ora | Bit | Adc | Sbc | Cmp | Lax | Las | Nop
+    );
+    let shifts = matches!(operation, Asl | Lsr | Rol | Ror);
+    match mode {
+        AbsoluteX => reads || (cmos && shifts),
+        AbsoluteY | IndirectY => reads,
+        _ => false,
+    }
+}
+
+// Fill in the given rows on top of a decode table
+const fn table(mut table: DecodeTable, rows: &Rows) -> DecodeTable {
+    let mut i = 0;
+    while i < rows.len() {
+        let (opcode, operation, mode) = rows[i];
+        table[opcode as usize] = (operation, mode);
+        i += 1;
+    }
+    table
+}
@@ -1,7 +1,9 @@
+// Device on the address bus, addresses are offsets from the start of the region it is mapped to
+// They are 24 bits wide to cover the address space of the 65C816
 pub trait Device {
     fn read_type(&self) -> String;
-    fn read(&self, address: u16) -> u8;
-    fn write(&mut self, address: u16, data: u8);
+    fn read(&self, address: u32) -> u8;
+    fn write(&mut self, address: u32, data: u8);
 }
 
 // Ram device
@@ -22,11 +24,11 @@ impl Device for Ram {
         "Ram".to_string()
     }
 
-    fn read(&self, address: u16) -> u8 {
+    fn read(&self, address: u32) -> u8 {
         self.data[address as usize]
     }
 
-    fn write(&mut self, address: u16, data: u8) {
+    fn write(&mut self, address: u32, data: u8) {
         self.data[address as usize] = data;
     }
 }
@@ -39,11 +41,11 @@ impl Device for Stdout {
         "Stdout".to_string()
     }
 
-    fn read(&self, _address: u16) -> u8 {
+    fn read(&self, _address: u32) -> u8 {
         0
     }
 
-    fn write(&mut self, _address: u16, data: u8) {
+    fn write(&mut self, _address: u32, data: u8) {
         print!("{}", data as char);
     }
 }
@@ -1,22 +1,25 @@
 // Imports
-use crate::device::{Device, Ram};
+use crate::{
+    device::{Device, Ram},
+    error::Error,
+};
 
 // Structs
 
 // Struct for the memory regions
 struct Region {
-    start: u16,
-    end: u16,
+    start: u32,
+    end: u32,
     device: Box<dyn Device>,
 }
 
 // Implement the Region struct
 impl Region {
-    pub fn new(start: u16, end: u16, device: Box<dyn Device>) -> Self {
+    pub fn new(start: u32, end: u32, device: Box<dyn Device>) -> Self {
         Self { start, end, device }
     }
 
-    pub fn contains(&self, address: u16) -> bool {
+    pub fn contains(&self, address: u32) -> bool {
         address >= self.start && address <= self.end
     }
 }
@@ -24,57 +27,74 @@ impl Region {
 // Struct for the memory mapper
 pub struct DeviceMapper {
     regions: Vec<Region>,
+    size: u32, // Size of the address space
 }
 
 // Implement the DeviceMapper struct
 impl DeviceMapper {
-    pub fn new() -> Self {
-        let ram = Box::new(Ram::new(0xFFFF));
+    // Create a memory mapper backed by RAM for the whole address space of the given size
+    pub fn new(size: u32) -> Self {
+        let ram = Box::new(Ram::new(size as usize));
 
-        let regions = vec![Region::new(0x0000, 0xFFFF, ram)];
+        let regions = vec![Region::new(0x0000, size - 1, ram)];
 
-        Self { regions }
+        Self { regions, size }
     }
 
-    pub fn map(&mut self, start: u16, end: u16, device: Box<dyn Device>) {
+    pub fn map(&mut self, start: u32, end: u32, device: Box<dyn Device>) -> Result<(), Error> {
+        // The region has to fit in the address space
+        if start > end || end >= self.size {
+            return Err(Error::BadRegion { start, end });
+        }
+
         // Create a new region
         let region = Region::new(start, end, device);
 
         // add the region to the regions vector at the first position
         self.regions.insert(0, region);
+
+        Ok(())
     }
 
-    pub fn unmap(&mut self, start: u16, end: u16) {
+    pub fn unmap(&mut self, start: u32, end: u32) -> Result<(), Error> {
         // Find the index of the region with the given start and end addresses
         let index = self
             .regions
             .iter()
             .position(|r| r.start == start && r.end == end)
-            .unwrap();
+            .ok_or(Error::BadRegion { start, end })?;
 
         // Remove the region from the regions vector
         self.regions.remove(index);
+
+        Ok(())
     }
 
-    pub fn read(&self, address: u16) -> u8 {
+    pub fn read(&self, address: u32) -> Result<u8, Error> {
         // Find the region that contains the given address
-        let region = self.regions.iter().find(|r| r.contains(address)).unwrap();
+        let region = self
+            .regions
+            .iter()
+            .find(|r| r.contains(address))
+            .ok_or(Error::UnmappedAccess(address))?;
 
         // Read the data from the device in the region
         let offset = address - region.start;
-        region.device.read(offset)
+        Ok(region.device.read(offset))
     }
 
-    pub fn write(&mut self, address: u16, data: u8) {
+    pub fn write(&mut self, address: u32, data: u8) -> Result<(), Error> {
         // Find the region that contains the given address
         let region = self
             .regions
             .iter_mut()
             .find(|r| r.contains(address))
-            .unwrap();
+            .ok_or(Error::UnmappedAccess(address))?;
 
         // Write the data to the device in the region
         let offset = address - region.start;
         region.device.write(offset, data);
+
+        Ok(())
     }
 }
@@ -0,0 +1,27 @@
+use std::fmt;
+
+// Errors of the emulation, returned instead of panicking so the host can report a crashed guest
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Error {
+    UnknownOpcode { opcode: u8, pc: u32 }, // Opcode not known to the variant, at the given address
+    UnmappedAccess(u32),                   // Read or write of an address no device is mapped to
+    BadRegion { start: u32, end: u32 },    // Region that is empty, out of range or not mapped
+    Halted,                                // Step of a processor stopped by a JAM or STP opcode
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::UnknownOpcode { opcode, pc } => {
+                write!(f, "Unknown opcode {:#04X} at {:#06X}", opcode, pc)
+            }
+            Error::UnmappedAccess(address) => write!(f, "Unmapped access at {:#06X}", address),
+            Error::BadRegion { start, end } => {
+                write!(f, "Bad region {:#06X}-{:#06X}", start, end)
+            }
+            Error::Halted => write!(f, "Processor is halted"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
@@ -1,12 +1,19 @@
 // Public modules
+pub mod decode;
 pub mod device;
+pub mod error;
 pub mod opcodes;
 pub mod processor;
+pub mod processor65c816;
 
 // Private modules
+mod control;
 mod device_mapper;
 mod registers;
 
+// Public types of the private modules
+pub use registers::Status;
+
 // Unit tests
 #[cfg(test)]
 mod tests;
@@ -135,4 +135,590 @@ mod arithmetic {
     pub const ADC_ABSY: u8 = 0x79;
     pub const ADC_INDX: u8 = 0x61;
     pub const ADC_INDY: u8 = 0x71;
+
+    // Subtract with carry
+    pub const SBC_IM: u8 = 0xE9;
+    pub const SBC_ZP: u8 = 0xE5;
+    pub const SBC_ZPX: u8 = 0xF5;
+    pub const SBC_ABS: u8 = 0xED;
+    pub const SBC_ABSX: u8 = 0xFD;
+    pub const SBC_ABSY: u8 = 0xF9;
+    pub const SBC_INDX: u8 = 0xE1;
+    pub const SBC_INDY: u8 = 0xF1;
+
+    // Compare accumulator
+    pub const CMP_IM: u8 = 0xC9;
+    pub const CMP_ZP: u8 = 0xC5;
+    pub const CMP_ZPX: u8 = 0xD5;
+    pub const CMP_ABS: u8 = 0xCD;
+    pub const CMP_ABSX: u8 = 0xDD;
+    pub const CMP_ABSY: u8 = 0xD9;
+    pub const CMP_INDX: u8 = 0xC1;
+    pub const CMP_INDY: u8 = 0xD1;
+
+    // Compare X register
+    pub const CPX_IM: u8 = 0xE0;
+    pub const CPX_ZP: u8 = 0xE4;
+    pub const CPX_ABS: u8 = 0xEC;
+
+    // Compare Y register
+    pub const CPY_IM: u8 = 0xC0;
+    pub const CPY_ZP: u8 = 0xC4;
+    pub const CPY_ABS: u8 = 0xCC;
+}
+
+// Increment/decrement opcodes
+pub use increments::*;
+mod increments {
+    // Increment memory
+    pub const INC_ZP: u8 = 0xE6;
+    pub const INC_ZPX: u8 = 0xF6;
+    pub const INC_ABS: u8 = 0xEE;
+    pub const INC_ABSX: u8 = 0xFE;
+
+    // Increment X register
+    pub const INX: u8 = 0xE8;
+
+    // Increment Y register
+    pub const INY: u8 = 0xC8;
+
+    // Decrement memory
+    pub const DEC_ZP: u8 = 0xC6;
+    pub const DEC_ZPX: u8 = 0xD6;
+    pub const DEC_ABS: u8 = 0xCE;
+    pub const DEC_ABSX: u8 = 0xDE;
+
+    // Decrement X register
+    pub const DEX: u8 = 0xCA;
+
+    // Decrement Y register
+    pub const DEY: u8 = 0x88;
+}
+
+// Shift opcodes
+pub use shifts::*;
+mod shifts {
+    // Arithmetic shift left
+    pub const ASL_ACC: u8 = 0x0A;
+    pub const ASL_ZP: u8 = 0x06;
+    pub const ASL_ZPX: u8 = 0x16;
+    pub const ASL_ABS: u8 = 0x0E;
+    pub const ASL_ABSX: u8 = 0x1E;
+
+    // Logical shift right
+    pub const LSR_ACC: u8 = 0x4A;
+    pub const LSR_ZP: u8 = 0x46;
+    pub const LSR_ZPX: u8 = 0x56;
+    pub const LSR_ABS: u8 = 0x4E;
+    pub const LSR_ABSX: u8 = 0x5E;
+
+    // Rotate left
+    pub const ROL_ACC: u8 = 0x2A;
+    pub const ROL_ZP: u8 = 0x26;
+    pub const ROL_ZPX: u8 = 0x36;
+    pub const ROL_ABS: u8 = 0x2E;
+    pub const ROL_ABSX: u8 = 0x3E;
+
+    // Rotate right
+    pub const ROR_ACC: u8 = 0x6A;
+    pub const ROR_ZP: u8 = 0x66;
+    pub const ROR_ZPX: u8 = 0x76;
+    pub const ROR_ABS: u8 = 0x6E;
+    pub const ROR_ABSX: u8 = 0x7E;
+}
+
+// Jump/call opcodes
+pub use jumps::*;
+mod jumps {
+    // Jump to another location
+    pub const JMP_ABS: u8 = 0x4C;
+    pub const JMP_IND: u8 = 0x6C;
+
+    // Jump to subroutine
+    pub const JSR: u8 = 0x20;
+
+    // Return from subroutine
+    pub const RTS: u8 = 0x60;
+}
+
+// Branch opcodes
+pub use branches::*;
+mod branches {
+    // Branch if positive
+    pub const BPL: u8 = 0x10;
+
+    // Branch if minus
+    pub const BMI: u8 = 0x30;
+
+    // Branch if overflow clear
+    pub const BVC: u8 = 0x50;
+
+    // Branch if overflow set
+    pub const BVS: u8 = 0x70;
+
+    // Branch if carry clear
+    pub const BCC: u8 = 0x90;
+
+    // Branch if carry set
+    pub const BCS: u8 = 0xB0;
+
+    // Branch if not equal
+    pub const BNE: u8 = 0xD0;
+
+    // Branch if equal
+    pub const BEQ: u8 = 0xF0;
+}
+
+// Status flag opcodes
+pub use flags::*;
+mod flags {
+    // Clear carry flag
+    pub const CLC: u8 = 0x18;
+
+    // Clear decimal mode flag
+    pub const CLD: u8 = 0xD8;
+
+    // Clear interrupt disable flag
+    pub const CLI: u8 = 0x58;
+
+    // Clear overflow flag
+    pub const CLV: u8 = 0xB8;
+
+    // Set carry flag
+    pub const SEC: u8 = 0x38;
+
+    // Set decimal mode flag
+    pub const SED: u8 = 0xF8;
+
+    // Set interrupt disable flag
+    pub const SEI: u8 = 0x78;
+}
+
+// System opcodes
+pub use system::*;
+mod system {
+    // Force interrupt
+    pub const BRK: u8 = 0x00;
+
+    // No operation
+    pub const NOP: u8 = 0xEA;
+
+    // Return from interrupt
+    pub const RTI: u8 = 0x40;
+}
+
+// Undocumented opcodes of the NMOS 6502
+// Only the stable ones are emulated, they are executed when enabled with
+// `Processor::set_illegal_opcodes`, except for JAM which always halts the processor
+pub mod illegal {
+    // Load accumulator and X register
+    pub const LAX_ZP: u8 = 0xA7;
+    pub const LAX_ZPY: u8 = 0xB7;
+    pub const LAX_ABS: u8 = 0xAF;
+    pub const LAX_ABSY: u8 = 0xBF;
+    pub const LAX_INDX: u8 = 0xA3;
+    pub const LAX_INDY: u8 = 0xB3;
+
+    // Store accumulator AND X register
+    pub const SAX_ZP: u8 = 0x87;
+    pub const SAX_ZPY: u8 = 0x97;
+    pub const SAX_ABS: u8 = 0x8F;
+    pub const SAX_INDX: u8 = 0x83;
+
+    // Decrement memory and compare
+    pub const DCP_ZP: u8 = 0xC7;
+    pub const DCP_ZPX: u8 = 0xD7;
+    pub const DCP_ABS: u8 = 0xCF;
+    pub const DCP_ABSX: u8 = 0xDF;
+    pub const DCP_ABSY: u8 = 0xDB;
+    pub const DCP_INDX: u8 = 0xC3;
+    pub const DCP_INDY: u8 = 0xD3;
+
+    // Increment memory and subtract with carry
+    pub const ISC_ZP: u8 = 0xE7;
+    pub const ISC_ZPX: u8 = 0xF7;
+    pub const ISC_ABS: u8 = 0xEF;
+    pub const ISC_ABSX: u8 = 0xFF;
+    pub const ISC_ABSY: u8 = 0xFB;
+    pub const ISC_INDX: u8 = 0xE3;
+    pub const ISC_INDY: u8 = 0xF3;
+
+    // Arithmetic shift left memory and logical ORA
+    pub const SLO_ZP: u8 = 0x07;
+    pub const SLO_ZPX: u8 = 0x17;
+    pub const SLO_ABS: u8 = 0x0F;
+    pub const SLO_ABSX: u8 = 0x1F;
+    pub const SLO_ABSY: u8 = 0x1B;
+    pub const SLO_INDX: u8 = 0x03;
+    pub const SLO_INDY: u8 = 0x13;
+
+    // Rotate left memory and logical AND
+    pub const RLA_ZP: u8 = 0x27;
+    pub const RLA_ZPX: u8 = 0x37;
+    pub const RLA_ABS: u8 = 0x2F;
+    pub const RLA_ABSX: u8 = 0x3F;
+    pub const RLA_ABSY: u8 = 0x3B;
+    pub const RLA_INDX: u8 = 0x23;
+    pub const RLA_INDY: u8 = 0x33;
+
+    // Logical shift right memory and logical EOR
+    pub const SRE_ZP: u8 = 0x47;
+    pub const SRE_ZPX: u8 = 0x57;
+    pub const SRE_ABS: u8 = 0x4F;
+    pub const SRE_ABSX: u8 = 0x5F;
+    pub const SRE_ABSY: u8 = 0x5B;
+    pub const SRE_INDX: u8 = 0x43;
+    pub const SRE_INDY: u8 = 0x53;
+
+    // Rotate right memory and add with carry
+    pub const RRA_ZP: u8 = 0x67;
+    pub const RRA_ZPX: u8 = 0x77;
+    pub const RRA_ABS: u8 = 0x6F;
+    pub const RRA_ABSX: u8 = 0x7F;
+    pub const RRA_ABSY: u8 = 0x7B;
+    pub const RRA_INDX: u8 = 0x63;
+    pub const RRA_INDY: u8 = 0x73;
+
+    // Logical AND and copy bit 7 to carry
+    pub const ANC_IM_0B: u8 = 0x0B;
+    pub const ANC_IM_2B: u8 = 0x2B;
+
+    // Logical AND and logical shift right accumulator
+    pub const ALR_IM: u8 = 0x4B;
+
+    // Logical AND and rotate right accumulator
+    pub const ARR_IM: u8 = 0x6B;
+
+    // Accumulator AND X register minus value into X register
+    pub const SBX_IM: u8 = 0xCB;
+
+    // Subtract with carry, same as SBC_IM
+    pub const USBC_IM: u8 = 0xEB;
+
+    // Halt the processor until it is reset, always emulated
+    pub const JAM_02: u8 = 0x02;
+    pub const JAM_12: u8 = 0x12;
+    pub const JAM_22: u8 = 0x22;
+    pub const JAM_32: u8 = 0x32;
+    pub const JAM_42: u8 = 0x42;
+    pub const JAM_52: u8 = 0x52;
+    pub const JAM_62: u8 = 0x62;
+    pub const JAM_72: u8 = 0x72;
+    pub const JAM_92: u8 = 0x92;
+    pub const JAM_B2: u8 = 0xB2;
+    pub const JAM_D2: u8 = 0xD2;
+    pub const JAM_F2: u8 = 0xF2;
+
+    // No operation, implied
+    pub const NOP_1A: u8 = 0x1A;
+    pub const NOP_3A: u8 = 0x3A;
+    pub const NOP_5A: u8 = 0x5A;
+    pub const NOP_7A: u8 = 0x7A;
+    pub const NOP_DA: u8 = 0xDA;
+    pub const NOP_FA: u8 = 0xFA;
+
+    // No operation, reads an immediate operand
+    pub const NOP_IM_80: u8 = 0x80;
+    pub const NOP_IM_82: u8 = 0x82;
+    pub const NOP_IM_89: u8 = 0x89;
+    pub const NOP_IM_C2: u8 = 0xC2;
+    pub const NOP_IM_E2: u8 = 0xE2;
+
+    // No operation, reads a zero page operand
+    pub const NOP_ZP_04: u8 = 0x04;
+    pub const NOP_ZP_44: u8 = 0x44;
+    pub const NOP_ZP_64: u8 = 0x64;
+
+    // No operation, reads a zero page, X operand
+    pub const NOP_ZPX_14: u8 = 0x14;
+    pub const NOP_ZPX_34: u8 = 0x34;
+    pub const NOP_ZPX_54: u8 = 0x54;
+    pub const NOP_ZPX_74: u8 = 0x74;
+    pub const NOP_ZPX_D4: u8 = 0xD4;
+    pub const NOP_ZPX_F4: u8 = 0xF4;
+
+    // No operation, reads an absolute operand
+    pub const NOP_ABS_0C: u8 = 0x0C;
+
+    // No operation, reads an absolute, X operand
+    pub const NOP_ABSX_1C: u8 = 0x1C;
+    pub const NOP_ABSX_3C: u8 = 0x3C;
+    pub const NOP_ABSX_5C: u8 = 0x5C;
+    pub const NOP_ABSX_7C: u8 = 0x7C;
+    pub const NOP_ABSX_DC: u8 = 0xDC;
+    pub const NOP_ABSX_FC: u8 = 0xFC;
+
+    // Unstable opcodes, described by the instruction info but not emulated
+    // ANE and LXA depend on the chip, SHA, SHX, SHY and TAS on the high byte of the address
+    pub const ANE_IM: u8 = 0x8B;
+    pub const LXA_IM: u8 = 0xAB;
+    pub const SHA_INDY: u8 = 0x93;
+    pub const SHA_ABSY: u8 = 0x9F;
+    pub const SHX_ABSY: u8 = 0x9E;
+    pub const SHY_ABSX: u8 = 0x9C;
+    pub const TAS_ABSY: u8 = 0x9B;
+    pub const LAS_ABSY: u8 = 0xBB;
+}
+
+// Opcodes added by the 65C02
+// They are only executed by the CMOS variants, see `processor::Variant`
+pub mod cmos {
+    // Load/store with the (zero page) addressing mode
+    pub const LDA_ZPI: u8 = 0xB2;
+    pub const STA_ZPI: u8 = 0x92;
+
+    // Store zero
+    pub const STZ_ZP: u8 = 0x64;
+    pub const STZ_ZPX: u8 = 0x74;
+    pub const STZ_ABS: u8 = 0x9C;
+    pub const STZ_ABSX: u8 = 0x9E;
+
+    // Push X register on stack
+    pub const PHX: u8 = 0xDA;
+
+    // Push Y register on stack
+    pub const PHY: u8 = 0x5A;
+
+    // Pull X register from stack
+    pub const PLX: u8 = 0xFA;
+
+    // Pull Y register from stack
+    pub const PLY: u8 = 0x7A;
+
+    // Logical operations with the (zero page) addressing mode
+    pub const AND_ZPI: u8 = 0x32;
+    pub const EOR_ZPI: u8 = 0x52;
+    pub const ORA_ZPI: u8 = 0x12;
+
+    // Bit test
+    pub const BIT_IM: u8 = 0x89;
+    pub const BIT_ZPX: u8 = 0x34;
+    pub const BIT_ABSX: u8 = 0x3C;
+
+    // Test and reset bits
+    pub const TRB_ZP: u8 = 0x14;
+    pub const TRB_ABS: u8 = 0x1C;
+
+    // Test and set bits
+    pub const TSB_ZP: u8 = 0x04;
+    pub const TSB_ABS: u8 = 0x0C;
+
+    // Arithmetic with the (zero page) addressing mode
+    pub const ADC_ZPI: u8 = 0x72;
+    pub const SBC_ZPI: u8 = 0xF2;
+    pub const CMP_ZPI: u8 = 0xD2;
+
+    // Increment accumulator
+    pub const INC_ACC: u8 = 0x1A;
+
+    // Decrement accumulator
+    pub const DEC_ACC: u8 = 0x3A;
+
+    // Jump indexed indirect
+    pub const JMP_INDX: u8 = 0x7C;
+
+    // Branch always
+    pub const BRA: u8 = 0x80;
+
+    // Bit instructions of the Rockwell and WDC chips
+
+    // Reset memory bit
+    pub const RMB0: u8 = 0x07;
+    pub const RMB1: u8 = 0x17;
+    pub const RMB2: u8 = 0x27;
+    pub const RMB3: u8 = 0x37;
+    pub const RMB4: u8 = 0x47;
+    pub const RMB5: u8 = 0x57;
+    pub const RMB6: u8 = 0x67;
+    pub const RMB7: u8 = 0x77;
+
+    // Set memory bit
+    pub const SMB0: u8 = 0x87;
+    pub const SMB1: u8 = 0x97;
+    pub const SMB2: u8 = 0xA7;
+    pub const SMB3: u8 = 0xB7;
+    pub const SMB4: u8 = 0xC7;
+    pub const SMB5: u8 = 0xD7;
+    pub const SMB6: u8 = 0xE7;
+    pub const SMB7: u8 = 0xF7;
+
+    // Branch on bit reset
+    pub const BBR0: u8 = 0x0F;
+    pub const BBR1: u8 = 0x1F;
+    pub const BBR2: u8 = 0x2F;
+    pub const BBR3: u8 = 0x3F;
+    pub const BBR4: u8 = 0x4F;
+    pub const BBR5: u8 = 0x5F;
+    pub const BBR6: u8 = 0x6F;
+    pub const BBR7: u8 = 0x7F;
+
+    // Branch on bit set
+    pub const BBS0: u8 = 0x8F;
+    pub const BBS1: u8 = 0x9F;
+    pub const BBS2: u8 = 0xAF;
+    pub const BBS3: u8 = 0xBF;
+    pub const BBS4: u8 = 0xCF;
+    pub const BBS5: u8 = 0xDF;
+    pub const BBS6: u8 = 0xEF;
+    pub const BBS7: u8 = 0xFF;
+
+    // Instructions only on the WDC chips
+
+    // Wait for interrupt
+    pub const WAI: u8 = 0xCB;
+
+    // Stop the processor
+    pub const STP: u8 = 0xDB;
+}
+
+// Opcodes added by the 65C816 on top of the 65C02 ones, without the Rockwell bit instructions
+// The new addressing modes use the suffixes `_SR` for stack relative, `_SRIY` for
+// (stack relative),Y, `_INDL` for [direct], `_INDLY` for [direct],Y, `_ABSL` for absolute long
+// and `_ABSLX` for absolute long,X
+pub mod w65c816 {
+    // Logical ORA
+    pub const ORA_SR: u8 = 0x03;
+    pub const ORA_INDL: u8 = 0x07;
+    pub const ORA_ABSL: u8 = 0x0f;
+    pub const ORA_SRIY: u8 = 0x13;
+    pub const ORA_INDLY: u8 = 0x17;
+    pub const ORA_ABSLX: u8 = 0x1f;
+
+    // Logical AND
+    pub const AND_SR: u8 = 0x23;
+    pub const AND_INDL: u8 = 0x27;
+    pub const AND_ABSL: u8 = 0x2f;
+    pub const AND_SRIY: u8 = 0x33;
+    pub const AND_INDLY: u8 = 0x37;
+    pub const AND_ABSLX: u8 = 0x3f;
+
+    // Logical EOR
+    pub const EOR_SR: u8 = 0x43;
+    pub const EOR_INDL: u8 = 0x47;
+    pub const EOR_ABSL: u8 = 0x4f;
+    pub const EOR_SRIY: u8 = 0x53;
+    pub const EOR_INDLY: u8 = 0x57;
+    pub const EOR_ABSLX: u8 = 0x5f;
+
+    // Add with carry
+    pub const ADC_SR: u8 = 0x63;
+    pub const ADC_INDL: u8 = 0x67;
+    pub const ADC_ABSL: u8 = 0x6f;
+    pub const ADC_SRIY: u8 = 0x73;
+    pub const ADC_INDLY: u8 = 0x77;
+    pub const ADC_ABSLX: u8 = 0x7f;
+
+    // Store accumulator
+    pub const STA_SR: u8 = 0x83;
+    pub const STA_INDL: u8 = 0x87;
+    pub const STA_ABSL: u8 = 0x8f;
+    pub const STA_SRIY: u8 = 0x93;
+    pub const STA_INDLY: u8 = 0x97;
+    pub const STA_ABSLX: u8 = 0x9f;
+
+    // Load accumulator
+    pub const LDA_SR: u8 = 0xa3;
+    pub const LDA_INDL: u8 = 0xa7;
+    pub const LDA_ABSL: u8 = 0xaf;
+    pub const LDA_SRIY: u8 = 0xb3;
+    pub const LDA_INDLY: u8 = 0xb7;
+    pub const LDA_ABSLX: u8 = 0xbf;
+
+    // Compare accumulator
+    pub const CMP_SR: u8 = 0xc3;
+    pub const CMP_INDL: u8 = 0xc7;
+    pub const CMP_ABSL: u8 = 0xcf;
+    pub const CMP_SRIY: u8 = 0xd3;
+    pub const CMP_INDLY: u8 = 0xd7;
+    pub const CMP_ABSLX: u8 = 0xdf;
+
+    // Subtract with carry
+    pub const SBC_SR: u8 = 0xe3;
+    pub const SBC_INDL: u8 = 0xe7;
+    pub const SBC_ABSL: u8 = 0xef;
+    pub const SBC_SRIY: u8 = 0xf3;
+    pub const SBC_INDLY: u8 = 0xf7;
+    pub const SBC_ABSLX: u8 = 0xff;
+
+    // Push direct page register on stack
+    pub const PHD: u8 = 0x0B;
+
+    // Push data bank register on stack
+    pub const PHB: u8 = 0x8B;
+
+    // Push program bank register on stack
+    pub const PHK: u8 = 0x4B;
+
+    // Pull direct page register from stack
+    pub const PLD: u8 = 0x2B;
+
+    // Pull data bank register from stack
+    pub const PLB: u8 = 0xAB;
+
+    // Push effective absolute address
+    pub const PEA: u8 = 0xF4;
+
+    // Push effective indirect address
+    pub const PEI: u8 = 0xD4;
+
+    // Push effective relative address
+    pub const PER: u8 = 0x62;
+
+    // Transfer accumulator to direct page register
+    pub const TCD: u8 = 0x5B;
+
+    // Transfer direct page register to accumulator
+    pub const TDC: u8 = 0x7B;
+
+    // Transfer accumulator to stack pointer
+    pub const TCS: u8 = 0x1B;
+
+    // Transfer stack pointer to accumulator
+    pub const TSC: u8 = 0x3B;
+
+    // Transfer X to Y
+    pub const TXY: u8 = 0x9B;
+
+    // Transfer Y to X
+    pub const TYX: u8 = 0xBB;
+
+    // Exchange the B and A accumulators
+    pub const XBA: u8 = 0xEB;
+
+    // Exchange carry and emulation flags
+    pub const XCE: u8 = 0xFB;
+
+    // Reset status bits
+    pub const REP: u8 = 0xC2;
+
+    // Set status bits
+    pub const SEP: u8 = 0xE2;
+
+    // Jump long
+    pub const JML: u8 = 0x5C;
+    pub const JML_IND: u8 = 0xDC;
+
+    // Jump to subroutine indexed indirect
+    pub const JSR_INDX: u8 = 0xFC;
+
+    // Jump to subroutine long
+    pub const JSL: u8 = 0x22;
+
+    // Return from subroutine long
+    pub const RTL: u8 = 0x6B;
+
+    // Branch always long
+    pub const BRL: u8 = 0x82;
+
+    // Block move next
+    pub const MVN: u8 = 0x54;
+
+    // Block move previous
+    pub const MVP: u8 = 0x44;
+
+    // Co-processor interrupt
+    pub const COP: u8 = 0x02;
+
+    // Reserved for future expansion, a two byte no operation
+    pub const WDM: u8 = 0x42;
 }
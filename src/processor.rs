@@ -1,71 +1,602 @@
+use std::{cmp::Ordering, fmt};
+
 use crate::{
+    control::{Control, Entry, Saved},
+    decode::{
+        self,
+        AddressingMode::{self, *},
+        DecodeTable,
+        Operation::*,
+        NMOS_TABLE, NMOS_UNDOCUMENTED_TABLE, ROCKWELL_TABLE, WDC_TABLE,
+    },
     device::Device,
     device_mapper::DeviceMapper,
-    opcodes::*,
+    error::Error,
     registers::{Registers, Status},
 };
 
+// Interrupt vectors
+const NMI_VECTOR: u16 = 0xFFFA;
+const RESET_VECTOR: u16 = 0xFFFC;
+const IRQ_VECTOR: u16 = 0xFFFE;
+
+// Cycles taken by the reset sequence and by servicing an interrupt
+const INTERRUPT_CYCLES: u64 = 7;
+
+// Variants of the processor
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Variant {
+    Nmos6502,      // The original NMOS 6502
+    Ricoh2A03,     // The Ricoh 2A03 of the NES, an NMOS 6502 without decimal mode
+    Rockwell65C02, // The Rockwell 65C02, the CMOS version with the bit instructions
+    Wdc65C02,      // The WDC 65C02, the Rockwell instructions plus WAI and STP
+}
+
+// Outcome of a branch instruction
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Branch {
+    NotTaken,    // Condition was false, execution continues after the branch
+    Taken,       // Branched to a target on the same page
+    PageCrossed, // Branched to a target on another page
+}
+
+// Why a run of the processor stopped
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StopReason {
+    BudgetExhausted, // Ran for the given number of cycles or instructions
+    Breakpoint,      // Reached the given address or the given condition holds
+    Halted,          // Stopped by a JAM or STP opcode
+    Waiting,         // Waiting for an interrupt that is not requested
+    Error(Error),    // An instruction failed
+}
+
+// Registers of the processor, for inspecting and changing it from the outside
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CpuState {
+    pub pc: u16,        // Program counter
+    pub sp: u8,         // Stack pointer
+    pub acc: u8,        // Accumulator
+    pub x: u8,          // X register
+    pub y: u8,          // Y register
+    pub status: Status, // Status register
+}
+
+// Show the registers on one line, e.g. `A=42 X=00 Y=00 SP=FD P=nv-bdIzc PC=0800`
+impl fmt::Display for CpuState {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "A={:02X} X={:02X} Y={:02X} SP={:02X} P={} PC={:04X}",
+            self.acc, self.x, self.y, self.sp, self.status, self.pc
+        )
+    }
+}
+
+// State of the processor before a step, restored when the step fails
+// The registers are also restored on every cycle of an instruction in flight
+#[derive(Clone, Copy)]
+struct Snapshot {
+    registers: Registers,
+    last_branch: Option<Branch>,
+    control: Saved,
+    cycles: u64,
+}
+
+// An instruction run one cycle at a time
+// Every cycle replays the instruction from the snapshot, the accesses of earlier cycles
+// come from the log and only the access of the current cycle goes to the bus
+struct InFlight {
+    snapshot: Snapshot,
+    entry: Entry,
+    log: Vec<u8>,    // Bytes read or written on the bus so far
+    cycle: usize,    // Cycle of the instruction being run, counting from 0
+    accesses: usize, // Accesses done by the current replay
+}
+
 pub struct Processor {
     registers: Registers,
     device_mapper: DeviceMapper,
+    variant: Variant,
+    jmp_indirect_bug: bool,
+    illegal_opcodes: bool,
+    last_branch: Option<Branch>,
+    control: Control,
+    cycles: u64,                 // Cycles run since power on
+    in_flight: Option<InFlight>, // Instruction partially run by step_cycle
 }
 
 impl Processor {
     // Public functions
 
-    // Create a new processor with the given program loaded into RAM
+    // Create a new processor with the given program loaded into RAM at $0800
+    // The reset vector is pointed at the program before the processor is reset
     pub fn new(program: Vec<u8>) -> Self {
-        // Create RAM (divice_mapper) and registers
-        let mut registers = Registers::new();
-        let mut device_mapper = DeviceMapper::new();
+        let offset: u16 = 0x0800;
+
+        let mut processor = Self::power_on();
+        processor.load(offset, &program);
+        processor.load(RESET_VECTOR, &offset.to_le_bytes());
+        processor.reset();
+
+        processor
+    }
+
+    // Create a new processor with the given memory image loaded into RAM at the given address
+    // The processor is reset, so it starts at the address in the reset vector of the image
+    pub fn with_image(image: Vec<u8>, address: u16) -> Self {
+        let mut processor = Self::power_on();
+        processor.load(address, &image);
+        processor.reset();
+
+        processor
+    }
 
-        // Load the program
-        let offset = 0x0800;
-        registers.pc = offset;
-        for (i, byte) in program.iter().enumerate() {
-            device_mapper.write((i + offset as usize) as u16, *byte);
+    // Select the variant of the processor, meant to be chained on a constructor
+    // e.g. `Processor::new(program).with_variant(Variant::Wdc65C02)`
+    pub fn with_variant(mut self, variant: Variant) -> Self {
+        self.variant = variant;
+        self.jmp_indirect_bug = !self.is_cmos();
+
+        // The CMOS chips leave reset with decimal mode cleared
+        if self.is_cmos() {
+            self.registers.status.remove(Status::DECIMAL);
         }
 
-        Self {
-            registers,
-            device_mapper,
+        self
+    }
+
+    // Get the variant of the processor
+    pub fn variant(&self) -> Variant {
+        self.variant
+    }
+
+    // Reset the processor like the RESET line does
+    // The reset sequence goes through the motions of an interrupt without writing to the stack,
+    // so the stack pointer ends up 3 lower, then the program counter is loaded from $FFFC/$FFFD
+    pub fn reset(&mut self) {
+        self.in_flight = None;
+        self.last_branch = None;
+
+        self.registers.sp = self.registers.sp.wrapping_sub(3);
+        self.registers.status.insert(Status::INTERRUPT);
+        if self.is_cmos() {
+            self.registers.status.remove(Status::DECIMAL);
         }
+
+        let low = self.read(RESET_VECTOR);
+        let high = self.read(RESET_VECTOR.wrapping_add(1));
+        self.registers.pc = u16::from_le_bytes([low, high]);
+        self.control.reset();
+
+        self.cycles += INTERRUPT_CYCLES;
+    }
+
+    // Enable or disable the NMOS page boundary bug of JMP (indirect)
+    // When disabled the pointer high byte is fetched like on the CMOS chips
+    pub fn set_jmp_indirect_bug(&mut self, enabled: bool) {
+        self.jmp_indirect_bug = enabled;
+    }
+
+    // Enable or disable the undocumented NMOS opcodes listed in `opcodes::illegal`
+    // When disabled they are unknown opcodes
+    pub fn set_illegal_opcodes(&mut self, enabled: bool) {
+        self.illegal_opcodes = enabled;
     }
 
     // Map a device to the given address range
-    pub fn map(&mut self, start: u16, end: u16, device: Box<dyn Device>) {
-        self.device_mapper.map(start, end, device);
+    pub fn map(&mut self, start: u16, end: u16, device: Box<dyn Device>) -> Result<(), Error> {
+        self.device_mapper.map(start as u32, end as u32, device)
     }
 
-    // Unmap a device from the given address range
-    pub fn unmap(&mut self, start: u16, end: u16) {
-        self.device_mapper.unmap(start, end);
+    // Unmap the device mapped to exactly the given address range
+    pub fn unmap(&mut self, start: u16, end: u16) -> Result<(), Error> {
+        self.device_mapper.unmap(start as u32, end as u32)
     }
 
-    // Run the processor until the program crashes
-    pub fn run(&mut self) {
-        loop {
-            let instruction = self.fetch8();
-            self.execute(instruction);
+    // Run the processor until it is halted or waiting for an interrupt that is not requested
+    // Stop at the first instruction that fails
+    pub fn run(&mut self) -> Result<(), Error> {
+        while self.control.is_running() {
+            self.step()?;
+        }
+
+        Ok(())
+    }
+
+    // Run the processor until the given number of cycles have passed
+    // Instructions are not split, so the last one can end past the budget
+    pub fn run_for_cycles(&mut self, cycles: u64) -> StopReason {
+        let end = self.cycles.saturating_add(cycles);
+        self.run_with(|processor| (processor.cycles >= end).then_some(StopReason::BudgetExhausted))
+    }
+
+    // Run the processor for the given number of instructions,
+    // an interrupt serviced instead of an instruction counts as one
+    pub fn run_for_instructions(&mut self, instructions: u64) -> StopReason {
+        let mut remaining = instructions;
+        self.run_with(|_| {
+            if remaining == 0 {
+                return Some(StopReason::BudgetExhausted);
+            }
+            remaining -= 1;
+            None
+        })
+    }
+
+    // Run the processor until it is about to run the instruction at the given address
+    pub fn run_until_pc(&mut self, pc: u16) -> StopReason {
+        self.run_with(|processor| (processor.registers.pc == pc).then_some(StopReason::Breakpoint))
+    }
+
+    // Run the processor until the given condition holds, it is checked before every instruction
+    // e.g. `processor.run_until(|cpu| cpu.cycles() >= 1000)`
+    pub fn run_until(&mut self, mut condition: impl FnMut(&Self) -> bool) -> StopReason {
+        self.run_with(|processor| condition(processor).then_some(StopReason::Breakpoint))
+    }
+
+    // Assert or deassert the IRQ line
+    pub fn set_irq(&mut self, asserted: bool) {
+        self.control.set_irq(asserted);
+    }
+
+    // Assert or deassert the NMI line
+    pub fn set_nmi(&mut self, asserted: bool) {
+        self.control.set_nmi(asserted);
+    }
+
+    // Run the processor for one instruction and return the cycles it took
+    // A pending interrupt is serviced instead of the next instruction
+    // Nothing happens while the processor is waiting without an interrupt request
+    // An instruction started by step_cycle is run to the end instead
+    // An instruction that fails leaves the registers as they were before it
+    pub fn step(&mut self) -> Result<u8, Error> {
+        if self.control.halted {
+            return Err(Error::Halted);
+        }
+
+        if let Some(in_flight) = &self.in_flight {
+            let start = in_flight.snapshot.cycles;
+            while !self.step_cycle()? {}
+            return Ok((self.cycles - start) as u8);
+        }
+
+        let snapshot = self.snapshot();
+        let Some(entry) = self.next_entry() else {
+            return Ok(0);
+        };
+
+        self.enter(entry);
+
+        if let Some(error) = self.control.take_fault() {
+            self.restore(snapshot);
+            return Err(error);
+        }
+
+        Ok((self.cycles - snapshot.cycles) as u8)
+    }
+
+    // Run the processor for one cycle, doing exactly one access on the bus
+    // Return true when the instruction or interrupt ended on this cycle,
+    // or nothing happened because the processor is waiting
+    // An instruction that fails is dropped and leaves the registers as they were before it
+    pub fn step_cycle(&mut self) -> Result<bool, Error> {
+        if self.control.halted {
+            return Err(Error::Halted);
+        }
+
+        let mut in_flight = match self.in_flight.take() {
+            Some(in_flight) => in_flight,
+            None => {
+                let snapshot = self.snapshot();
+                let Some(entry) = self.next_entry() else {
+                    return Ok(true);
+                };
+                InFlight {
+                    snapshot,
+                    entry,
+                    log: Vec::new(),
+                    cycle: 0,
+                    accesses: 0,
+                }
+            }
+        };
+
+        // Replay the instruction up to the access of this cycle
+        in_flight.cycle = in_flight.log.len();
+        in_flight.accesses = 0;
+        self.cycles = in_flight.snapshot.cycles;
+        let entry = in_flight.entry;
+        self.in_flight = Some(in_flight);
+        self.enter(entry);
+
+        let in_flight = self.in_flight.take().unwrap();
+        if let Some(error) = self.control.take_fault() {
+            self.restore(in_flight.snapshot);
+            return Err(error);
+        }
+        if in_flight.accesses == in_flight.log.len() {
+            return Ok(true);
+        }
+
+        // Go back to the start of the instruction, only the cycles done so far count
+        // The step was entered, so there is no branch yet and the processor is running
+        self.registers = in_flight.snapshot.registers;
+        self.last_branch = None;
+        self.control.halted = false;
+        self.control.waiting = false;
+        self.cycles = in_flight.snapshot.cycles + in_flight.log.len() as u64;
+        self.in_flight = Some(in_flight);
+
+        Ok(false)
+    }
+
+    // Get the number of cycles run since power on, including the reset sequences
+    pub fn cycles(&self) -> u64 {
+        self.cycles
+    }
+
+    // Check if the processor is halted, only a reset gets it running again
+    pub fn is_halted(&self) -> bool {
+        self.control.halted
+    }
+
+    // Check if the processor is waiting for an interrupt after a WAI
+    pub fn is_waiting(&self) -> bool {
+        self.control.waiting
+    }
+
+    // Get the registers of the processor
+    pub fn state(&self) -> CpuState {
+        CpuState {
+            pc: self.registers.pc,
+            sp: self.registers.sp,
+            acc: self.registers.acc,
+            x: self.registers.x,
+            y: self.registers.y,
+            status: self.registers.status,
         }
     }
 
-    // Run the processor for one instruction
-    pub fn step(&mut self) {
-        let instruction = self.fetch8();
-        self.execute(instruction);
+    // Set the registers of the processor
+    // An instruction started by step_cycle is dropped, the next step starts a new one
+    pub fn set_state(&mut self, state: CpuState) {
+        self.in_flight = None;
+        self.registers.pc = state.pc;
+        self.registers.sp = state.sp;
+        self.registers.acc = state.acc;
+        self.registers.x = state.x;
+        self.registers.y = state.y;
+        self.registers.status = state.status;
+    }
+
+    // Read a byte from the device mapped to the given address, without running a cycle
+    pub fn read_memory(&self, address: u16) -> Result<u8, Error> {
+        self.device_mapper.read(address as u32)
+    }
+
+    // Write a byte to the device mapped to the given address, without running a cycle
+    pub fn write_memory(&mut self, address: u16, byte: u8) -> Result<(), Error> {
+        self.device_mapper.write(address as u32, byte)
+    }
+
+    // Get the outcome of the last instruction if it was a branch
+    pub fn last_branch(&self) -> Option<Branch> {
+        self.last_branch
     }
 
     // Private functions
 
+    // Check if the processor is one of the CMOS variants
+    fn is_cmos(&self) -> bool {
+        matches!(self.variant, Variant::Rockwell65C02 | Variant::Wdc65C02)
+    }
+
+    // Check if ADC and SBC do decimal arithmetic, the decimal flag can be set on every variant
+    // but the Ricoh 2A03 has the decimal circuitry disconnected
+    fn decimal_mode(&self) -> bool {
+        self.registers.status.contains(Status::DECIMAL) && self.variant != Variant::Ricoh2A03
+    }
+
+    // Get the base cycles of the given opcode
+    fn base_cycles(&self, opcode: u8) -> u8 {
+        decode::base_cycles(self.variant, opcode)
+    }
+
+    // Add the extra cycle of an indexed read that crosses a page
+    fn page_cross_penalty(&mut self, base: u16, address: u16) {
+        if base & 0xFF00 != address & 0xFF00 {
+            self.cycles += 1;
+            self.indexed_dummy_read(base, address);
+        }
+    }
+
+    // Run the processor until the given check, done before every instruction, gives a reason
+    // to stop, or the processor cannot go on
+    fn run_with(&mut self, mut check: impl FnMut(&Self) -> Option<StopReason>) -> StopReason {
+        loop {
+            if let Some(reason) = check(self) {
+                return reason;
+            }
+            if self.control.halted {
+                return StopReason::Halted;
+            }
+            if !self.control.is_running() {
+                return StopReason::Waiting;
+            }
+            if let Err(error) = self.step() {
+                return StopReason::Error(error);
+            }
+        }
+    }
+
+    // Decide what the next step does, or nothing when the processor is halted or asleep
+    fn next_entry(&mut self) -> Option<Entry> {
+        self.last_branch = None;
+        let interrupt_disabled = self.registers.status.contains(Status::INTERRUPT);
+        self.control.next_entry(interrupt_disabled)
+    }
+
+    // Run the given step from start to end
+    fn enter(&mut self, entry: Entry) {
+        match entry {
+            Entry::Nmi => {
+                self.interrupt_sequence(NMI_VECTOR);
+            }
+            Entry::Irq => {
+                self.interrupt_sequence(IRQ_VECTOR);
+            }
+            Entry::Instruction => {
+                let instruction = self.fetch8();
+                self.cycles += self.base_cycles(instruction) as u64;
+                self.execute(instruction);
+            }
+        }
+    }
+
+    // Service an interrupt through the given vector
+    // The next opcode is read twice and thrown away before the interrupt is taken
+    fn interrupt_sequence(&mut self, vector: u16) {
+        self.implied();
+        self.implied();
+        self.interrupt(vector, false);
+        self.cycles += INTERRUPT_CYCLES;
+    }
+
+    // Take a snapshot of the state an instruction changes, except memory
+    fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            registers: self.registers,
+            last_branch: self.last_branch,
+            control: self.control.save(),
+            cycles: self.cycles,
+        }
+    }
+
+    // Go back to the state of the given snapshot, as if the failed step never started
+    fn restore(&mut self, snapshot: Snapshot) {
+        self.registers = snapshot.registers;
+        self.last_branch = snapshot.last_branch;
+        self.control.restore(snapshot.control);
+        self.cycles = snapshot.cycles;
+    }
+
+    // Create a processor in its power-on state, before the first reset
+    fn power_on() -> Self {
+        // Create RAM (divice_mapper) and registers
+        let registers = Registers::new();
+        let device_mapper = DeviceMapper::new(0x10000);
+
+        Self {
+            registers,
+            device_mapper,
+            variant: Variant::Nmos6502,
+            jmp_indirect_bug: true,
+            illegal_opcodes: false,
+            last_branch: None,
+            control: Control::new(),
+            cycles: 0,
+            in_flight: None,
+        }
+    }
+
+    // Write the given bytes to memory starting at the given address
+    fn load(&mut self, address: u16, data: &[u8]) {
+        for (i, byte) in data.iter().enumerate() {
+            self.write(address.wrapping_add(i as u16), *byte);
+        }
+    }
+
     // Read a byte from the given address
+    // While step_cycle replays an instruction, the reads of earlier cycles come from the log
+    // and the reads past the current cycle do not happen
     fn read(&mut self, address: u16) -> u8 {
-        self.device_mapper.read(address)
+        let Some(in_flight) = &mut self.in_flight else {
+            return self.bus_read(address);
+        };
+
+        let index = in_flight.accesses;
+        in_flight.accesses += 1;
+        match index.cmp(&in_flight.cycle) {
+            Ordering::Less => in_flight.log[index],
+            Ordering::Equal => {
+                let byte = self.bus_read(address);
+                if let Some(in_flight) = &mut self.in_flight {
+                    in_flight.log.push(byte);
+                }
+                byte
+            }
+            Ordering::Greater => 0,
+        }
     }
 
     // Write a byte to the given address
+    // While step_cycle replays an instruction, only the write of the current cycle happens
     fn write(&mut self, address: u16, byte: u8) {
-        self.device_mapper.write(address, byte);
+        let Some(in_flight) = &mut self.in_flight else {
+            self.bus_write(address, byte);
+            return;
+        };
+
+        let index = in_flight.accesses;
+        in_flight.accesses += 1;
+        if index == in_flight.cycle {
+            in_flight.log.push(byte);
+            self.bus_write(address, byte);
+        }
+    }
+
+    // Read a byte from the device mapped to the given address, an unmapped address reads as zero
+    // and fails the instruction
+    fn bus_read(&mut self, address: u16) -> u8 {
+        self.device_mapper
+            .read(address as u32)
+            .unwrap_or_else(|error| {
+                self.control.fail(error);
+                0
+            })
+    }
+
+    // Write a byte to the device mapped to the given address, an unmapped address
+    // fails the instruction
+    fn bus_write(&mut self, address: u16, byte: u8) {
+        if let Err(error) = self.device_mapper.write(address as u32, byte) {
+            self.control.fail(error);
+        }
+    }
+
+    // Read the byte after the opcode, single byte instructions do this
+    // on the cycle after the opcode fetch and throw it away
+    fn implied(&mut self) -> u8 {
+        self.read(self.registers.pc)
+    }
+
+    // Go through the cycles before the first pull, reading the byte after the opcode
+    // and the top of the stack
+    fn pull_setup(&mut self) {
+        self.implied();
+        self.read(0x0100 + self.registers.sp as u16);
+    }
+
+    // Write the result of a read-modify-write instruction
+    // The NMOS chips write the unmodified value back first, the CMOS chips read it again instead
+    fn modify_write(&mut self, address: u16, value: u8, result: u8) {
+        if self.is_cmos() {
+            self.read(address);
+        } else {
+            self.write(address, value);
+        }
+        self.write(address, result)
+    }
+
+    // Read from an indexed address before its high byte is fixed up
+    // The CMOS chips read the last operand byte again instead
+    fn indexed_dummy_read(&mut self, base: u16, address: u16) {
+        let address = if self.is_cmos() {
+            self.registers.pc.wrapping_sub(1)
+        } else {
+            (base & 0xFF00) | (address & 0x00FF)
+        };
+        self.read(address);
     }
 
     // Fetch an 8-bit value from the program counter
@@ -74,16 +605,16 @@ impl Processor {
         let byte = self.read(self.registers.pc);
 
         // Increment the PC
-        self.registers.pc += 1;
+        self.registers.pc = self.registers.pc.wrapping_add(1);
 
         byte
     }
 
     // Fetch a 16-bit value from the program counter
     fn fetch16(&mut self) -> u16 {
-        // Read the two bytes at the PC
-        let high = self.fetch8();
+        // Read the two bytes at the PC (little endian, low byte first)
         let low = self.fetch8();
+        let high = self.fetch8();
 
         // Return as u16
         u16::from_le_bytes([low, high])
@@ -91,164 +622,246 @@ impl Processor {
 
     // Push a byte on the stack
     fn push(&mut self, byte: u8) {
-        // Decrement the stack pointer
-        self.registers.sp = self.registers.sp.wrapping_sub(1);
-
         // Write the byte to the stack
         let address = 0x0100 + self.registers.sp as u16;
         self.write(address, byte);
+
+        // Decrement the stack pointer
+        self.registers.sp = self.registers.sp.wrapping_sub(1);
     }
 
     // Pull a byte from the stack
     fn pull(&mut self) -> u8 {
-        // Read the byte from the stack
-        let address = 0x0100 + self.registers.sp as u16;
-        let byte = self.read(address);
-
         // Increment the stack pointer
         self.registers.sp = self.registers.sp.wrapping_add(1);
 
-        byte
+        // Read the byte from the stack
+        let address = 0x0100 + self.registers.sp as u16;
+        self.read(address)
     }
 
-    // Execute the given opcode
-    fn execute(&mut self, opcode: u8) {
-        match opcode {
-            // Load/Store
-
-            // Load accumulator
-            LDA_IM => self.lda_immediate(),
-            LDA_ZP => self.lda_zero_page(),
-            LDA_ZPX => self.lda_zero_page_x(),
-            LDA_ABS => self.lda_absolute(),
-            LDA_ABSX => self.lda_absolute_x(),
-            LDA_ABSY => self.lda_absolute_y(),
-            LDA_INDX => self.lda_indirect_x(),
-            LDA_INDY => self.lda_indirect_y(),
-
-            // Load X register
-            LDX_IM => self.ldx_immediate(),
-            LDX_ZP => self.ldx_zero_page(),
-            LDX_ZPY => self.ldx_zero_page_y(),
-            LDX_ABS => self.ldx_absolute(),
-            LDX_ABSY => self.ldx_absolute_y(),
-
-            // Load Y register
-            LDY_IM => self.ldy_immediate(),
-            LDY_ZP => self.ldy_zero_page(),
-            LDY_ZPX => self.ldy_zero_page_x(),
-            LDY_ABS => self.ldy_absolute(),
-            LDY_ABSX => self.ldy_absolute_x(),
-
-            // Store accumulator
-            STA_ZP => self.sta_zero_page(),
-            STA_ZPX => self.sta_zero_page_x(),
-            STA_ABS => self.sta_absolute(),
-            STA_ABSX => self.sta_absolute_x(),
-            STA_ABSY => self.sta_absolute_y(),
-            STA_INDX => self.sta_indirect_x(),
-            STA_INDY => self.sta_indirect_y(),
-
-            // Store X register
-            STX_ZP => self.stx_zero_page(),
-            STX_ZPY => self.stx_zero_page_y(),
-            STX_ABS => self.stx_absolute(),
-
-            // Store Y register
-            STY_ZP => self.sty_zero_page(),
-            STY_ZPX => self.sty_zero_page_x(),
-            STY_ABS => self.sty_absolute(),
+    // Push the program counter and status, then jump through the given vector
+    fn interrupt(&mut self, vector: u16, break_command: bool) {
+        let [low, high] = self.registers.pc.to_le_bytes();
+        self.push(high);
+        self.push(low);
 
-            // Transfer
+        // The break flag only exists on the stack, to tell BRK apart from an IRQ
+        let mut status = self.registers.status | Status::UNUSED;
+        status.set(Status::BREAK, break_command);
+        self.push(status.bits());
 
-            // Transfer accumulator to X register
-            TAX => self.tax(),
+        self.registers.status.insert(Status::INTERRUPT);
+        if self.is_cmos() {
+            self.registers.status.remove(Status::DECIMAL);
+        }
 
-            // Transfer accumulator to Y register
-            TAY => self.tay(),
+        let low = self.read(vector);
+        let high = self.read(vector.wrapping_add(1));
+        self.registers.pc = u16::from_le_bytes([low, high]);
+    }
 
-            // Transfer X register to accumulator
-            TXA => self.txa(),
+    // Execute the given opcode
+    fn execute(&mut self, opcode: u8) {
+        let (operation, mode) = self.decode_table()[opcode as usize];
+        match operation {
+            // Load/store
+            Lda => self.with_operand(mode, Self::lda),
+            Ldx => self.with_operand(mode, Self::ldx),
+            Ldy => self.with_operand(mode, Self::ldy),
+            Sta => self.with_address(mode, Self::sta),
+            Stx => self.with_address(mode, Self::stx),
+            Sty => self.with_address(mode, Self::sty),
+            Stz => self.with_address(mode, Self::stz),
 
-            // Transfer Y register to accumulator
-            TYA => self.tya(),
+            // Transfer
+            Tax => self.tax(),
+            Tay => self.tay(),
+            Txa => self.txa(),
+            Tya => self.tya(),
+            Tsx => self.tsx(),
+            Txs => self.txs(),
 
             // Stack
+            Pha => self.pha(),
+            Php => self.php(),
+            Pla => self.pla(),
+            Plp => self.plp(),
+            Phx => self.phx(),
+            Phy => self.phy(),
+            Plx => self.plx(),
+            Ply => self.ply(),
 
-            // Transfer stack pointer to X register
-            TSX => self.tsx(),
-
-            // Transfer X register to stack pointer
-            TXS => self.txs(),
-
-            // Push accumulator on stack
-            PHA => self.pha(),
+            // Logical
+            And => self.with_operand(mode, Self::and),
+            Eor => self.with_operand(mode, Self::eor),
+            Ora => self.with_operand(mode, Self::ora),
+            Bit if mode == Immediate => self.with_operand(mode, Self::bit_immediate),
+            Bit => self.with_operand(mode, Self::bit),
+            Trb => self.with_address(mode, Self::trb),
+            Tsb => self.with_address(mode, Self::tsb),
 
-            // Push processor status on stack
-            PHP => self.php(),
+            // Arithmetic
+            Adc => self.with_operand(mode, Self::adc),
+            Sbc => self.with_operand(mode, Self::sbc),
+            Cmp => self.with_operand(mode, Self::cmp),
+            Cpx => self.with_operand(mode, Self::cpx),
+            Cpy => self.with_operand(mode, Self::cpy),
+
+            // Increments/decrements
+            Inc if mode == Accumulator => self.inc_accumulator(),
+            Inc => self.with_address(mode, Self::inc),
+            Inx => self.inx(),
+            Iny => self.iny(),
+            Dec if mode == Accumulator => self.dec_accumulator(),
+            Dec => self.with_address(mode, Self::dec),
+            Dex => self.dex(),
+            Dey => self.dey(),
+
+            // Shifts
+            Asl => self.shift(mode, Self::asl),
+            Lsr => self.shift(mode, Self::lsr),
+            Rol => self.shift(mode, Self::rol),
+            Ror => self.shift(mode, Self::ror),
+
+            // Jumps
+            Jmp => self.with_address(mode, Self::jmp),
+            Jsr => self.jsr(),
+            Rts => self.rts(),
+
+            // Branches
+            Bpl => self.bpl(),
+            Bmi => self.bmi(),
+            Bvc => self.bvc(),
+            Bvs => self.bvs(),
+            Bcc => self.bcc(),
+            Bcs => self.bcs(),
+            Bne => self.bne(),
+            Beq => self.beq(),
+            Bra => self.bra(),
+
+            // Status flags
+            Clc => self.clc(),
+            Cld => self.cld(),
+            Cli => self.cli(),
+            Clv => self.clv(),
+            Sec => self.sec(),
+            Sed => self.sed(),
+            Sei => self.sei(),
+
+            // System
+            Brk => self.brk(),
+            Nop => self.with_operand(mode, Self::nop),
+            Rti => self.rti(),
+            Wai => self.wai(),
+            Stp => self.stp(),
+
+            // Bit instructions
+            Rmb(bit) => self.rmb(bit),
+            Smb(bit) => self.smb(bit),
+            Bbr(bit) => self.bbr(bit),
+            Bbs(bit) => self.bbs(bit),
+
+            // Undocumented opcodes
+            Lax => self.with_operand(mode, Self::lax),
+            Sax => self.with_address(mode, Self::sax),
+            Dcp => self.with_address(mode, Self::dcp),
+            Isc => self.with_address(mode, Self::isc),
+            Slo => self.with_address(mode, Self::slo),
+            Rla => self.with_address(mode, Self::rla),
+            Sre => self.with_address(mode, Self::sre),
+            Rra => self.with_address(mode, Self::rra),
+            Anc => self.with_operand(mode, Self::anc),
+            Alr => self.with_operand(mode, Self::alr),
+            Arr => self.with_operand(mode, Self::arr),
+            Sbx => self.with_operand(mode, Self::sbx),
+            Jam => self.jam(),
+
+            // CMOS no operations
+            NopSingle => {}
+            NopLong => self.nop_long(),
+
+            // Unknown opcode, the unstable undocumented opcodes are never in the decode tables
+            // of the processor
+            Unknown | Ane | Lxa | Sha | Shx | Shy | Tas | Las => {
+                let pc = self.registers.pc.wrapping_sub(1) as u32;
+                self.control.fail(Error::UnknownOpcode { opcode, pc });
+            }
+        }
+    }
 
-            // Pull accumulator from stack
-            PLA => self.pla(),
+    // Get the decode table of the variant
+    fn decode_table(&self) -> &'static DecodeTable {
+        match self.variant {
+            Variant::Nmos6502 | Variant::Ricoh2A03 if self.illegal_opcodes => {
+                &NMOS_UNDOCUMENTED_TABLE
+            }
+            Variant::Nmos6502 | Variant::Ricoh2A03 => &NMOS_TABLE,
+            Variant::Rockwell65C02 => &ROCKWELL_TABLE,
+            Variant::Wdc65C02 => &WDC_TABLE,
+        }
+    }
 
-            // Pull processor status from stack
-            PLP => self.plp(),
+    // Run the given operation on the operand of the addressing mode
+    fn with_operand(&mut self, mode: AddressingMode, operation: fn(&mut Self, u8)) {
+        let value = self.operand(mode);
+        operation(self, value)
+    }
 
-            // Logical
+    // Run the given operation on the address of the addressing mode
+    fn with_address(&mut self, mode: AddressingMode, operation: fn(&mut Self, u16)) {
+        let address = self.address(mode);
+        operation(self, address)
+    }
 
-            // Logical AND
-            AND_IM => self.and_immediate(),
-            AND_ZP => self.and_zero_page(),
-            AND_ZPX => self.and_zero_page_x(),
-            AND_ABS => self.and_absolute(),
-            AND_ABSX => self.and_absolute_x(),
-            AND_ABSY => self.and_absolute_y(),
-            AND_INDX => self.and_indirect_x(),
-            AND_INDY => self.and_indirect_y(),
-
-            // Logical EOR
-            EOR_IM => self.eor_immediate(),
-            EOR_ZP => self.eor_zero_page(),
-            EOR_ZPX => self.eor_zero_page_x(),
-            EOR_ABS => self.eor_absolute(),
-            EOR_ABSX => self.eor_absolute_x(),
-            EOR_ABSY => self.eor_absolute_y(),
-            EOR_INDX => self.eor_indirect_x(),
-            EOR_INDY => self.eor_indirect_y(),
-
-            // Logical ORA
-            ORA_IM => self.ora_immediate(),
-            ORA_ZP => self.ora_zero_page(),
-            ORA_ZPX => self.ora_zero_page_x(),
-            ORA_ABS => self.ora_absolute(),
-            ORA_ABSX => self.ora_absolute_x(),
-            ORA_ABSY => self.ora_absolute_y(),
-            ORA_INDX => self.ora_indirect_x(),
-            ORA_INDY => self.ora_indirect_y(),
-
-            // Bit test
-            BIT_ZP => self.bit_zero_page(),
-            BIT_ABS => self.bit_absolute(),
+    // Addressing modes
 
-            // Arithmetic
+    // Read the operand of the given addressing mode
+    fn operand(&mut self, mode: AddressingMode) -> u8 {
+        match mode {
+            Implied => self.implied(),
+            Accumulator => self.accumulator(),
+            Immediate => self.immediate(),
+            ZeroPage => self.zero_page_read(),
+            ZeroPageX => self.zero_page_x_read(),
+            ZeroPageY => self.zero_page_y_read(),
+            Absolute => self.absolute_read(),
+            AbsoluteX => self.absolute_x_read(),
+            AbsoluteY => self.absolute_y_read(),
+            IndirectX => self.indirect_x_read(),
+            IndirectY => self.indirect_y_read(),
+            ZeroPageIndirect => self.zero_page_indirect_read(),
+            Indirect | AbsoluteXIndirect | Relative | ZeroPageRelative => {
+                unreachable!("No operand to read with {:?} addressing", mode)
+            }
+        }
+    }
 
-            // Add with carry
-            ADC_IM => self.adc_immediate(),
-            ADC_ZP => self.adc_zero_page(),
-            ADC_ZPX => self.adc_zero_page_x(),
-            ADC_ABS => self.adc_absolute(),
-            ADC_ABSX => self.adc_absolute_x(),
-            ADC_ABSY => self.adc_absolute_y(),
-            ADC_INDX => self.adc_indirect_x(),
-            ADC_INDY => self.adc_indirect_y(),
-
-            // Unknow opcode
-            _ => {
-                panic!("Unknown opcode: {:#X}", opcode);
+    // Get the address of the given addressing mode
+    fn address(&mut self, mode: AddressingMode) -> u16 {
+        match mode {
+            ZeroPage => self.zero_page_addr(),
+            ZeroPageX => self.zero_page_x_addr(),
+            ZeroPageY => self.zero_page_y_addr(),
+            Absolute => self.absolute_addr(),
+            AbsoluteX => self.absolute_x_addr(),
+            AbsoluteY => self.absolute_y_addr(),
+            Indirect => self.indirect_addr(),
+            IndirectX => self.indirect_x_addr(),
+            IndirectY => self.indirect_y_addr(),
+            ZeroPageIndirect => self.zero_page_indirect_addr(),
+            AbsoluteXIndirect => self.absolute_x_indirect_addr(),
+            Relative => self.relative_addr(),
+            Implied | Accumulator | Immediate | ZeroPageRelative => {
+                unreachable!("No address with {:?} addressing", mode)
             }
         }
     }
 
-    // Addressing modes
+    fn accumulator(&mut self) -> u8 {
+        self.implied();
+        self.registers.acc
+    }
 
     fn immediate(&mut self) -> u8 {
         self.fetch8()
@@ -265,25 +878,33 @@ impl Processor {
 
     fn zero_page_x_read(&mut self) -> u8 {
         let x = self.registers.x;
-        let address = self.fetch8().wrapping_add(x);
+        let base = self.fetch8();
+        self.read(base as u16);
+        let address = base.wrapping_add(x);
         self.read(address as u16)
     }
 
     fn zero_page_x_addr(&mut self) -> u16 {
         let x = self.registers.x;
-        let address = self.fetch8().wrapping_add(x);
+        let base = self.fetch8();
+        self.read(base as u16);
+        let address = base.wrapping_add(x);
         address as u16
     }
 
     fn zero_page_y_read(&mut self) -> u8 {
         let y = self.registers.y;
-        let address = self.fetch8().wrapping_add(y);
+        let base = self.fetch8();
+        self.read(base as u16);
+        let address = base.wrapping_add(y);
         self.read(address as u16)
     }
 
     fn zero_page_y_addr(&mut self) -> u16 {
         let y = self.registers.y;
-        let address = self.fetch8().wrapping_add(y);
+        let base = self.fetch8();
+        self.read(base as u16);
+        let address = base.wrapping_add(y);
         address as u16
     }
 
@@ -298,29 +919,106 @@ impl Processor {
 
     fn absolute_x_read(&mut self) -> u8 {
         let x = self.registers.x;
-        let address = self.fetch16().wrapping_add(x as u16);
+        let base = self.fetch16();
+        let address = base.wrapping_add(x as u16);
+        self.page_cross_penalty(base, address);
         self.read(address)
     }
 
     fn absolute_x_addr(&mut self) -> u16 {
         let x = self.registers.x;
-        self.fetch16().wrapping_add(x as u16)
+        let base = self.fetch16();
+        let address = base.wrapping_add(x as u16);
+        self.indexed_dummy_read(base, address);
+        address
+    }
+
+    // The 65C02 only takes the extra cycle of a shift when the indexing crosses a page
+    fn absolute_x_shift_addr(&mut self) -> u16 {
+        let x = self.registers.x;
+        let base = self.fetch16();
+        let address = base.wrapping_add(x as u16);
+        if self.is_cmos() {
+            self.page_cross_penalty(base, address);
+        } else {
+            self.indexed_dummy_read(base, address);
+        }
+        address
     }
 
     fn absolute_y_read(&mut self) -> u8 {
         let y = self.registers.y;
-        let address = self.fetch16().wrapping_add(y as u16);
+        let base = self.fetch16();
+        let address = base.wrapping_add(y as u16);
+        self.page_cross_penalty(base, address);
         self.read(address)
     }
 
     fn absolute_y_addr(&mut self) -> u16 {
         let y = self.registers.y;
-        self.fetch16().wrapping_add(y as u16)
+        let base = self.fetch16();
+        let address = base.wrapping_add(y as u16);
+        self.indexed_dummy_read(base, address);
+        address
+    }
+
+    fn relative_addr(&mut self) -> u16 {
+        // The offset is signed and relative to the next instruction
+        let offset = self.fetch8() as i8;
+        self.registers.pc.wrapping_add(offset as u16)
+    }
+
+    fn indirect_addr(&mut self) -> u16 {
+        let pointer = self.fetch16();
+        let low = self.read(pointer);
+
+        // The 65C02 spends an extra cycle fixing the page boundary bug
+        if self.is_cmos() {
+            self.read(pointer);
+        }
+
+        // The NMOS 6502 does not carry into the high byte of the pointer,
+        // so a pointer at $xxFF takes its high byte from $xx00
+        let pointer = if self.jmp_indirect_bug {
+            (pointer & 0xFF00) | (pointer.wrapping_add(1) & 0x00FF)
+        } else {
+            pointer.wrapping_add(1)
+        };
+        let high = self.read(pointer);
+
+        u16::from_le_bytes([low, high])
+    }
+
+    fn absolute_x_indirect_addr(&mut self) -> u16 {
+        let x = self.registers.x;
+        let pointer = self.fetch16();
+        self.read(self.registers.pc.wrapping_sub(1));
+        let pointer = pointer.wrapping_add(x as u16);
+        let low = self.read(pointer);
+        let high = self.read(pointer.wrapping_add(1));
+        u16::from_le_bytes([low, high])
+    }
+
+    fn zero_page_indirect_read(&mut self) -> u8 {
+        let pointer = self.fetch8();
+        let low = self.read(pointer as u16);
+        let high = self.read(pointer.wrapping_add(1) as u16);
+        let address = u16::from_le_bytes([low, high]);
+        self.read(address)
+    }
+
+    fn zero_page_indirect_addr(&mut self) -> u16 {
+        let pointer = self.fetch8();
+        let low = self.read(pointer as u16);
+        let high = self.read(pointer.wrapping_add(1) as u16);
+        u16::from_le_bytes([low, high])
     }
 
     fn indirect_x_read(&mut self) -> u8 {
         let x = self.registers.x;
-        let pointer = self.fetch8().wrapping_add(x);
+        let pointer = self.fetch8();
+        self.read(pointer as u16);
+        let pointer = pointer.wrapping_add(x);
         let low = self.read(pointer as u16);
         let high = self.read(pointer.wrapping_add(1) as u16);
         let address = u16::from_le_bytes([low, high]);
@@ -329,7 +1027,9 @@ impl Processor {
 
     fn indirect_x_addr(&mut self) -> u16 {
         let x = self.registers.x;
-        let pointer = self.fetch8().wrapping_add(x);
+        let pointer = self.fetch8();
+        self.read(pointer as u16);
+        let pointer = pointer.wrapping_add(x);
         let low = self.read(pointer as u16);
         let high = self.read(pointer.wrapping_add(1) as u16);
         u16::from_le_bytes([low, high])
@@ -340,7 +1040,9 @@ impl Processor {
         let pointer = self.fetch8();
         let low = self.read(pointer as u16);
         let high = self.read(pointer.wrapping_add(1) as u16);
-        let address = u16::from_le_bytes([low, high]).wrapping_add(y as u16);
+        let base = u16::from_le_bytes([low, high]);
+        let address = base.wrapping_add(y as u16);
+        self.page_cross_penalty(base, address);
         self.read(address)
     }
 
@@ -349,7 +1051,10 @@ impl Processor {
         let pointer = self.fetch8();
         let low = self.read(pointer as u16);
         let high = self.read(pointer.wrapping_add(1) as u16);
-        u16::from_le_bytes([low, high]).wrapping_add(y as u16)
+        let base = u16::from_le_bytes([low, high]);
+        let address = base.wrapping_add(y as u16);
+        self.indexed_dummy_read(base, address);
+        address
     }
 
     // Opcode implementations
@@ -366,56 +1071,47 @@ impl Processor {
             .set(Status::NEGATIVE, value & 0b1000_0000 != 0);
     }
 
-    // Load accumulator immediate
-    fn lda_immediate(&mut self) {
-        let value = self.immediate();
-        self.lda(value);
-    }
+    // Load X register
+    fn ldx(&mut self, value: u8) {
+        self.registers.x = value;
 
-    // Load accumulator zero page
-    fn lda_zero_page(&mut self) {
-        let value = self.zero_page_read();
-        self.lda(value);
+        self.registers.status.set(Status::ZERO, value == 0x00);
+        self.registers
+            .status
+            .set(Status::NEGATIVE, value & 0b1000_0000 != 0);
     }
 
-    // Load accumulator zero page, X
-    fn lda_zero_page_x(&mut self) {
-        let value = self.zero_page_x_read();
-        self.lda(value);
-    }
+    // Load Y register
+    fn ldy(&mut self, value: u8) {
+        self.registers.y = value;
 
-    // Load accumulator absolute
-    fn lda_absolute(&mut self) {
-        let value = self.absolute_read();
-        self.lda(value);
+        self.registers.status.set(Status::ZERO, value == 0x00);
+        self.registers
+            .status
+            .set(Status::NEGATIVE, value & 0b1000_0000 != 0);
     }
 
-    // Load accumulator absolute, X
-    fn lda_absolute_x(&mut self) {
-        let value = self.absolute_x_read();
-        self.lda(value);
+    // Store accumulator
+    fn sta(&mut self, address: u16) {
+        self.write(address, self.registers.acc);
     }
 
-    // Load accumulator absolute, Y
-    fn lda_absolute_y(&mut self) {
-        let value = self.absolute_y_read();
-        self.lda(value);
+    // Store X register
+    fn stx(&mut self, address: u16) {
+        self.write(address, self.registers.x);
     }
 
-    // Load accumulator indirect, X
-    fn lda_indirect_x(&mut self) {
-        let value = self.indirect_x_read();
-        self.lda(value);
+    // Store Y register
+    fn sty(&mut self, address: u16) {
+        self.write(address, self.registers.y);
     }
 
-    // Load accumulator indirect, Y
-    fn lda_indirect_y(&mut self) {
-        let value = self.indirect_y_read();
-        self.lda(value);
-    }
+    // Transfer
 
-    // Load X register
-    fn ldx(&mut self, value: u8) {
+    // Transfer accumulator to X register
+    fn tax(&mut self) {
+        self.implied();
+        let value = self.registers.acc;
         self.registers.x = value;
 
         self.registers.status.set(Status::ZERO, value == 0x00);
@@ -424,39 +1120,47 @@ impl Processor {
             .set(Status::NEGATIVE, value & 0b1000_0000 != 0);
     }
 
-    // Load X register immediate
-    fn ldx_immediate(&mut self) {
-        let value = self.immediate();
-        self.ldx(value);
-    }
+    // Transfer accumulator to Y register
+    fn tay(&mut self) {
+        self.implied();
+        let value = self.registers.acc;
+        self.registers.y = value;
 
-    // Load X register zero page
-    fn ldx_zero_page(&mut self) {
-        let value = self.zero_page_read();
-        self.ldx(value);
+        self.registers.status.set(Status::ZERO, value == 0x00);
+        self.registers
+            .status
+            .set(Status::NEGATIVE, value & 0b1000_0000 != 0);
     }
 
-    // Load X register zero page, Y
-    fn ldx_zero_page_y(&mut self) {
-        let value = self.zero_page_y_read();
-        self.ldx(value);
-    }
+    // Transfer X register to accumulator
+    fn txa(&mut self) {
+        self.implied();
+        let value = self.registers.x;
+        self.registers.acc = value;
 
-    // Load X register absolute
-    fn ldx_absolute(&mut self) {
-        let value = self.absolute_read();
-        self.ldx(value);
+        self.registers.status.set(Status::ZERO, value == 0x00);
+        self.registers
+            .status
+            .set(Status::NEGATIVE, value & 0b1000_0000 != 0);
     }
 
-    // Load X register absolute, Y
-    fn ldx_absolute_y(&mut self) {
-        let value = self.absolute_y_read();
-        self.ldx(value);
+    // Transfer Y register to accumulator
+    fn tya(&mut self) {
+        self.implied();
+        let value = self.registers.y;
+        self.registers.acc = value;
+
+        self.registers.status.set(Status::ZERO, value == 0x00);
+        self.registers
+            .status
+            .set(Status::NEGATIVE, value & 0b1000_0000 != 0);
     }
 
-    // Load Y register
-    fn ldy(&mut self, value: u8) {
-        self.registers.y = value;
+    // Transfer stack pointer to X register
+    fn tsx(&mut self) {
+        self.implied();
+        let value = self.registers.sp;
+        self.registers.x = value;
 
         self.registers.status.set(Status::ZERO, value == 0x00);
         self.registers
@@ -464,125 +1168,300 @@ impl Processor {
             .set(Status::NEGATIVE, value & 0b1000_0000 != 0);
     }
 
-    // Load Y register immediate
-    fn ldy_immediate(&mut self) {
-        let value = self.immediate();
-        self.ldy(value);
+    // Transfer X register to stack pointer
+    fn txs(&mut self) {
+        self.implied();
+        let value = self.registers.x;
+        self.registers.sp = value;
     }
 
-    // Load Y register zero page
-    fn ldy_zero_page(&mut self) {
-        let value = self.zero_page_read();
-        self.ldy(value);
-    }
+    // Stack
 
-    // Load Y register zero page, X
-    fn ldy_zero_page_x(&mut self) {
-        let value = self.zero_page_x_read();
-        self.ldy(value);
+    // Push accumulator
+    fn pha(&mut self) {
+        self.implied();
+        let value = self.registers.acc;
+        self.push(value);
     }
 
-    // Load Y register absolute
-    fn ldy_absolute(&mut self) {
-        let value = self.absolute_read();
-        self.ldy(value);
+    // Push processor status
+    fn php(&mut self) {
+        self.implied();
+        // Pushed like BRK does, with the break flag and bit 5 set
+        let value = self.registers.status | Status::BREAK | Status::UNUSED;
+        self.push(value.bits());
     }
 
-    // Load Y register absolute, X
-    fn ldy_absolute_x(&mut self) {
-        let value = self.absolute_x_read();
-        self.ldy(value);
+    // Pull accumulator
+    fn pla(&mut self) {
+        self.pull_setup();
+        let value = self.pull();
+        self.registers.acc = value;
+
+        self.registers.status.set(Status::ZERO, value == 0x00);
+        self.registers
+            .status
+            .set(Status::NEGATIVE, value & 0b1000_0000 != 0);
     }
 
-    // Store accumulator
+    // Pull processor status
+    fn plp(&mut self) {
+        self.pull_setup();
+        self.pull_status();
+    }
 
-    // Store accumulator zero page
-    fn sta_zero_page(&mut self) {
-        let address = self.zero_page_addr();
-        self.write(address, self.registers.acc);
+    // Pull the status register, the break and unused bits only exist on the stack
+    // and are not affected by the pulled status
+    fn pull_status(&mut self) {
+        let ignored = Status::BREAK | Status::UNUSED;
+        let status = Status::from_bits_truncate(self.pull());
+        self.registers.status = (status - ignored) | (self.registers.status & ignored);
     }
 
-    // Store accumulator zero page, X
-    fn sta_zero_page_x(&mut self) {
-        let address = self.zero_page_x_addr();
-        self.write(address, self.registers.acc);
+    // Logical
+
+    // Logical AND
+    fn and(&mut self, value: u8) {
+        let result = self.registers.acc & value;
+        self.registers.acc = result;
+
+        self.registers.status.set(Status::ZERO, result == 0x00);
+        self.registers
+            .status
+            .set(Status::NEGATIVE, result & 0x80 != 0);
     }
 
-    // Store accumulator absolute
-    fn sta_absolute(&mut self) {
-        let address = self.absolute_addr();
-        self.write(address, self.registers.acc);
+    // Logical EOR
+    fn eor(&mut self, value: u8) {
+        self.registers.acc ^= value;
+
+        self.registers
+            .status
+            .set(Status::ZERO, self.registers.acc == 0x00);
+        self.registers
+            .status
+            .set(Status::NEGATIVE, self.registers.acc & 0b1000_0000 != 0);
     }
 
-    // Store accumulator absolute, X
-    fn sta_absolute_x(&mut self) {
-        let address = self.absolute_x_addr();
-        self.write(address, self.registers.acc);
+    // Logical ORA
+    fn ora(&mut self, value: u8) {
+        self.registers.acc |= value;
+
+        self.registers
+            .status
+            .set(Status::ZERO, self.registers.acc == 0x00);
+        self.registers
+            .status
+            .set(Status::NEGATIVE, self.registers.acc & 0b1000_0000 != 0);
     }
 
-    // Store accumulator absolute, Y
-    fn sta_absolute_y(&mut self) {
-        let address = self.absolute_y_addr();
-        self.write(address, self.registers.acc);
+    // Logical BIT test
+    fn bit(&mut self, value: u8) {
+        let result = self.registers.acc & value;
+
+        // Negative and overflow are bits 7 and 6 of the operand, not of the result
+        self.registers.status.set(Status::ZERO, result == 0x00);
+        self.registers
+            .status
+            .set(Status::NEGATIVE, value & 0b1000_0000 != 0);
+        self.registers
+            .status
+            .set(Status::OVERFLOW, value & 0b0100_0000 != 0);
     }
 
-    // Store accumulator indirect, X
-    fn sta_indirect_x(&mut self) {
-        let address = self.indirect_x_addr();
-        self.write(address, self.registers.acc);
+    // Arithmetic
+
+    // Add with carry
+    fn adc(&mut self, value: u8) {
+        if self.decimal_mode() {
+            // The 65C02 takes an extra cycle to fix up the flags
+            if self.is_cmos() {
+                self.cycles += 1;
+                self.implied();
+            }
+            self.adc_decimal(value)
+        } else {
+            self.adc_binary(value)
+        }
     }
 
-    // Store accumulator indirect, Y
-    fn sta_indirect_y(&mut self) {
-        let address = self.indirect_y_addr();
-        self.write(address, self.registers.acc);
+    // Add with carry in binary mode
+    fn adc_binary(&mut self, value: u8) {
+        let carry = self.registers.status.contains(Status::CARRY);
+        let acc = self.registers.acc as u16;
+
+        let result = acc.wrapping_add(value as u16).wrapping_add(carry as u16);
+        self.registers.acc = result as u8;
+
+        self.registers
+            .status
+            .set(Status::ZERO, result as u8 == 0x00);
+        self.registers
+            .status
+            .set(Status::NEGATIVE, result as u8 & 0b1000_0000 != 0);
+        self.registers.status.set(Status::CARRY, result > 0xFF);
+
+        let sign_acc = (acc & 0b1000_0000) != 0;
+        let sign_value = (value as u16 & 0b1000_0000) != 0;
+        let sign_result = (result & 0b1000_0000) != 0;
+        self.registers.status.set(
+            Status::OVERFLOW,
+            (sign_acc == sign_value) && (sign_acc != sign_result),
+        );
     }
 
-    // Store X register
+    // Add with carry in decimal mode
+    // Follows the NMOS 6502, including its results for invalid BCD values,
+    // the CMOS variants only fix the N and Z flags
+    fn adc_decimal(&mut self, value: u8) {
+        let carry = self.registers.status.contains(Status::CARRY) as u16;
+        let acc = self.registers.acc as u16;
+        let value = value as u16;
 
-    // Store X register zero page
-    fn stx_zero_page(&mut self) {
-        let address = self.zero_page_addr();
-        self.write(address, self.registers.x);
+        // Add the low digits and adjust them into the high digit
+        let mut low = (acc & 0x0F) + (value & 0x0F) + carry;
+        if low >= 0x0A {
+            low = ((low + 0x06) & 0x0F) + 0x10;
+        }
+        let mut result = (acc & 0xF0) + (value & 0xF0) + low;
+
+        // N and V are taken before the high digit is adjusted
+        let signed =
+            (acc & 0xF0) as u8 as i8 as i16 + (value & 0xF0) as u8 as i8 as i16 + low as i16;
+        self.registers
+            .status
+            .set(Status::NEGATIVE, result & 0b1000_0000 != 0);
+        self.registers
+            .status
+            .set(Status::OVERFLOW, !(-128..=127).contains(&signed));
+
+        // Z is the same as in binary mode
+        let binary = acc.wrapping_add(value).wrapping_add(carry) as u8;
+        self.registers.status.set(Status::ZERO, binary == 0x00);
+
+        // Adjust the high digit
+        if result >= 0xA0 {
+            result += 0x60;
+        }
+        self.registers.acc = result as u8;
+        self.registers.status.set(Status::CARRY, result > 0xFF);
+
+        // The CMOS variants set N and Z from the decimal result
+        if self.is_cmos() {
+            self.registers
+                .status
+                .set(Status::ZERO, result as u8 == 0x00);
+            self.registers
+                .status
+                .set(Status::NEGATIVE, result as u8 & 0b1000_0000 != 0);
+        }
     }
 
-    // Store X register zero page, Y
-    fn stx_zero_page_y(&mut self) {
-        let address = self.zero_page_y_addr();
-        self.write(address, self.registers.x);
+    // Subtract with carry
+    fn sbc(&mut self, value: u8) {
+        let carry = self.registers.status.contains(Status::CARRY);
+        let acc = self.registers.acc;
+
+        // A - M - (1 - C) is the same as A + !M + C, so the carry acts as an inverted borrow
+        // The flags are the same in binary and decimal mode
+        self.adc_binary(!value);
+
+        if self.decimal_mode() {
+            if self.is_cmos() {
+                self.cycles += 1;
+                self.implied();
+            }
+
+            let result = self.sbc_decimal(acc, value, carry);
+            self.registers.acc = result;
+
+            // The CMOS variants set N and Z from the decimal result
+            if self.is_cmos() {
+                self.registers.status.set(Status::ZERO, result == 0x00);
+                self.registers
+                    .status
+                    .set(Status::NEGATIVE, result & 0b1000_0000 != 0);
+            }
+        }
     }
 
-    // Store X register absolute
-    fn stx_absolute(&mut self) {
-        let address = self.absolute_addr();
-        self.write(address, self.registers.x);
+    // Subtract with carry in decimal mode, only the accumulator result
+    // Follows the NMOS 6502 or the 65C02, including their results for invalid BCD values
+    fn sbc_decimal(&self, acc: u8, value: u8, carry: bool) -> u8 {
+        let acc = acc as i16;
+        let value = value as i16;
+
+        if self.is_cmos() {
+            // Subtract everything at once, then adjust the digits that borrowed
+            let low = (acc & 0x0F) - (value & 0x0F) + carry as i16 - 1;
+            let mut result = acc - value + carry as i16 - 1;
+            if result < 0 {
+                result -= 0x60;
+            }
+            if low < 0 {
+                result -= 0x06;
+            }
+            return result as u8;
+        }
+
+        // Subtract the low digits and borrow from the high digit
+        let mut low = (acc & 0x0F) - (value & 0x0F) + carry as i16 - 1;
+        if low < 0 {
+            low = ((low - 0x06) & 0x0F) - 0x10;
+        }
+        let mut result = (acc & 0xF0) - (value & 0xF0) + low;
+
+        // Adjust the high digit
+        if result < 0 {
+            result -= 0x60;
+        }
+        result as u8
     }
 
-    // Store Y register
+    // Compare a register with a value
+    fn compare(&mut self, register: u8, value: u8) {
+        let result = register.wrapping_sub(value);
 
-    // Store Y register zero page
-    fn sty_zero_page(&mut self) {
-        let address = self.zero_page_addr();
-        self.write(address, self.registers.y);
+        self.registers.status.set(Status::CARRY, register >= value);
+        self.registers.status.set(Status::ZERO, register == value);
+        self.registers
+            .status
+            .set(Status::NEGATIVE, result & 0b1000_0000 != 0);
     }
 
-    // Store Y register zero page, X
-    fn sty_zero_page_x(&mut self) {
-        let address = self.zero_page_x_addr();
-        self.write(address, self.registers.y);
+    // Compare accumulator
+    fn cmp(&mut self, value: u8) {
+        self.compare(self.registers.acc, value)
     }
 
-    // Store Y register absolute
-    fn sty_absolute(&mut self) {
-        let address = self.absolute_addr();
-        self.write(address, self.registers.y);
+    // Compare X register
+    fn cpx(&mut self, value: u8) {
+        self.compare(self.registers.x, value)
     }
 
-    // Transfer
+    // Compare Y register
+    fn cpy(&mut self, value: u8) {
+        self.compare(self.registers.y, value)
+    }
 
-    // Transfer accumulator to X register
-    fn tax(&mut self) {
-        let value = self.registers.acc;
+    // Increments/decrements
+
+    // Increment memory
+    fn inc(&mut self, address: u16) {
+        let value = self.read(address);
+        let result = value.wrapping_add(1);
+        self.modify_write(address, value, result);
+
+        self.registers.status.set(Status::ZERO, result == 0x00);
+        self.registers
+            .status
+            .set(Status::NEGATIVE, result & 0b1000_0000 != 0);
+    }
+
+    // Increment X register
+    fn inx(&mut self) {
+        self.implied();
+        let value = self.registers.x.wrapping_add(1);
         self.registers.x = value;
 
         self.registers.status.set(Status::ZERO, value == 0x00);
@@ -591,9 +1470,10 @@ impl Processor {
             .set(Status::NEGATIVE, value & 0b1000_0000 != 0);
     }
 
-    // Transfer accumulator to Y register
-    fn tay(&mut self) {
-        let value = self.registers.acc;
+    // Increment Y register
+    fn iny(&mut self) {
+        self.implied();
+        let value = self.registers.y.wrapping_add(1);
         self.registers.y = value;
 
         self.registers.status.set(Status::ZERO, value == 0x00);
@@ -602,21 +1482,23 @@ impl Processor {
             .set(Status::NEGATIVE, value & 0b1000_0000 != 0);
     }
 
-    // Transfer X register to accumulator
-    fn txa(&mut self) {
-        let value = self.registers.x;
-        self.registers.acc = value;
+    // Decrement memory
+    fn dec(&mut self, address: u16) {
+        let value = self.read(address);
+        let result = value.wrapping_sub(1);
+        self.modify_write(address, value, result);
 
-        self.registers.status.set(Status::ZERO, value == 0x00);
+        self.registers.status.set(Status::ZERO, result == 0x00);
         self.registers
             .status
-            .set(Status::NEGATIVE, value & 0b1000_0000 != 0);
+            .set(Status::NEGATIVE, result & 0b1000_0000 != 0);
     }
 
-    // Transfer Y register to accumulator
-    fn tya(&mut self) {
-        let value = self.registers.y;
-        self.registers.acc = value;
+    // Decrement X register
+    fn dex(&mut self) {
+        self.implied();
+        let value = self.registers.x.wrapping_sub(1);
+        self.registers.x = value;
 
         self.registers.status.set(Status::ZERO, value == 0x00);
         self.registers
@@ -624,10 +1506,11 @@ impl Processor {
             .set(Status::NEGATIVE, value & 0b1000_0000 != 0);
     }
 
-    // Transfer stack pointer to X register
-    fn tsx(&mut self) {
-        let value = self.registers.sp;
-        self.registers.x = value;
+    // Decrement Y register
+    fn dey(&mut self) {
+        self.implied();
+        let value = self.registers.y.wrapping_sub(1);
+        self.registers.y = value;
 
         self.registers.status.set(Status::ZERO, value == 0x00);
         self.registers
@@ -635,322 +1518,519 @@ impl Processor {
             .set(Status::NEGATIVE, value & 0b1000_0000 != 0);
     }
 
-    // Transfer X register to stack pointer
-    fn txs(&mut self) {
-        let value = self.registers.x;
-        self.registers.sp = value;
-    }
+    // Shifts
 
-    // Stack
+    // Arithmetic shift left
+    fn asl(&mut self, value: u8) -> u8 {
+        let result = value << 1;
+        self.registers
+            .status
+            .set(Status::CARRY, value & 0b1000_0000 != 0);
 
-    // Push accumulator
-    fn pha(&mut self) {
-        let value = self.registers.acc;
-        self.push(value);
-    }
+        self.registers.status.set(Status::ZERO, result == 0x00);
+        self.registers
+            .status
+            .set(Status::NEGATIVE, result & 0b1000_0000 != 0);
 
-    // Push processor status
-    fn php(&mut self) {
-        let value = self.registers.status.bits();
-        self.push(value);
+        result
     }
 
-    // Pull accumulator
-    fn pla(&mut self) {
-        let value = self.pull();
-        self.registers.acc = value;
+    // Logical shift right
+    fn lsr(&mut self, value: u8) -> u8 {
+        let result = value >> 1;
+        self.registers
+            .status
+            .set(Status::CARRY, value & 0b0000_0001 != 0);
 
-        self.registers.status.set(Status::ZERO, value == 0x00);
+        self.registers.status.set(Status::ZERO, result == 0x00);
         self.registers
             .status
-            .set(Status::NEGATIVE, value & 0b1000_0000 != 0);
-    }
+            .set(Status::NEGATIVE, result & 0b1000_0000 != 0);
 
-    // Pull processor status
-    fn plp(&mut self) {
-        let value = self.pull();
-        self.registers.status = Status::from_bits_truncate(value);
+        result
     }
 
-    // Logical
+    // Rotate left
+    fn rol(&mut self, value: u8) -> u8 {
+        let carry = self.registers.status.contains(Status::CARRY);
+        let result = (value << 1) | carry as u8;
+        self.registers
+            .status
+            .set(Status::CARRY, value & 0b1000_0000 != 0);
 
-    // Logical AND
-    fn and(&mut self, value: u8) {
-        let result = self.registers.acc & value;
-        self.registers.acc = result;
+        self.registers.status.set(Status::ZERO, result == 0x00);
+        self.registers
+            .status
+            .set(Status::NEGATIVE, result & 0b1000_0000 != 0);
+
+        result
+    }
+
+    // Rotate right
+    fn ror(&mut self, value: u8) -> u8 {
+        let carry = self.registers.status.contains(Status::CARRY);
+        let result = (value >> 1) | ((carry as u8) << 7);
+        self.registers
+            .status
+            .set(Status::CARRY, value & 0b0000_0001 != 0);
 
         self.registers.status.set(Status::ZERO, result == 0x00);
         self.registers
             .status
-            .set(Status::NEGATIVE, result & 0x80 != 0);
+            .set(Status::NEGATIVE, result & 0b1000_0000 != 0);
+
+        result
     }
 
-    // Logical AND immediate
-    fn and_immediate(&mut self) {
-        let value = self.immediate();
-        self.and(value)
+    // Shift or rotate the accumulator or a value in memory with the given operation
+    fn shift(&mut self, mode: AddressingMode, operation: fn(&mut Self, u8) -> u8) {
+        if mode == Accumulator {
+            let value = self.accumulator();
+            self.registers.acc = operation(self, value);
+            return;
+        }
+
+        let address = match mode {
+            AbsoluteX => self.absolute_x_shift_addr(),
+            _ => self.address(mode),
+        };
+        let value = self.read(address);
+        let result = operation(self, value);
+        self.modify_write(address, value, result);
     }
 
-    // Logical AND zero page
-    fn and_zero_page(&mut self) {
-        let value = self.zero_page_read();
-        self.and(value)
+    // Jumps
+
+    // Jump
+    fn jmp(&mut self, address: u16) {
+        self.registers.pc = address;
     }
 
-    // Logical AND zero page, X
-    fn and_zero_page_x(&mut self) {
-        let value = self.zero_page_x_read();
-        self.and(value)
+    // Jump to subroutine
+    fn jsr(&mut self) {
+        // The high byte of the address is fetched after the return address is pushed,
+        // with a read of the stack before the pushes
+        let low = self.fetch8();
+        self.read(0x0100 + self.registers.sp as u16);
+
+        // Push the address of the last byte of the instruction, high byte first
+        let [pc_low, pc_high] = self.registers.pc.to_le_bytes();
+        self.push(pc_high);
+        self.push(pc_low);
+
+        let high = self.fetch8();
+        self.registers.pc = u16::from_le_bytes([low, high]);
     }
 
-    // Logical AND absolute
-    fn and_absolute(&mut self) {
-        let value = self.absolute_read();
-        self.and(value)
+    // Return from subroutine
+    fn rts(&mut self) {
+        self.pull_setup();
+        let low = self.pull();
+        let high = self.pull();
+        self.registers.pc = u16::from_le_bytes([low, high]);
+
+        // The pulled address is read before it is incremented
+        self.implied();
+        self.registers.pc = self.registers.pc.wrapping_add(1);
     }
 
-    // Logical AND absolute, X
-    fn and_absolute_x(&mut self) {
-        let value = self.absolute_x_read();
-        self.and(value)
+    // Branches
+
+    // Branch to the relative address if the condition is true
+    fn branch(&mut self, condition: bool) {
+        let address = self.relative_addr();
+
+        if !condition {
+            self.last_branch = Some(Branch::NotTaken);
+            return;
+        }
+
+        // Crossing a page costs an extra cycle on top of taking the branch,
+        // both are spent reading from the program counter before it is fixed up
+        self.implied();
+        let page_crossed = address & 0xFF00 != self.registers.pc & 0xFF00;
+        self.last_branch = Some(if page_crossed {
+            self.read((self.registers.pc & 0xFF00) | (address & 0x00FF));
+            self.cycles += 2;
+            Branch::PageCrossed
+        } else {
+            self.cycles += 1;
+            Branch::Taken
+        });
+
+        self.registers.pc = address;
     }
 
-    // Logical AND absolute, Y
-    fn and_absolute_y(&mut self) {
-        let value = self.absolute_y_read();
-        self.and(value)
+    // Branch if positive
+    fn bpl(&mut self) {
+        let condition = !self.registers.status.contains(Status::NEGATIVE);
+        self.branch(condition)
     }
 
-    // Logical AND (indirect, X)
-    fn and_indirect_x(&mut self) {
-        let value = self.indirect_x_read();
-        self.and(value)
+    // Branch if minus
+    fn bmi(&mut self) {
+        let condition = self.registers.status.contains(Status::NEGATIVE);
+        self.branch(condition)
     }
 
-    // Logical AND (indirect), Y
-    fn and_indirect_y(&mut self) {
-        let value = self.indirect_y_read();
-        self.and(value)
+    // Branch if overflow clear
+    fn bvc(&mut self) {
+        let condition = !self.registers.status.contains(Status::OVERFLOW);
+        self.branch(condition)
     }
 
-    // Logical EOR
-    fn eor(&mut self, value: u8) {
-        self.registers.acc ^= value;
+    // Branch if overflow set
+    fn bvs(&mut self) {
+        let condition = self.registers.status.contains(Status::OVERFLOW);
+        self.branch(condition)
+    }
 
-        self.registers
-            .status
-            .set(Status::ZERO, self.registers.acc == 0x00);
-        self.registers
-            .status
-            .set(Status::NEGATIVE, self.registers.acc & 0b1000_0000 != 0);
+    // Branch if carry clear
+    fn bcc(&mut self) {
+        let condition = !self.registers.status.contains(Status::CARRY);
+        self.branch(condition)
     }
 
-    // Logical EOR immediate
-    fn eor_immediate(&mut self) {
-        let value = self.immediate();
-        self.eor(value)
+    // Branch if carry set
+    fn bcs(&mut self) {
+        let condition = self.registers.status.contains(Status::CARRY);
+        self.branch(condition)
     }
 
-    // Logical EOR zero page
-    fn eor_zero_page(&mut self) {
-        let value = self.zero_page_read();
-        self.eor(value)
+    // Branch if not equal
+    fn bne(&mut self) {
+        let condition = !self.registers.status.contains(Status::ZERO);
+        self.branch(condition)
     }
 
-    // Logical EOR zero page, X
-    fn eor_zero_page_x(&mut self) {
-        let value = self.zero_page_x_read();
-        self.eor(value)
+    // Branch if equal
+    fn beq(&mut self) {
+        let condition = self.registers.status.contains(Status::ZERO);
+        self.branch(condition)
     }
 
-    // Logical EOR absolute
-    fn eor_absolute(&mut self) {
-        let value = self.absolute_read();
-        self.eor(value)
+    // Status flags
+
+    // Clear carry flag
+    fn clc(&mut self) {
+        self.implied();
+        self.registers.status.remove(Status::CARRY);
     }
 
-    // Logical EOR absolute, X
-    fn eor_absolute_x(&mut self) {
-        let value = self.absolute_x_read();
-        self.eor(value)
+    // Clear decimal mode flag
+    fn cld(&mut self) {
+        self.implied();
+        self.registers.status.remove(Status::DECIMAL);
     }
 
-    // Logical EOR absolute, Y
-    fn eor_absolute_y(&mut self) {
-        let value = self.absolute_y_read();
-        self.eor(value)
+    // Clear interrupt disable flag
+    fn cli(&mut self) {
+        self.implied();
+        self.registers.status.remove(Status::INTERRUPT);
     }
 
-    // Logical EOR (indirect, X)
-    fn eor_indirect_x(&mut self) {
-        let value = self.indirect_x_read();
-        self.eor(value)
+    // Clear overflow flag
+    fn clv(&mut self) {
+        self.implied();
+        self.registers.status.remove(Status::OVERFLOW);
     }
 
-    // Logical EOR (indirect), Y
-    fn eor_indirect_y(&mut self) {
-        let value = self.indirect_y_read();
-        self.eor(value)
+    // Set carry flag
+    fn sec(&mut self) {
+        self.implied();
+        self.registers.status.insert(Status::CARRY);
     }
 
-    // Logical ORA
-    fn ora(&mut self, value: u8) {
-        self.registers.acc |= value;
+    // Set decimal mode flag
+    fn sed(&mut self) {
+        self.implied();
+        self.registers.status.insert(Status::DECIMAL);
+    }
 
-        self.registers
-            .status
-            .set(Status::ZERO, self.registers.acc == 0x00);
-        self.registers
-            .status
-            .set(Status::NEGATIVE, self.registers.acc & 0b1000_0000 != 0);
+    // Set interrupt disable flag
+    fn sei(&mut self) {
+        self.implied();
+        self.registers.status.insert(Status::INTERRUPT);
     }
 
-    // Logical ORA immediate
-    fn ora_immediate(&mut self) {
-        let value = self.immediate();
-        self.ora(value)
+    // System
+
+    // Force interrupt
+    fn brk(&mut self) {
+        // BRK is a two byte instruction, the second byte is skipped
+        self.fetch8();
+        self.interrupt(IRQ_VECTOR, true);
     }
 
-    // Logical ORA zero page
-    fn ora_zero_page(&mut self) {
-        let value = self.zero_page_read();
-        self.ora(value)
+    // No operation, the operand is read and ignored
+    fn nop(&mut self, _value: u8) {}
+
+    // Halt the processor
+    fn jam(&mut self) {
+        // The program counter is left on the JAM opcode
+        self.registers.pc = self.registers.pc.wrapping_sub(1);
+        self.control.halted = true;
     }
 
-    // Logical ORA zero page, X
-    fn ora_zero_page_x(&mut self) {
-        let value = self.zero_page_x_read();
-        self.ora(value)
+    // Return from interrupt
+    fn rti(&mut self) {
+        self.pull_setup();
+        self.pull_status();
+
+        let low = self.pull();
+        let high = self.pull();
+        self.registers.pc = u16::from_le_bytes([low, high]);
     }
 
-    // Logical ORA absolute
-    fn ora_absolute(&mut self) {
-        let value = self.absolute_read();
-        self.ora(value)
+    // Undocumented opcodes
+
+    // Load accumulator and X register
+    fn lax(&mut self, value: u8) {
+        self.lda(value);
+        self.registers.x = value;
     }
 
-    // Logical ORA absolute, X
-    fn ora_absolute_x(&mut self) {
-        let value = self.absolute_x_read();
-        self.ora(value)
+    // Store accumulator AND X register
+    fn sax(&mut self, address: u16) {
+        let value = self.registers.acc & self.registers.x;
+        self.write(address, value);
     }
 
-    // Logical ORA absolute, Y
-    fn ora_absolute_y(&mut self) {
-        let value = self.absolute_y_read();
-        self.ora(value)
+    // Decrement memory and compare
+    fn dcp(&mut self, address: u16) {
+        let value = self.read(address);
+        let result = value.wrapping_sub(1);
+        self.modify_write(address, value, result);
+        self.cmp(result)
     }
 
-    // Logical ORA (indirect, X)
-    fn ora_indirect_x(&mut self) {
-        let value = self.indirect_x_read();
-        self.ora(value)
+    // Increment memory and subtract with carry
+    fn isc(&mut self, address: u16) {
+        let value = self.read(address);
+        let result = value.wrapping_add(1);
+        self.modify_write(address, value, result);
+        self.sbc(result)
     }
 
-    // Logical ORA (indirect), Y
-    fn ora_indirect_y(&mut self) {
-        let value = self.indirect_y_read();
-        self.ora(value)
+    // Arithmetic shift left memory and logical ORA
+    fn slo(&mut self, address: u16) {
+        let value = self.read(address);
+        let result = self.asl(value);
+        self.modify_write(address, value, result);
+        self.ora(result)
     }
 
-    // Logical BIT test
-    fn bit(&mut self, value: u8) {
-        let result = self.registers.acc & value;
+    // Rotate left memory and logical AND
+    fn rla(&mut self, address: u16) {
+        let value = self.read(address);
+        let result = self.rol(value);
+        self.modify_write(address, value, result);
+        self.and(result)
+    }
 
-        self.registers.status.set(Status::ZERO, result == 0x00);
-        self.registers
-            .status
-            .set(Status::NEGATIVE, result & 0b1000_0000 != 0);
-        self.registers
-            .status
-            .set(Status::OVERFLOW, result & 0b0100_0000 != 0);
+    // Logical shift right memory and logical EOR
+    fn sre(&mut self, address: u16) {
+        let value = self.read(address);
+        let result = self.lsr(value);
+        self.modify_write(address, value, result);
+        self.eor(result)
     }
 
-    // Logical BIT zero page
-    fn bit_zero_page(&mut self) {
-        let value = self.zero_page_read();
-        self.bit(value)
+    // Rotate right memory and add with carry
+    fn rra(&mut self, address: u16) {
+        let value = self.read(address);
+        let result = self.ror(value);
+        self.modify_write(address, value, result);
+        self.adc(result)
     }
 
-    // Logical BIT absolute
-    fn bit_absolute(&mut self) {
-        let value = self.absolute_read();
-        self.bit(value)
+    // Logical AND and copy bit 7 to carry
+    fn anc(&mut self, value: u8) {
+        self.and(value);
+
+        let negative = self.registers.status.contains(Status::NEGATIVE);
+        self.registers.status.set(Status::CARRY, negative);
     }
 
-    // Arithmetic
+    // Logical AND and logical shift right accumulator
+    fn alr(&mut self, value: u8) {
+        self.and(value);
+        self.registers.acc = self.lsr(self.registers.acc);
+    }
 
-    // Add with carry
-    fn adc(&mut self, value: u8) {
+    // Logical AND and rotate right accumulator
+    fn arr(&mut self, value: u8) {
+        let value = value & self.registers.acc;
         let carry = self.registers.status.contains(Status::CARRY);
-        let acc = self.registers.acc as u16;
-
-        let result = acc.wrapping_add(value as u16).wrapping_add(carry as u16);
-        self.registers.acc = result as u8;
+        let mut result = (value >> 1) | ((carry as u8) << 7);
 
+        self.registers.status.set(Status::ZERO, result == 0x00);
         self.registers
             .status
-            .set(Status::ZERO, result as u8 == 0x00);
-        self.registers
-            .status
-            .set(Status::NEGATIVE, result as u8 & 0b1000_0000 != 0);
-        self.registers.status.set(Status::CARRY, result > 0xFF);
-
-        let sign_acc = (acc & 0b1000_0000) != 0;
-        let sign_value = (value as u16 & 0b1000_0000) != 0;
-        let sign_result = (result & 0b1000_0000) != 0;
+            .set(Status::NEGATIVE, result & 0b1000_0000 != 0);
         self.registers.status.set(
             Status::OVERFLOW,
-            (sign_acc == sign_value) && (sign_acc != sign_result),
+            (result ^ (result << 1)) & 0b0100_0000 != 0,
         );
+
+        if self.decimal_mode() {
+            // The NMOS 6502 fixes up both digits like a decimal addition would,
+            // but the flags above are based on the value before the fixup
+            if (value & 0x0F) + (value & 0x01) > 0x05 {
+                result = (result & 0xF0) | (result.wrapping_add(0x06) & 0x0F);
+            }
+
+            let high_carry = (value as u16 & 0xF0) + (value as u16 & 0x10) > 0x50;
+            if high_carry {
+                result = result.wrapping_add(0x60);
+            }
+            self.registers.status.set(Status::CARRY, high_carry);
+        } else {
+            self.registers
+                .status
+                .set(Status::CARRY, result & 0b0100_0000 != 0);
+        }
+
+        self.registers.acc = result;
     }
 
-    // Add with carry immediate
-    fn adc_immediate(&mut self) {
-        let value = self.immediate();
-        self.adc(value)
+    // Accumulator AND X register minus value into X register
+    fn sbx(&mut self, value: u8) {
+        let register = self.registers.acc & self.registers.x;
+
+        // Sets the flags like a compare, without using the carry or decimal mode
+        self.compare(register, value);
+        self.registers.x = register.wrapping_sub(value);
+    }
+
+    // CMOS opcodes
+
+    // Store zero
+    fn stz(&mut self, address: u16) {
+        self.write(address, 0x00);
+    }
+
+    // Push X register
+    fn phx(&mut self) {
+        self.implied();
+        let value = self.registers.x;
+        self.push(value);
     }
 
-    // Add with carry zero page
-    fn adc_zero_page(&mut self) {
-        let value = self.zero_page_read();
-        self.adc(value)
+    // Push Y register
+    fn phy(&mut self) {
+        self.implied();
+        let value = self.registers.y;
+        self.push(value);
     }
 
-    // Add with carry zero page, X
-    fn adc_zero_page_x(&mut self) {
-        let value = self.zero_page_x_read();
-        self.adc(value)
+    // Pull X register
+    fn plx(&mut self) {
+        self.pull_setup();
+        let value = self.pull();
+        self.ldx(value);
     }
 
-    // Add with carry absolute
-    fn adc_absolute(&mut self) {
-        let value = self.absolute_read();
-        self.adc(value)
+    // Pull Y register
+    fn ply(&mut self) {
+        self.pull_setup();
+        let value = self.pull();
+        self.ldy(value);
     }
 
-    // Add with carry absolute, X
-    fn adc_absolute_x(&mut self) {
-        let value = self.absolute_x_read();
-        self.adc(value)
+    // Logical BIT immediate, only affects the zero flag
+    fn bit_immediate(&mut self, value: u8) {
+        let result = self.registers.acc & value;
+
+        self.registers.status.set(Status::ZERO, result == 0x00);
     }
 
-    // Add with carry absolute, Y
-    fn adc_absolute_y(&mut self) {
-        let value = self.absolute_y_read();
-        self.adc(value)
+    // Test and reset bits
+    fn trb(&mut self, address: u16) {
+        let value = self.read(address);
+        let acc = self.registers.acc;
+
+        self.registers.status.set(Status::ZERO, acc & value == 0x00);
+        self.modify_write(address, value, value & !acc);
     }
 
-    // Add with carry (indirect, X)
-    fn adc_indirect_x(&mut self) {
-        let value = self.indirect_x_read();
-        self.adc(value)
+    // Test and set bits
+    fn tsb(&mut self, address: u16) {
+        let value = self.read(address);
+        let acc = self.registers.acc;
+
+        self.registers.status.set(Status::ZERO, acc & value == 0x00);
+        self.modify_write(address, value, value | acc);
     }
 
-    // Add with carry (indirect), Y
-    fn adc_indirect_y(&mut self) {
-        let value = self.indirect_y_read();
-        self.adc(value)
+    // Increment accumulator
+    fn inc_accumulator(&mut self) {
+        let value = self.accumulator().wrapping_add(1);
+        self.lda(value);
+    }
+
+    // Decrement accumulator
+    fn dec_accumulator(&mut self) {
+        let value = self.accumulator().wrapping_sub(1);
+        self.lda(value);
+    }
+
+    // Branch always
+    fn bra(&mut self) {
+        self.branch(true)
+    }
+
+    // Reset memory bit zero page
+    fn rmb(&mut self, bit: u8) {
+        let address = self.zero_page_addr();
+        let value = self.read(address);
+        self.modify_write(address, value, value & !(1 << bit));
+    }
+
+    // Set memory bit zero page
+    fn smb(&mut self, bit: u8) {
+        let address = self.zero_page_addr();
+        let value = self.read(address);
+        self.modify_write(address, value, value | (1 << bit));
+    }
+
+    // Branch on bit reset, tests a zero page byte and then branches relative to the next instruction
+    fn bbr(&mut self, bit: u8) {
+        let address = self.zero_page_addr();
+        let value = self.read(address);
+        self.read(address);
+        self.branch(value & (1 << bit) == 0)
+    }
+
+    // Branch on bit set, tests a zero page byte and then branches relative to the next instruction
+    fn bbs(&mut self, bit: u8) {
+        let address = self.zero_page_addr();
+        let value = self.read(address);
+        self.read(address);
+        self.branch(value & (1 << bit) != 0)
+    }
+
+    // Wait for interrupt
+    fn wai(&mut self) {
+        self.implied();
+        self.implied();
+        self.control.waiting = true;
+    }
+
+    // Stop the processor until reset
+    fn stp(&mut self) {
+        self.implied();
+        self.implied();
+        self.control.halted = true;
+    }
+
+    // No operation that takes an absolute operand and keeps reading from the bus,
+    // only the cycles matter
+    fn nop_long(&mut self) {
+        let address = self.absolute_addr();
+        for _ in 0..5 {
+            self.read(address | 0xFF00);
+        }
     }
 }
 
@@ -969,11 +2049,11 @@ impl Processor {
 
     // Write a byte to the given address
     pub fn set_mem(&mut self, address: u16, byte: u8) {
-        self.device_mapper.write(address, byte);
+        self.device_mapper.write(address as u32, byte).unwrap();
     }
 
     // Read a byte from the given address
     pub fn get_mem(&mut self, address: u16) -> u8 {
-        self.device_mapper.read(address)
+        self.device_mapper.read(address as u32).unwrap()
     }
 }
@@ -0,0 +1,2040 @@
+use std::fmt;
+
+use crate::{
+    control::{Control, Entry},
+    device::Device,
+    device_mapper::DeviceMapper,
+    error::Error,
+    opcodes::{cmos::*, w65c816::*, *},
+    registers::{Registers65C816, Status},
+};
+
+// Interrupt vectors in emulation mode, the same as on the 6502
+const COP_VECTOR: u16 = 0xFFF4;
+const NMI_VECTOR: u16 = 0xFFFA;
+const RESET_VECTOR: u16 = 0xFFFC;
+const IRQ_VECTOR: u16 = 0xFFFE;
+
+// Interrupt vectors in native mode
+const NATIVE_COP_VECTOR: u16 = 0xFFE4;
+const NATIVE_BRK_VECTOR: u16 = 0xFFE6;
+const NATIVE_NMI_VECTOR: u16 = 0xFFEA;
+const NATIVE_IRQ_VECTOR: u16 = 0xFFEE;
+
+// The 65C816 in native mode uses the unused and break bits for the register widths
+pub(crate) const MEMORY: Status = Status::UNUSED; // 8-bit accumulator and memory when set
+pub(crate) const INDEX: Status = Status::BREAK; // 8-bit X and Y registers when set
+
+// Size of the 24-bit address space
+const ADDRESS_SPACE: u32 = 0x100_0000;
+
+// Addressing modes of the operands
+#[derive(Clone, Copy)]
+enum Mode {
+    Immediate,              // #, as wide as the accumulator
+    ImmediateIndex,         // #, as wide as the index registers
+    Absolute,               // a
+    AbsoluteX,              // a,X
+    AbsoluteY,              // a,Y
+    AbsoluteLong,           // al
+    AbsoluteLongX,          // al,X
+    Direct,                 // d
+    DirectX,                // d,X
+    DirectY,                // d,Y
+    DirectIndirect,         // (d)
+    DirectIndirectLong,     // [d]
+    DirectXIndirect,        // (d,X)
+    DirectIndirectY,        // (d),Y
+    DirectIndirectLongY,    // [d],Y
+    StackRelative,          // d,S
+    StackRelativeIndirectY, // (d,S),Y
+}
+
+use Mode::*;
+
+// Registers of the 65C816, for inspecting and changing it from the outside
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CpuState65C816 {
+    pub pc: u16,         // Program counter
+    pub pbr: u8,         // Program bank register
+    pub dbr: u8,         // Data bank register
+    pub dp: u16,         // Direct page register
+    pub sp: u16,         // Stack pointer
+    pub acc: u16,        // Accumulator, B in the high byte
+    pub x: u16,          // X register
+    pub y: u16,          // Y register
+    pub status: Status,  // Status register
+    pub emulation: bool, // Emulation flag
+}
+
+// Show the registers on one line, e.g.
+// `A=0042 X=0000 Y=0000 SP=01FD D=0000 DB=00 P=nvMXdIzc E=1 PC=00:0800`
+// The unused and break bits are shown as the register widths M and X in either mode
+impl fmt::Display for CpuState65C816 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "A={:04X} X={:04X} Y={:04X} SP={:04X} D={:04X} DB={:02X} P=",
+            self.acc, self.x, self.y, self.sp, self.dp, self.dbr
+        )?;
+        self.status.write_flags(f, 'm', 'x')?;
+        write!(
+            f,
+            " E={} PC={:02X}:{:04X}",
+            self.emulation as u8, self.pbr, self.pc
+        )
+    }
+}
+
+// The WDC 65C816, a 65C02 with 16-bit registers and a 24-bit address space
+// It runs in emulation mode after reset, where it behaves like a 65C02 without the bit instructions
+pub struct Processor65C816 {
+    registers: Registers65C816,
+    device_mapper: DeviceMapper,
+    control: Control,
+}
+
+impl Processor65C816 {
+    // Public functions
+
+    // Create a new processor with the given program loaded into RAM at $00:0800
+    // The reset vector is pointed at the program before the processor is reset
+    pub fn new(program: Vec<u8>) -> Self {
+        let offset: u16 = 0x0800;
+
+        let mut processor = Self::power_on();
+        processor.load(offset as u32, &program);
+        processor.load(RESET_VECTOR as u32, &offset.to_le_bytes());
+        processor.reset();
+
+        processor
+    }
+
+    // Create a new processor with the given memory image loaded into RAM at the given address
+    // The processor is reset, so it starts at the address in the reset vector of the image
+    pub fn with_image(image: Vec<u8>, address: u32) -> Self {
+        let mut processor = Self::power_on();
+        processor.load(address, &image);
+        processor.reset();
+
+        processor
+    }
+
+    // Reset the processor like the RESET line does
+    // The processor comes out of reset in emulation mode with the stack in page 1 and the bank
+    // and direct page registers cleared, then the program counter is loaded from $FFFC/$FFFD
+    pub fn reset(&mut self) {
+        self.registers.emulation = true;
+        self.registers.pbr = 0x00;
+        self.registers.dbr = 0x00;
+        self.registers.dp = 0x0000;
+        self.registers.sp = 0x0100 | (self.registers.sp.wrapping_sub(3) & 0x00FF);
+        self.registers
+            .status
+            .insert(MEMORY | INDEX | Status::INTERRUPT);
+        self.registers.status.remove(Status::DECIMAL);
+        self.update_widths();
+
+        self.registers.pc = self.read_word(RESET_VECTOR as u32);
+        self.control.reset();
+    }
+
+    // Map a device to the given range of the 24-bit address space
+    pub fn map(&mut self, start: u32, end: u32, device: Box<dyn Device>) -> Result<(), Error> {
+        self.device_mapper.map(start, end, device)
+    }
+
+    // Unmap the device mapped to exactly the given range of the 24-bit address space
+    pub fn unmap(&mut self, start: u32, end: u32) -> Result<(), Error> {
+        self.device_mapper.unmap(start, end)
+    }
+
+    // Run the processor until it is halted or waiting for an interrupt that is not requested
+    // Stop at the first instruction that fails
+    pub fn run(&mut self) -> Result<(), Error> {
+        while self.control.is_running() {
+            self.step()?;
+        }
+
+        Ok(())
+    }
+
+    // Assert or deassert the IRQ line
+    pub fn set_irq(&mut self, asserted: bool) {
+        self.control.set_irq(asserted);
+    }
+
+    // Assert or deassert the NMI line
+    pub fn set_nmi(&mut self, asserted: bool) {
+        self.control.set_nmi(asserted);
+    }
+
+    // Run the processor for one instruction
+    // A pending interrupt is serviced instead of the next instruction
+    // Nothing happens while the processor is waiting without an interrupt request
+    // An instruction that fails leaves the registers as they were before it
+    pub fn step(&mut self) -> Result<(), Error> {
+        if self.control.halted {
+            return Err(Error::Halted);
+        }
+
+        let registers = self.registers;
+        let saved = self.control.save();
+        let interrupt_disabled = self.registers.status.contains(Status::INTERRUPT);
+        let Some(entry) = self.control.next_entry(interrupt_disabled) else {
+            return Ok(());
+        };
+
+        match entry {
+            Entry::Nmi => self.interrupt(NATIVE_NMI_VECTOR, NMI_VECTOR, false),
+            Entry::Irq => self.interrupt(NATIVE_IRQ_VECTOR, IRQ_VECTOR, false),
+            Entry::Instruction => {
+                let instruction = self.fetch8();
+                self.execute(instruction);
+            }
+        }
+
+        if let Some(error) = self.control.take_fault() {
+            self.registers = registers;
+            self.control.restore(saved);
+            return Err(error);
+        }
+
+        Ok(())
+    }
+
+    // Check if the processor is halted, only a reset gets it running again
+    pub fn is_halted(&self) -> bool {
+        self.control.halted
+    }
+
+    // Check if the processor is waiting for an interrupt after a WAI
+    pub fn is_waiting(&self) -> bool {
+        self.control.waiting
+    }
+
+    // Check if the processor is in emulation mode
+    pub fn is_emulation(&self) -> bool {
+        self.registers.emulation
+    }
+
+    // Get the registers of the processor
+    pub fn state(&self) -> CpuState65C816 {
+        CpuState65C816 {
+            pc: self.registers.pc,
+            pbr: self.registers.pbr,
+            dbr: self.registers.dbr,
+            dp: self.registers.dp,
+            sp: self.registers.sp,
+            acc: self.registers.acc,
+            x: self.registers.x,
+            y: self.registers.y,
+            status: self.registers.status,
+            emulation: self.registers.emulation,
+        }
+    }
+
+    // Set the registers of the processor
+    // In emulation mode the stack is kept in page 1 and the registers are 8 bits wide,
+    // the high bytes of X and Y are cleared when the index registers are 8 bits wide
+    pub fn set_state(&mut self, state: CpuState65C816) {
+        self.registers.pc = state.pc;
+        self.registers.pbr = state.pbr;
+        self.registers.dbr = state.dbr;
+        self.registers.dp = state.dp;
+        self.registers.sp = state.sp;
+        self.registers.acc = state.acc;
+        self.registers.x = state.x;
+        self.registers.y = state.y;
+        self.registers.status = state.status;
+        self.registers.emulation = state.emulation;
+
+        if self.registers.emulation {
+            self.registers.sp = 0x0100 | (self.registers.sp & 0x00FF);
+        }
+        self.update_widths();
+    }
+
+    // Read a byte from the device mapped to the given address, without running a cycle
+    pub fn read_memory(&self, address: u32) -> Result<u8, Error> {
+        self.device_mapper.read(address)
+    }
+
+    // Write a byte to the device mapped to the given address, without running a cycle
+    pub fn write_memory(&mut self, address: u32, byte: u8) -> Result<(), Error> {
+        self.device_mapper.write(address, byte)
+    }
+
+    // Private functions
+
+    // Create a processor in its power-on state, before the first reset
+    fn power_on() -> Self {
+        // Create RAM (device_mapper) and registers
+        let registers = Registers65C816::new();
+        let device_mapper = DeviceMapper::new(ADDRESS_SPACE);
+
+        Self {
+            registers,
+            device_mapper,
+            control: Control::new(),
+        }
+    }
+
+    // Write the given bytes to memory starting at the given address
+    fn load(&mut self, address: u32, data: &[u8]) {
+        for (i, byte) in data.iter().enumerate() {
+            self.write(address.wrapping_add(i as u32), *byte);
+        }
+    }
+
+    // Read a byte from the given address, wrapping around the 24-bit address space
+    // An unmapped address reads as zero and fails the instruction
+    fn read(&mut self, address: u32) -> u8 {
+        self.device_mapper
+            .read(address % ADDRESS_SPACE)
+            .unwrap_or_else(|error| {
+                self.control.fail(error);
+                0
+            })
+    }
+
+    // Write a byte to the given address, wrapping around the 24-bit address space
+    // An unmapped address fails the instruction
+    fn write(&mut self, address: u32, byte: u8) {
+        if let Err(error) = self.device_mapper.write(address % ADDRESS_SPACE, byte) {
+            self.control.fail(error);
+        }
+    }
+
+    // Read a 16-bit value from the given address (little endian, low byte first)
+    fn read_word(&mut self, address: u32) -> u16 {
+        let low = self.read(address);
+        let high = self.read(address.wrapping_add(1));
+
+        u16::from_le_bytes([low, high])
+    }
+
+    // Read an 8 or 16-bit value from the given address
+    fn read_data(&mut self, address: u32, wide: bool) -> u16 {
+        if wide {
+            self.read_word(address)
+        } else {
+            self.read(address) as u16
+        }
+    }
+
+    // Write an 8 or 16-bit value to the given address
+    fn write_data(&mut self, address: u32, value: u16, wide: bool) {
+        self.write(address, value as u8);
+        if wide {
+            self.write(address.wrapping_add(1), (value >> 8) as u8);
+        }
+    }
+
+    // Fetch an 8-bit value from the program counter in the program bank
+    fn fetch8(&mut self) -> u8 {
+        // Read the byte at the PC
+        let byte = self.read(self.program_address(self.registers.pc));
+
+        // Increment the PC, it wraps around within the program bank
+        self.registers.pc = self.registers.pc.wrapping_add(1);
+
+        byte
+    }
+
+    // Fetch a 16-bit value from the program counter
+    fn fetch16(&mut self) -> u16 {
+        let low = self.fetch8();
+        let high = self.fetch8();
+
+        u16::from_le_bytes([low, high])
+    }
+
+    // Fetch a 24-bit value from the program counter
+    fn fetch24(&mut self) -> u32 {
+        let low = self.fetch16();
+        let bank = self.fetch8();
+
+        (bank as u32) << 16 | low as u32
+    }
+
+    // Push a byte on the stack
+    // In emulation mode the stack stays in page 1 like on the 6502
+    fn push(&mut self, byte: u8) {
+        self.write(self.registers.sp as u32, byte);
+
+        self.registers.sp = self.registers.sp.wrapping_sub(1);
+        if self.registers.emulation {
+            self.registers.sp = 0x0100 | (self.registers.sp & 0x00FF);
+        }
+    }
+
+    // Pull a byte from the stack
+    fn pull(&mut self) -> u8 {
+        self.registers.sp = self.registers.sp.wrapping_add(1);
+        if self.registers.emulation {
+            self.registers.sp = 0x0100 | (self.registers.sp & 0x00FF);
+        }
+
+        self.read(self.registers.sp as u32)
+    }
+
+    // Push a 16-bit value on the stack, high byte first
+    fn push16(&mut self, value: u16) {
+        self.push((value >> 8) as u8);
+        self.push(value as u8);
+    }
+
+    // Pull a 16-bit value from the stack, low byte first
+    fn pull16(&mut self) -> u16 {
+        let low = self.pull();
+        let high = self.pull();
+
+        u16::from_le_bytes([low, high])
+    }
+
+    // Handle an interrupt, pushing the return address and status and jumping to the vector
+    // In native mode the program bank is pushed as well and the break flag does not exist
+    fn interrupt(&mut self, native_vector: u16, emulation_vector: u16, break_command: bool) {
+        let vector = if self.registers.emulation {
+            self.push16(self.registers.pc);
+
+            let mut status = self.registers.status | Status::UNUSED;
+            status.set(Status::BREAK, break_command);
+            self.push(status.bits());
+
+            emulation_vector
+        } else {
+            self.push(self.registers.pbr);
+            self.push16(self.registers.pc);
+            self.push(self.registers.status.bits());
+
+            native_vector
+        };
+
+        self.registers.status.insert(Status::INTERRUPT);
+        self.registers.status.remove(Status::DECIMAL);
+        self.registers.pbr = 0x00;
+        self.registers.pc = self.read_word(vector as u32);
+    }
+
+    // Check if the accumulator and memory accesses are 8 bits wide
+    fn m8(&self) -> bool {
+        self.registers.emulation || self.registers.status.contains(MEMORY)
+    }
+
+    // Check if the index registers are 8 bits wide
+    fn x8(&self) -> bool {
+        self.registers.emulation || self.registers.status.contains(INDEX)
+    }
+
+    // Apply the register widths after the status or emulation flag changed
+    // Emulation mode forces 8-bit registers and 8-bit index registers lose their high byte
+    fn update_widths(&mut self) {
+        if self.registers.emulation {
+            self.registers.status.insert(MEMORY | INDEX);
+        }
+
+        if self.x8() {
+            self.registers.x &= 0x00FF;
+            self.registers.y &= 0x00FF;
+        }
+    }
+
+    // Get the accumulator, only the A half of it when it is 8 bits wide
+    fn acc(&self) -> u16 {
+        if self.m8() {
+            self.registers.acc & 0x00FF
+        } else {
+            self.registers.acc
+        }
+    }
+
+    // Set the accumulator, the B half is left alone when it is 8 bits wide
+    fn set_acc(&mut self, value: u16) {
+        if self.m8() {
+            self.registers.acc = (self.registers.acc & 0xFF00) | (value & 0x00FF);
+        } else {
+            self.registers.acc = value;
+        }
+    }
+
+    // Set the X register, truncated to 8 bits when the index registers are 8 bits wide
+    fn set_x(&mut self, value: u16) {
+        self.registers.x = value & mask(!self.x8());
+    }
+
+    // Set the Y register, truncated to 8 bits when the index registers are 8 bits wide
+    fn set_y(&mut self, value: u16) {
+        self.registers.y = value & mask(!self.x8());
+    }
+
+    // Set the zero and negative flags for an 8 or 16-bit value
+    fn set_nz(&mut self, value: u16, wide: bool) {
+        let value = value & mask(wide);
+
+        self.registers.status.set(Status::ZERO, value == 0x0000);
+        self.registers
+            .status
+            .set(Status::NEGATIVE, value & sign(wide) != 0);
+    }
+
+    // Get the address in the program bank
+    fn program_address(&self, address: u16) -> u32 {
+        (self.registers.pbr as u32) << 16 | address as u32
+    }
+
+    // Get the address in the data bank
+    fn data_address(&self, address: u16) -> u32 {
+        (self.registers.dbr as u32) << 16 | address as u32
+    }
+
+    // Get the address in the direct page, which is always in bank 0
+    // In emulation mode with the direct page on a page boundary it wraps around within the page
+    fn direct_address(&self, offset: u16) -> u32 {
+        let dp = self.registers.dp;
+
+        if self.registers.emulation && dp & 0x00FF == 0 {
+            (dp | (offset & 0x00FF)) as u32
+        } else {
+            dp.wrapping_add(offset) as u32
+        }
+    }
+
+    // Read a 16-bit pointer from the direct page
+    fn direct_pointer(&mut self, offset: u16) -> u16 {
+        let low = self.read(self.direct_address(offset));
+        let high = self.read(self.direct_address(offset.wrapping_add(1)));
+
+        u16::from_le_bytes([low, high])
+    }
+
+    // Read a 24-bit pointer from the direct page
+    fn direct_pointer_long(&mut self, offset: u16) -> u32 {
+        let low = self.direct_pointer(offset);
+        let bank = self.read(self.direct_address(offset.wrapping_add(2)));
+
+        (bank as u32) << 16 | low as u32
+    }
+
+    // Get the effective address of the operand for the given addressing mode
+    fn address(&mut self, mode: Mode) -> u32 {
+        match mode {
+            Immediate | ImmediateIndex => {
+                // The operand follows the opcode and is as wide as the register it is used with
+                let address = self.program_address(self.registers.pc);
+                let wide = match mode {
+                    Immediate => !self.m8(),
+                    _ => !self.x8(),
+                };
+                let size = if wide { 2 } else { 1 };
+                self.registers.pc = self.registers.pc.wrapping_add(size);
+                address
+            }
+            Absolute => {
+                let address = self.fetch16();
+                self.data_address(address)
+            }
+            AbsoluteX => {
+                let address = self.fetch16();
+                self.data_address(address) + self.registers.x as u32
+            }
+            AbsoluteY => {
+                let address = self.fetch16();
+                self.data_address(address) + self.registers.y as u32
+            }
+            AbsoluteLong => self.fetch24(),
+            AbsoluteLongX => self.fetch24() + self.registers.x as u32,
+            Direct => {
+                let offset = self.fetch8() as u16;
+                self.direct_address(offset)
+            }
+            DirectX => {
+                let offset = (self.fetch8() as u16).wrapping_add(self.registers.x);
+                self.direct_address(offset)
+            }
+            DirectY => {
+                let offset = (self.fetch8() as u16).wrapping_add(self.registers.y);
+                self.direct_address(offset)
+            }
+            DirectIndirect => {
+                let offset = self.fetch8() as u16;
+                let pointer = self.direct_pointer(offset);
+                self.data_address(pointer)
+            }
+            DirectIndirectLong => {
+                let offset = self.fetch8() as u16;
+                self.direct_pointer_long(offset)
+            }
+            DirectXIndirect => {
+                let offset = (self.fetch8() as u16).wrapping_add(self.registers.x);
+                let pointer = self.direct_pointer(offset);
+                self.data_address(pointer)
+            }
+            DirectIndirectY => {
+                let offset = self.fetch8() as u16;
+                let pointer = self.direct_pointer(offset);
+                self.data_address(pointer) + self.registers.y as u32
+            }
+            DirectIndirectLongY => {
+                let offset = self.fetch8() as u16;
+                self.direct_pointer_long(offset) + self.registers.y as u32
+            }
+            StackRelative => {
+                let offset = self.fetch8() as u16;
+                self.registers.sp.wrapping_add(offset) as u32
+            }
+            StackRelativeIndirectY => {
+                let offset = self.fetch8() as u16;
+                let pointer = self.read_word(self.registers.sp.wrapping_add(offset) as u32);
+                self.data_address(pointer) + self.registers.y as u32
+            }
+        }
+    }
+
+    // Read the operand for the given addressing mode, as wide as the accumulator
+    fn read_m(&mut self, mode: Mode) -> u16 {
+        let address = self.address(mode);
+        self.read_data(address, !self.m8())
+    }
+
+    // Read the operand for the given addressing mode, as wide as the index registers
+    fn read_x(&mut self, mode: Mode) -> u16 {
+        let address = self.address(mode);
+        self.read_data(address, !self.x8())
+    }
+
+    // Execute the given opcode, every opcode is defined on the 65C816
+    fn execute(&mut self, opcode: u8) {
+        match opcode {
+            // Load/store
+
+            // Load accumulator
+            LDA_IM => self.lda(Immediate),
+            LDA_ZP => self.lda(Direct),
+            LDA_ZPX => self.lda(DirectX),
+            LDA_ABS => self.lda(Absolute),
+            LDA_ABSX => self.lda(AbsoluteX),
+            LDA_ABSY => self.lda(AbsoluteY),
+            LDA_INDX => self.lda(DirectXIndirect),
+            LDA_INDY => self.lda(DirectIndirectY),
+            LDA_ZPI => self.lda(DirectIndirect),
+            LDA_SR => self.lda(StackRelative),
+            LDA_SRIY => self.lda(StackRelativeIndirectY),
+            LDA_INDL => self.lda(DirectIndirectLong),
+            LDA_INDLY => self.lda(DirectIndirectLongY),
+            LDA_ABSL => self.lda(AbsoluteLong),
+            LDA_ABSLX => self.lda(AbsoluteLongX),
+
+            // Load X register
+            LDX_IM => self.ldx(ImmediateIndex),
+            LDX_ZP => self.ldx(Direct),
+            LDX_ZPY => self.ldx(DirectY),
+            LDX_ABS => self.ldx(Absolute),
+            LDX_ABSY => self.ldx(AbsoluteY),
+
+            // Load Y register
+            LDY_IM => self.ldy(ImmediateIndex),
+            LDY_ZP => self.ldy(Direct),
+            LDY_ZPX => self.ldy(DirectX),
+            LDY_ABS => self.ldy(Absolute),
+            LDY_ABSX => self.ldy(AbsoluteX),
+
+            // Store accumulator
+            STA_ZP => self.sta(Direct),
+            STA_ZPX => self.sta(DirectX),
+            STA_ABS => self.sta(Absolute),
+            STA_ABSX => self.sta(AbsoluteX),
+            STA_ABSY => self.sta(AbsoluteY),
+            STA_INDX => self.sta(DirectXIndirect),
+            STA_INDY => self.sta(DirectIndirectY),
+            STA_ZPI => self.sta(DirectIndirect),
+            STA_SR => self.sta(StackRelative),
+            STA_SRIY => self.sta(StackRelativeIndirectY),
+            STA_INDL => self.sta(DirectIndirectLong),
+            STA_INDLY => self.sta(DirectIndirectLongY),
+            STA_ABSL => self.sta(AbsoluteLong),
+            STA_ABSLX => self.sta(AbsoluteLongX),
+
+            // Store X register
+            STX_ZP => self.stx(Direct),
+            STX_ZPY => self.stx(DirectY),
+            STX_ABS => self.stx(Absolute),
+
+            // Store Y register
+            STY_ZP => self.sty(Direct),
+            STY_ZPX => self.sty(DirectX),
+            STY_ABS => self.sty(Absolute),
+
+            // Store zero
+            STZ_ZP => self.stz(Direct),
+            STZ_ZPX => self.stz(DirectX),
+            STZ_ABS => self.stz(Absolute),
+            STZ_ABSX => self.stz(AbsoluteX),
+
+            // Register transfers
+
+            // Transfer accumulator to X
+            TAX => self.tax(),
+
+            // Transfer accumulator to Y
+            TAY => self.tay(),
+
+            // Transfer X to accumulator
+            TXA => self.txa(),
+
+            // Transfer Y to accumulator
+            TYA => self.tya(),
+
+            // Transfer stack pointer to X
+            TSX => self.tsx(),
+
+            // Transfer X to stack pointer
+            TXS => self.txs(),
+
+            // Transfer X to Y
+            TXY => self.txy(),
+
+            // Transfer Y to X
+            TYX => self.tyx(),
+
+            // Transfer accumulator to direct page register
+            TCD => self.tcd(),
+
+            // Transfer direct page register to accumulator
+            TDC => self.tdc(),
+
+            // Transfer accumulator to stack pointer
+            TCS => self.tcs(),
+
+            // Transfer stack pointer to accumulator
+            TSC => self.tsc(),
+
+            // Exchange the B and A accumulators
+            XBA => self.xba(),
+
+            // Stack
+
+            // Push accumulator on stack
+            PHA => self.pha(),
+
+            // Push processor status on stack
+            PHP => self.php(),
+
+            // Pull accumulator from stack
+            PLA => self.pla(),
+
+            // Pull processor status from stack
+            PLP => self.plp(),
+
+            // Push X register on stack
+            PHX => self.phx(),
+
+            // Push Y register on stack
+            PHY => self.phy(),
+
+            // Pull X register from stack
+            PLX => self.plx(),
+
+            // Pull Y register from stack
+            PLY => self.ply(),
+
+            // Push data bank register on stack
+            PHB => self.phb(),
+
+            // Push direct page register on stack
+            PHD => self.phd(),
+
+            // Push program bank register on stack
+            PHK => self.phk(),
+
+            // Pull data bank register from stack
+            PLB => self.plb(),
+
+            // Pull direct page register from stack
+            PLD => self.pld(),
+
+            // Push effective absolute address
+            PEA => self.pea(),
+
+            // Push effective indirect address
+            PEI => self.pei(),
+
+            // Push effective relative address
+            PER => self.per(),
+
+            // Logical
+
+            // Logical AND
+            AND_IM => self.and(Immediate),
+            AND_ZP => self.and(Direct),
+            AND_ZPX => self.and(DirectX),
+            AND_ABS => self.and(Absolute),
+            AND_ABSX => self.and(AbsoluteX),
+            AND_ABSY => self.and(AbsoluteY),
+            AND_INDX => self.and(DirectXIndirect),
+            AND_INDY => self.and(DirectIndirectY),
+            AND_ZPI => self.and(DirectIndirect),
+            AND_SR => self.and(StackRelative),
+            AND_SRIY => self.and(StackRelativeIndirectY),
+            AND_INDL => self.and(DirectIndirectLong),
+            AND_INDLY => self.and(DirectIndirectLongY),
+            AND_ABSL => self.and(AbsoluteLong),
+            AND_ABSLX => self.and(AbsoluteLongX),
+
+            // Logical EOR
+            EOR_IM => self.eor(Immediate),
+            EOR_ZP => self.eor(Direct),
+            EOR_ZPX => self.eor(DirectX),
+            EOR_ABS => self.eor(Absolute),
+            EOR_ABSX => self.eor(AbsoluteX),
+            EOR_ABSY => self.eor(AbsoluteY),
+            EOR_INDX => self.eor(DirectXIndirect),
+            EOR_INDY => self.eor(DirectIndirectY),
+            EOR_ZPI => self.eor(DirectIndirect),
+            EOR_SR => self.eor(StackRelative),
+            EOR_SRIY => self.eor(StackRelativeIndirectY),
+            EOR_INDL => self.eor(DirectIndirectLong),
+            EOR_INDLY => self.eor(DirectIndirectLongY),
+            EOR_ABSL => self.eor(AbsoluteLong),
+            EOR_ABSLX => self.eor(AbsoluteLongX),
+
+            // Logical ORA
+            ORA_IM => self.ora(Immediate),
+            ORA_ZP => self.ora(Direct),
+            ORA_ZPX => self.ora(DirectX),
+            ORA_ABS => self.ora(Absolute),
+            ORA_ABSX => self.ora(AbsoluteX),
+            ORA_ABSY => self.ora(AbsoluteY),
+            ORA_INDX => self.ora(DirectXIndirect),
+            ORA_INDY => self.ora(DirectIndirectY),
+            ORA_ZPI => self.ora(DirectIndirect),
+            ORA_SR => self.ora(StackRelative),
+            ORA_SRIY => self.ora(StackRelativeIndirectY),
+            ORA_INDL => self.ora(DirectIndirectLong),
+            ORA_INDLY => self.ora(DirectIndirectLongY),
+            ORA_ABSL => self.ora(AbsoluteLong),
+            ORA_ABSLX => self.ora(AbsoluteLongX),
+
+            // Bit test
+            BIT_IM => self.bit(Immediate),
+            BIT_ZP => self.bit(Direct),
+            BIT_ZPX => self.bit(DirectX),
+            BIT_ABS => self.bit(Absolute),
+            BIT_ABSX => self.bit(AbsoluteX),
+
+            // Test and reset bits
+            TRB_ZP => self.trb(Direct),
+            TRB_ABS => self.trb(Absolute),
+
+            // Test and set bits
+            TSB_ZP => self.tsb(Direct),
+            TSB_ABS => self.tsb(Absolute),
+
+            // Arithmetic
+
+            // Add with carry
+            ADC_IM => self.adc(Immediate),
+            ADC_ZP => self.adc(Direct),
+            ADC_ZPX => self.adc(DirectX),
+            ADC_ABS => self.adc(Absolute),
+            ADC_ABSX => self.adc(AbsoluteX),
+            ADC_ABSY => self.adc(AbsoluteY),
+            ADC_INDX => self.adc(DirectXIndirect),
+            ADC_INDY => self.adc(DirectIndirectY),
+            ADC_ZPI => self.adc(DirectIndirect),
+            ADC_SR => self.adc(StackRelative),
+            ADC_SRIY => self.adc(StackRelativeIndirectY),
+            ADC_INDL => self.adc(DirectIndirectLong),
+            ADC_INDLY => self.adc(DirectIndirectLongY),
+            ADC_ABSL => self.adc(AbsoluteLong),
+            ADC_ABSLX => self.adc(AbsoluteLongX),
+
+            // Subtract with carry
+            SBC_IM => self.sbc(Immediate),
+            SBC_ZP => self.sbc(Direct),
+            SBC_ZPX => self.sbc(DirectX),
+            SBC_ABS => self.sbc(Absolute),
+            SBC_ABSX => self.sbc(AbsoluteX),
+            SBC_ABSY => self.sbc(AbsoluteY),
+            SBC_INDX => self.sbc(DirectXIndirect),
+            SBC_INDY => self.sbc(DirectIndirectY),
+            SBC_ZPI => self.sbc(DirectIndirect),
+            SBC_SR => self.sbc(StackRelative),
+            SBC_SRIY => self.sbc(StackRelativeIndirectY),
+            SBC_INDL => self.sbc(DirectIndirectLong),
+            SBC_INDLY => self.sbc(DirectIndirectLongY),
+            SBC_ABSL => self.sbc(AbsoluteLong),
+            SBC_ABSLX => self.sbc(AbsoluteLongX),
+
+            // Compare accumulator
+            CMP_IM => self.cmp(Immediate),
+            CMP_ZP => self.cmp(Direct),
+            CMP_ZPX => self.cmp(DirectX),
+            CMP_ABS => self.cmp(Absolute),
+            CMP_ABSX => self.cmp(AbsoluteX),
+            CMP_ABSY => self.cmp(AbsoluteY),
+            CMP_INDX => self.cmp(DirectXIndirect),
+            CMP_INDY => self.cmp(DirectIndirectY),
+            CMP_ZPI => self.cmp(DirectIndirect),
+            CMP_SR => self.cmp(StackRelative),
+            CMP_SRIY => self.cmp(StackRelativeIndirectY),
+            CMP_INDL => self.cmp(DirectIndirectLong),
+            CMP_INDLY => self.cmp(DirectIndirectLongY),
+            CMP_ABSL => self.cmp(AbsoluteLong),
+            CMP_ABSLX => self.cmp(AbsoluteLongX),
+
+            // Compare X register
+            CPX_IM => self.cpx(ImmediateIndex),
+            CPX_ZP => self.cpx(Direct),
+            CPX_ABS => self.cpx(Absolute),
+
+            // Compare Y register
+            CPY_IM => self.cpy(ImmediateIndex),
+            CPY_ZP => self.cpy(Direct),
+            CPY_ABS => self.cpy(Absolute),
+
+            // Increments/decrements
+
+            // Increment memory
+            INC_ZP => self.inc_memory(Direct),
+            INC_ZPX => self.inc_memory(DirectX),
+            INC_ABS => self.inc_memory(Absolute),
+            INC_ABSX => self.inc_memory(AbsoluteX),
+
+            // Increment accumulator
+            INC_ACC => self.inc_accumulator(),
+
+            // Increment X register
+            INX => self.inx(),
+
+            // Increment Y register
+            INY => self.iny(),
+
+            // Decrement memory
+            DEC_ZP => self.dec_memory(Direct),
+            DEC_ZPX => self.dec_memory(DirectX),
+            DEC_ABS => self.dec_memory(Absolute),
+            DEC_ABSX => self.dec_memory(AbsoluteX),
+
+            // Decrement accumulator
+            DEC_ACC => self.dec_accumulator(),
+
+            // Decrement X register
+            DEX => self.dex(),
+
+            // Decrement Y register
+            DEY => self.dey(),
+
+            // Shifts
+
+            // Arithmetic shift left
+            ASL_ACC => self.asl_accumulator(),
+            ASL_ZP => self.asl_memory(Direct),
+            ASL_ZPX => self.asl_memory(DirectX),
+            ASL_ABS => self.asl_memory(Absolute),
+            ASL_ABSX => self.asl_memory(AbsoluteX),
+
+            // Logical shift right
+            LSR_ACC => self.lsr_accumulator(),
+            LSR_ZP => self.lsr_memory(Direct),
+            LSR_ZPX => self.lsr_memory(DirectX),
+            LSR_ABS => self.lsr_memory(Absolute),
+            LSR_ABSX => self.lsr_memory(AbsoluteX),
+
+            // Rotate left
+            ROL_ACC => self.rol_accumulator(),
+            ROL_ZP => self.rol_memory(Direct),
+            ROL_ZPX => self.rol_memory(DirectX),
+            ROL_ABS => self.rol_memory(Absolute),
+            ROL_ABSX => self.rol_memory(AbsoluteX),
+
+            // Rotate right
+            ROR_ACC => self.ror_accumulator(),
+            ROR_ZP => self.ror_memory(Direct),
+            ROR_ZPX => self.ror_memory(DirectX),
+            ROR_ABS => self.ror_memory(Absolute),
+            ROR_ABSX => self.ror_memory(AbsoluteX),
+
+            // Jumps/calls
+
+            // Jump to another location
+            JMP_ABS => self.jmp_absolute(),
+            JMP_IND => self.jmp_indirect(),
+            JMP_INDX => self.jmp_indirect_x(),
+
+            // Jump long
+            JML => self.jml_absolute(),
+            JML_IND => self.jml_indirect(),
+
+            // Jump to subroutine
+            JSR => self.jsr_absolute(),
+            JSR_INDX => self.jsr_indirect_x(),
+
+            // Jump to subroutine long
+            JSL => self.jsl(),
+
+            // Return from subroutine
+            RTS => self.rts(),
+
+            // Return from subroutine long
+            RTL => self.rtl(),
+
+            // Branches
+
+            // Branch if positive
+            BPL => self.bpl(),
+
+            // Branch if minus
+            BMI => self.bmi(),
+
+            // Branch if overflow clear
+            BVC => self.bvc(),
+
+            // Branch if overflow set
+            BVS => self.bvs(),
+
+            // Branch if carry clear
+            BCC => self.bcc(),
+
+            // Branch if carry set
+            BCS => self.bcs(),
+
+            // Branch if not equal
+            BNE => self.bne(),
+
+            // Branch if equal
+            BEQ => self.beq(),
+
+            // Branch always
+            BRA => self.bra(),
+
+            // Branch always long
+            BRL => self.brl(),
+
+            // Status flags
+
+            // Clear carry flag
+            CLC => self.clc(),
+
+            // Clear decimal mode flag
+            CLD => self.cld(),
+
+            // Clear interrupt disable flag
+            CLI => self.cli(),
+
+            // Clear overflow flag
+            CLV => self.clv(),
+
+            // Set carry flag
+            SEC => self.sec(),
+
+            // Set decimal mode flag
+            SED => self.sed(),
+
+            // Set interrupt disable flag
+            SEI => self.sei(),
+
+            // Reset status bits
+            REP => self.rep(),
+
+            // Set status bits
+            SEP => self.sep(),
+
+            // Exchange carry and emulation flags
+            XCE => self.xce(),
+
+            // Block moves
+
+            // Block move next
+            MVN => self.mvn(),
+
+            // Block move previous
+            MVP => self.mvp(),
+
+            // System
+
+            // Force interrupt
+            BRK => self.brk(),
+
+            // Co-processor interrupt
+            COP => self.cop(),
+
+            // No operation
+            NOP => self.nop(),
+
+            // Return from interrupt
+            RTI => self.rti(),
+
+            // Reserved two byte no operation
+            WDM => self.wdm(),
+
+            // Wait for interrupt
+            WAI => self.wai(),
+
+            // Stop the processor
+            STP => self.stp(),
+        }
+    }
+
+    // Load/store
+
+    // Load accumulator
+    fn lda(&mut self, mode: Mode) {
+        let value = self.read_m(mode);
+        self.set_acc(value);
+        self.set_nz(value, !self.m8());
+    }
+
+    // Load X register
+    fn ldx(&mut self, mode: Mode) {
+        let value = self.read_x(mode);
+        self.set_x(value);
+        self.set_nz(value, !self.x8());
+    }
+
+    // Load Y register
+    fn ldy(&mut self, mode: Mode) {
+        let value = self.read_x(mode);
+        self.set_y(value);
+        self.set_nz(value, !self.x8());
+    }
+
+    // Store accumulator
+    fn sta(&mut self, mode: Mode) {
+        let address = self.address(mode);
+        self.write_data(address, self.registers.acc, !self.m8());
+    }
+
+    // Store X register
+    fn stx(&mut self, mode: Mode) {
+        let address = self.address(mode);
+        self.write_data(address, self.registers.x, !self.x8());
+    }
+
+    // Store Y register
+    fn sty(&mut self, mode: Mode) {
+        let address = self.address(mode);
+        self.write_data(address, self.registers.y, !self.x8());
+    }
+
+    // Store zero
+    fn stz(&mut self, mode: Mode) {
+        let address = self.address(mode);
+        self.write_data(address, 0x0000, !self.m8());
+    }
+
+    // Register transfers
+
+    // Transfer accumulator to X
+    // With 16-bit index registers the whole accumulator is transferred, even when it is 8 bits wide
+    fn tax(&mut self) {
+        self.set_x(self.registers.acc);
+        self.set_nz(self.registers.x, !self.x8());
+    }
+
+    // Transfer accumulator to Y
+    fn tay(&mut self) {
+        self.set_y(self.registers.acc);
+        self.set_nz(self.registers.y, !self.x8());
+    }
+
+    // Transfer X to accumulator
+    fn txa(&mut self) {
+        self.set_acc(self.registers.x);
+        self.set_nz(self.registers.acc, !self.m8());
+    }
+
+    // Transfer Y to accumulator
+    fn tya(&mut self) {
+        self.set_acc(self.registers.y);
+        self.set_nz(self.registers.acc, !self.m8());
+    }
+
+    // Transfer stack pointer to X
+    fn tsx(&mut self) {
+        self.set_x(self.registers.sp);
+        self.set_nz(self.registers.x, !self.x8());
+    }
+
+    // Transfer X to stack pointer
+    fn txs(&mut self) {
+        self.registers.sp = if self.registers.emulation {
+            0x0100 | (self.registers.x & 0x00FF)
+        } else {
+            self.registers.x
+        };
+    }
+
+    // Transfer X to Y
+    fn txy(&mut self) {
+        self.set_y(self.registers.x);
+        self.set_nz(self.registers.y, !self.x8());
+    }
+
+    // Transfer Y to X
+    fn tyx(&mut self) {
+        self.set_x(self.registers.y);
+        self.set_nz(self.registers.x, !self.x8());
+    }
+
+    // Transfer accumulator to direct page register, always 16 bits
+    fn tcd(&mut self) {
+        self.registers.dp = self.registers.acc;
+        self.set_nz(self.registers.dp, true);
+    }
+
+    // Transfer direct page register to accumulator, always 16 bits
+    fn tdc(&mut self) {
+        self.registers.acc = self.registers.dp;
+        self.set_nz(self.registers.acc, true);
+    }
+
+    // Transfer accumulator to stack pointer, always 16 bits
+    fn tcs(&mut self) {
+        self.registers.sp = if self.registers.emulation {
+            0x0100 | (self.registers.acc & 0x00FF)
+        } else {
+            self.registers.acc
+        };
+    }
+
+    // Transfer stack pointer to accumulator, always 16 bits
+    fn tsc(&mut self) {
+        self.registers.acc = self.registers.sp;
+        self.set_nz(self.registers.acc, true);
+    }
+
+    // Exchange the B and A accumulators, the flags are set from the new A
+    fn xba(&mut self) {
+        self.registers.acc = self.registers.acc.swap_bytes();
+        self.set_nz(self.registers.acc, false);
+    }
+
+    // Stack
+
+    // Push accumulator on stack
+    fn pha(&mut self) {
+        if self.m8() {
+            self.push(self.registers.acc as u8);
+        } else {
+            self.push16(self.registers.acc);
+        }
+    }
+
+    // Push processor status on stack
+    fn php(&mut self) {
+        self.push(self.registers.status.bits());
+    }
+
+    // Pull accumulator from stack
+    fn pla(&mut self) {
+        let value = if self.m8() {
+            self.pull() as u16
+        } else {
+            self.pull16()
+        };
+        self.set_acc(value);
+        self.set_nz(value, !self.m8());
+    }
+
+    // Pull processor status from stack
+    fn plp(&mut self) {
+        let status = self.pull();
+        self.registers.status = Status::from_bits_truncate(status);
+        self.update_widths();
+    }
+
+    // Push X register on stack
+    fn phx(&mut self) {
+        if self.x8() {
+            self.push(self.registers.x as u8);
+        } else {
+            self.push16(self.registers.x);
+        }
+    }
+
+    // Push Y register on stack
+    fn phy(&mut self) {
+        if self.x8() {
+            self.push(self.registers.y as u8);
+        } else {
+            self.push16(self.registers.y);
+        }
+    }
+
+    // Pull X register from stack
+    fn plx(&mut self) {
+        let value = if self.x8() {
+            self.pull() as u16
+        } else {
+            self.pull16()
+        };
+        self.set_x(value);
+        self.set_nz(value, !self.x8());
+    }
+
+    // Pull Y register from stack
+    fn ply(&mut self) {
+        let value = if self.x8() {
+            self.pull() as u16
+        } else {
+            self.pull16()
+        };
+        self.set_y(value);
+        self.set_nz(value, !self.x8());
+    }
+
+    // Push data bank register on stack
+    fn phb(&mut self) {
+        self.push(self.registers.dbr);
+    }
+
+    // Push direct page register on stack
+    fn phd(&mut self) {
+        self.push16(self.registers.dp);
+    }
+
+    // Push program bank register on stack
+    fn phk(&mut self) {
+        self.push(self.registers.pbr);
+    }
+
+    // Pull data bank register from stack
+    fn plb(&mut self) {
+        self.registers.dbr = self.pull();
+        self.set_nz(self.registers.dbr as u16, false);
+    }
+
+    // Pull direct page register from stack
+    fn pld(&mut self) {
+        self.registers.dp = self.pull16();
+        self.set_nz(self.registers.dp, true);
+    }
+
+    // Push effective absolute address, the 16-bit operand itself
+    fn pea(&mut self) {
+        let value = self.fetch16();
+        self.push16(value);
+    }
+
+    // Push effective indirect address, the 16-bit value at the direct page operand
+    fn pei(&mut self) {
+        let offset = self.fetch8() as u16;
+        let value = self.direct_pointer(offset);
+        self.push16(value);
+    }
+
+    // Push effective relative address, the operand added to the address of the next instruction
+    fn per(&mut self) {
+        let offset = self.fetch16();
+        self.push16(self.registers.pc.wrapping_add(offset));
+    }
+
+    // Logical
+
+    // Logical AND
+    fn and(&mut self, mode: Mode) {
+        let value = self.acc() & self.read_m(mode);
+        self.set_acc(value);
+        self.set_nz(value, !self.m8());
+    }
+
+    // Logical EOR
+    fn eor(&mut self, mode: Mode) {
+        let value = self.acc() ^ self.read_m(mode);
+        self.set_acc(value);
+        self.set_nz(value, !self.m8());
+    }
+
+    // Logical ORA
+    fn ora(&mut self, mode: Mode) {
+        let value = self.acc() | self.read_m(mode);
+        self.set_acc(value);
+        self.set_nz(value, !self.m8());
+    }
+
+    // Bit test, N and V are copied from the operand except in immediate mode
+    fn bit(&mut self, mode: Mode) {
+        let wide = !self.m8();
+        let value = self.read_m(mode);
+
+        self.registers
+            .status
+            .set(Status::ZERO, self.acc() & value == 0x0000);
+
+        if !matches!(mode, Immediate) {
+            self.registers
+                .status
+                .set(Status::NEGATIVE, value & sign(wide) != 0);
+            self.registers
+                .status
+                .set(Status::OVERFLOW, value & (sign(wide) >> 1) != 0);
+        }
+    }
+
+    // Test and reset bits
+    fn trb(&mut self, mode: Mode) {
+        let wide = !self.m8();
+        let address = self.address(mode);
+        let value = self.read_data(address, wide);
+        let acc = self.acc();
+
+        self.registers
+            .status
+            .set(Status::ZERO, acc & value == 0x0000);
+        self.write_data(address, value & !acc, wide);
+    }
+
+    // Test and set bits
+    fn tsb(&mut self, mode: Mode) {
+        let wide = !self.m8();
+        let address = self.address(mode);
+        let value = self.read_data(address, wide);
+        let acc = self.acc();
+
+        self.registers
+            .status
+            .set(Status::ZERO, acc & value == 0x0000);
+        self.write_data(address, value | acc, wide);
+    }
+
+    // Arithmetic
+
+    // Add with carry
+    fn adc(&mut self, mode: Mode) {
+        let value = self.read_m(mode);
+
+        if self.registers.status.contains(Status::DECIMAL) {
+            self.adc_decimal(value)
+        } else {
+            self.adc_binary(value)
+        }
+    }
+
+    // Add with carry in binary mode
+    fn adc_binary(&mut self, value: u16) {
+        let wide = !self.m8();
+        let carry = self.registers.status.contains(Status::CARRY) as u32;
+        let acc = self.acc();
+
+        let sum = acc as u32 + value as u32 + carry;
+        let result = sum as u16 & mask(wide);
+
+        self.registers
+            .status
+            .set(Status::CARRY, sum > mask(wide) as u32);
+        self.registers.status.set(
+            Status::OVERFLOW,
+            (acc ^ result) & (value ^ result) & sign(wide) != 0,
+        );
+        self.set_acc(result);
+        self.set_nz(result, wide);
+    }
+
+    // Add with carry in decimal mode, digit by digit like the 65C02
+    // The flags are set from the decimal result, except for V which is set from the top digit
+    // before it is adjusted
+    fn adc_decimal(&mut self, value: u16) {
+        let wide = !self.m8();
+        let digits = if wide { 4 } else { 2 };
+        let acc = self.acc();
+
+        let mut carry = self.registers.status.contains(Status::CARRY) as u16;
+        let mut result = 0x0000;
+        for digit in 0..digits {
+            let shift = digit * 4;
+            let mut sum = ((acc >> shift) & 0x0F) + ((value >> shift) & 0x0F) + carry;
+
+            if digit == digits - 1 {
+                let unadjusted = result | (sum << shift);
+                self.registers.status.set(
+                    Status::OVERFLOW,
+                    (acc ^ unadjusted) & (value ^ unadjusted) & sign(wide) != 0,
+                );
+            }
+
+            if sum > 0x09 {
+                sum += 0x06;
+            }
+            carry = (sum > 0x0F) as u16;
+            result |= (sum & 0x0F) << shift;
+        }
+
+        self.registers.status.set(Status::CARRY, carry != 0);
+        self.set_acc(result);
+        self.set_nz(result, wide);
+    }
+
+    // Subtract with carry
+    // A - M - (1 - C) is the same as A + !M + C, so the carry acts as an inverted borrow
+    fn sbc(&mut self, mode: Mode) {
+        let wide = !self.m8();
+        let value = self.read_m(mode);
+        let acc = self.acc();
+        let carry = self.registers.status.contains(Status::CARRY);
+
+        // The carry and overflow flags are the same in binary and decimal mode
+        self.adc_binary(!value & mask(wide));
+
+        if self.registers.status.contains(Status::DECIMAL) {
+            let digits = if wide { 4 } else { 2 };
+
+            let mut borrow = !carry as i16;
+            let mut result = 0x0000;
+            for digit in 0..digits {
+                let shift = digit * 4;
+                let mut difference =
+                    ((acc >> shift) & 0x0F) as i16 - ((value >> shift) & 0x0F) as i16 - borrow;
+
+                borrow = (difference < 0) as i16;
+                if difference < 0 {
+                    difference += 10;
+                }
+                result |= (difference as u16 & 0x0F) << shift;
+            }
+
+            self.set_acc(result);
+            self.set_nz(result, wide);
+        }
+    }
+
+    // Compare a register with a value
+    fn compare(&mut self, register: u16, value: u16, wide: bool) {
+        self.registers.status.set(Status::CARRY, register >= value);
+        self.set_nz(register.wrapping_sub(value), wide);
+    }
+
+    // Compare accumulator
+    fn cmp(&mut self, mode: Mode) {
+        let value = self.read_m(mode);
+        self.compare(self.acc(), value, !self.m8());
+    }
+
+    // Compare X register
+    fn cpx(&mut self, mode: Mode) {
+        let value = self.read_x(mode);
+        self.compare(self.registers.x, value, !self.x8());
+    }
+
+    // Compare Y register
+    fn cpy(&mut self, mode: Mode) {
+        let value = self.read_x(mode);
+        self.compare(self.registers.y, value, !self.x8());
+    }
+
+    // Increments/decrements
+
+    // Increment a value as wide as the accumulator
+    fn inc(&mut self, value: u16) -> u16 {
+        let wide = !self.m8();
+        let result = value.wrapping_add(1) & mask(wide);
+        self.set_nz(result, wide);
+
+        result
+    }
+
+    // Increment memory
+    fn inc_memory(&mut self, mode: Mode) {
+        let wide = !self.m8();
+        let address = self.address(mode);
+        let value = self.read_data(address, wide);
+        let result = self.inc(value);
+        self.write_data(address, result, wide);
+    }
+
+    // Increment accumulator
+    fn inc_accumulator(&mut self) {
+        let value = self.inc(self.acc());
+        self.set_acc(value);
+    }
+
+    // Decrement a value as wide as the accumulator
+    fn dec(&mut self, value: u16) -> u16 {
+        let wide = !self.m8();
+        let result = value.wrapping_sub(1) & mask(wide);
+        self.set_nz(result, wide);
+
+        result
+    }
+
+    // Decrement memory
+    fn dec_memory(&mut self, mode: Mode) {
+        let wide = !self.m8();
+        let address = self.address(mode);
+        let value = self.read_data(address, wide);
+        let result = self.dec(value);
+        self.write_data(address, result, wide);
+    }
+
+    // Decrement accumulator
+    fn dec_accumulator(&mut self) {
+        let value = self.dec(self.acc());
+        self.set_acc(value);
+    }
+
+    // Increment X register
+    fn inx(&mut self) {
+        self.set_x(self.registers.x.wrapping_add(1));
+        self.set_nz(self.registers.x, !self.x8());
+    }
+
+    // Increment Y register
+    fn iny(&mut self) {
+        self.set_y(self.registers.y.wrapping_add(1));
+        self.set_nz(self.registers.y, !self.x8());
+    }
+
+    // Decrement X register
+    fn dex(&mut self) {
+        self.set_x(self.registers.x.wrapping_sub(1));
+        self.set_nz(self.registers.x, !self.x8());
+    }
+
+    // Decrement Y register
+    fn dey(&mut self) {
+        self.set_y(self.registers.y.wrapping_sub(1));
+        self.set_nz(self.registers.y, !self.x8());
+    }
+
+    // Shifts
+
+    // Arithmetic shift left a value as wide as the accumulator
+    fn asl(&mut self, value: u16) -> u16 {
+        let wide = !self.m8();
+        let result = (value << 1) & mask(wide);
+        self.registers
+            .status
+            .set(Status::CARRY, value & sign(wide) != 0);
+        self.set_nz(result, wide);
+
+        result
+    }
+
+    // Arithmetic shift left accumulator
+    fn asl_accumulator(&mut self) {
+        let value = self.asl(self.acc());
+        self.set_acc(value);
+    }
+
+    // Arithmetic shift left memory
+    fn asl_memory(&mut self, mode: Mode) {
+        let wide = !self.m8();
+        let address = self.address(mode);
+        let value = self.read_data(address, wide);
+        let result = self.asl(value);
+        self.write_data(address, result, wide);
+    }
+
+    // Logical shift right a value as wide as the accumulator
+    fn lsr(&mut self, value: u16) -> u16 {
+        let wide = !self.m8();
+        let result = value >> 1;
+        self.registers
+            .status
+            .set(Status::CARRY, value & 0x0001 != 0);
+        self.set_nz(result, wide);
+
+        result
+    }
+
+    // Logical shift right accumulator
+    fn lsr_accumulator(&mut self) {
+        let value = self.lsr(self.acc());
+        self.set_acc(value);
+    }
+
+    // Logical shift right memory
+    fn lsr_memory(&mut self, mode: Mode) {
+        let wide = !self.m8();
+        let address = self.address(mode);
+        let value = self.read_data(address, wide);
+        let result = self.lsr(value);
+        self.write_data(address, result, wide);
+    }
+
+    // Rotate left a value as wide as the accumulator
+    fn rol(&mut self, value: u16) -> u16 {
+        let wide = !self.m8();
+        let carry = self.registers.status.contains(Status::CARRY) as u16;
+        let result = ((value << 1) | carry) & mask(wide);
+        self.registers
+            .status
+            .set(Status::CARRY, value & sign(wide) != 0);
+        self.set_nz(result, wide);
+
+        result
+    }
+
+    // Rotate left accumulator
+    fn rol_accumulator(&mut self) {
+        let value = self.rol(self.acc());
+        self.set_acc(value);
+    }
+
+    // Rotate left memory
+    fn rol_memory(&mut self, mode: Mode) {
+        let wide = !self.m8();
+        let address = self.address(mode);
+        let value = self.read_data(address, wide);
+        let result = self.rol(value);
+        self.write_data(address, result, wide);
+    }
+
+    // Rotate right a value as wide as the accumulator
+    fn ror(&mut self, value: u16) -> u16 {
+        let wide = !self.m8();
+        let carry = self.registers.status.contains(Status::CARRY);
+        let mut result = value >> 1;
+        if carry {
+            result |= sign(wide);
+        }
+        self.registers
+            .status
+            .set(Status::CARRY, value & 0x0001 != 0);
+        self.set_nz(result, wide);
+
+        result
+    }
+
+    // Rotate right accumulator
+    fn ror_accumulator(&mut self) {
+        let value = self.ror(self.acc());
+        self.set_acc(value);
+    }
+
+    // Rotate right memory
+    fn ror_memory(&mut self, mode: Mode) {
+        let wide = !self.m8();
+        let address = self.address(mode);
+        let value = self.read_data(address, wide);
+        let result = self.ror(value);
+        self.write_data(address, result, wide);
+    }
+
+    // Jumps/calls
+
+    // Jump absolute, within the program bank
+    fn jmp_absolute(&mut self) {
+        self.registers.pc = self.fetch16();
+    }
+
+    // Jump indirect, the pointer is in bank 0
+    fn jmp_indirect(&mut self) {
+        let pointer = self.fetch16();
+        self.registers.pc = self.read_word(pointer as u32);
+    }
+
+    // Jump indexed indirect, the pointer is in the program bank
+    fn jmp_indirect_x(&mut self) {
+        let pointer = self.fetch16().wrapping_add(self.registers.x);
+        self.registers.pc = self.read_word(self.program_address(pointer));
+    }
+
+    // Jump long absolute
+    fn jml_absolute(&mut self) {
+        let address = self.fetch24();
+        self.registers.pc = address as u16;
+        self.registers.pbr = (address >> 16) as u8;
+    }
+
+    // Jump long indirect, the 24-bit pointer is in bank 0
+    fn jml_indirect(&mut self) {
+        let pointer = self.fetch16() as u32;
+        self.registers.pc = self.read_word(pointer);
+        self.registers.pbr = self.read(pointer + 2);
+    }
+
+    // Jump to subroutine absolute
+    fn jsr_absolute(&mut self) {
+        let address = self.fetch16();
+
+        // The return address pushed is the last byte of the instruction
+        self.push16(self.registers.pc.wrapping_sub(1));
+        self.registers.pc = address;
+    }
+
+    // Jump to subroutine indexed indirect, the pointer is in the program bank
+    fn jsr_indirect_x(&mut self) {
+        let pointer = self.fetch16().wrapping_add(self.registers.x);
+
+        self.push16(self.registers.pc.wrapping_sub(1));
+        self.registers.pc = self.read_word(self.program_address(pointer));
+    }
+
+    // Jump to subroutine long, the program bank is pushed before the return address
+    fn jsl(&mut self) {
+        let address = self.fetch24();
+
+        self.push(self.registers.pbr);
+        self.push16(self.registers.pc.wrapping_sub(1));
+        self.registers.pc = address as u16;
+        self.registers.pbr = (address >> 16) as u8;
+    }
+
+    // Return from subroutine
+    fn rts(&mut self) {
+        self.registers.pc = self.pull16().wrapping_add(1);
+    }
+
+    // Return from subroutine long
+    fn rtl(&mut self) {
+        self.registers.pc = self.pull16().wrapping_add(1);
+        self.registers.pbr = self.pull();
+    }
+
+    // Branches
+
+    // Branch with an 8-bit offset if the condition is true
+    // The offset is signed and relative to the next instruction, within the program bank
+    fn branch(&mut self, condition: bool) {
+        let offset = self.fetch8() as i8;
+
+        if condition {
+            self.registers.pc = self.registers.pc.wrapping_add(offset as u16);
+        }
+    }
+
+    // Branch if positive
+    fn bpl(&mut self) {
+        let condition = !self.registers.status.contains(Status::NEGATIVE);
+        self.branch(condition)
+    }
+
+    // Branch if minus
+    fn bmi(&mut self) {
+        let condition = self.registers.status.contains(Status::NEGATIVE);
+        self.branch(condition)
+    }
+
+    // Branch if overflow clear
+    fn bvc(&mut self) {
+        let condition = !self.registers.status.contains(Status::OVERFLOW);
+        self.branch(condition)
+    }
+
+    // Branch if overflow set
+    fn bvs(&mut self) {
+        let condition = self.registers.status.contains(Status::OVERFLOW);
+        self.branch(condition)
+    }
+
+    // Branch if carry clear
+    fn bcc(&mut self) {
+        let condition = !self.registers.status.contains(Status::CARRY);
+        self.branch(condition)
+    }
+
+    // Branch if carry set
+    fn bcs(&mut self) {
+        let condition = self.registers.status.contains(Status::CARRY);
+        self.branch(condition)
+    }
+
+    // Branch if not equal
+    fn bne(&mut self) {
+        let condition = !self.registers.status.contains(Status::ZERO);
+        self.branch(condition)
+    }
+
+    // Branch if equal
+    fn beq(&mut self) {
+        let condition = self.registers.status.contains(Status::ZERO);
+        self.branch(condition)
+    }
+
+    // Branch always
+    fn bra(&mut self) {
+        self.branch(true)
+    }
+
+    // Branch always long, with a 16-bit offset
+    fn brl(&mut self) {
+        let offset = self.fetch16();
+        self.registers.pc = self.registers.pc.wrapping_add(offset);
+    }
+
+    // Status flags
+
+    // Clear carry flag
+    fn clc(&mut self) {
+        self.registers.status.remove(Status::CARRY);
+    }
+
+    // Clear decimal mode flag
+    fn cld(&mut self) {
+        self.registers.status.remove(Status::DECIMAL);
+    }
+
+    // Clear interrupt disable flag
+    fn cli(&mut self) {
+        self.registers.status.remove(Status::INTERRUPT);
+    }
+
+    // Clear overflow flag
+    fn clv(&mut self) {
+        self.registers.status.remove(Status::OVERFLOW);
+    }
+
+    // Set carry flag
+    fn sec(&mut self) {
+        self.registers.status.insert(Status::CARRY);
+    }
+
+    // Set decimal mode flag
+    fn sed(&mut self) {
+        self.registers.status.insert(Status::DECIMAL);
+    }
+
+    // Set interrupt disable flag
+    fn sei(&mut self) {
+        self.registers.status.insert(Status::INTERRUPT);
+    }
+
+    // Reset the status bits set in the operand
+    fn rep(&mut self) {
+        let bits = Status::from_bits_truncate(self.fetch8());
+        self.registers.status.remove(bits);
+        self.update_widths();
+    }
+
+    // Set the status bits set in the operand
+    fn sep(&mut self) {
+        let bits = Status::from_bits_truncate(self.fetch8());
+        self.registers.status.insert(bits);
+        self.update_widths();
+    }
+
+    // Exchange carry and emulation flags, the only way to switch between native and emulation mode
+    fn xce(&mut self) {
+        let carry = self.registers.status.contains(Status::CARRY);
+        self.registers
+            .status
+            .set(Status::CARRY, self.registers.emulation);
+        self.registers.emulation = carry;
+
+        // Entering emulation mode also moves the stack back to page 1
+        if self.registers.emulation {
+            self.registers.sp = 0x0100 | (self.registers.sp & 0x00FF);
+        }
+        self.update_widths();
+    }
+
+    // Block moves
+
+    // Move one byte from the source bank at X to the destination bank at Y
+    // The instruction repeats itself until the accumulator, the byte count minus one, wraps
+    // around, one byte per step so interrupts can be serviced in between
+    fn block_move(&mut self, step: u16) {
+        let destination = self.fetch8();
+        let source = self.fetch8();
+        self.registers.dbr = destination;
+
+        let byte = self.read((source as u32) << 16 | self.registers.x as u32);
+        self.write((destination as u32) << 16 | self.registers.y as u32, byte);
+
+        self.set_x(self.registers.x.wrapping_add(step));
+        self.set_y(self.registers.y.wrapping_add(step));
+        self.registers.acc = self.registers.acc.wrapping_sub(1);
+
+        if self.registers.acc != 0xFFFF {
+            self.registers.pc = self.registers.pc.wrapping_sub(3);
+        }
+    }
+
+    // Block move next, for moves to a lower address
+    fn mvn(&mut self) {
+        self.block_move(0x0001)
+    }
+
+    // Block move previous, for moves to a higher address
+    fn mvp(&mut self) {
+        self.block_move(0xFFFF)
+    }
+
+    // System
+
+    // Force interrupt, the byte after the opcode is a signature that is skipped
+    fn brk(&mut self) {
+        self.fetch8();
+        self.interrupt(NATIVE_BRK_VECTOR, IRQ_VECTOR, true);
+    }
+
+    // Co-processor interrupt, the byte after the opcode is a signature that is skipped
+    fn cop(&mut self) {
+        self.fetch8();
+        self.interrupt(NATIVE_COP_VECTOR, COP_VECTOR, false);
+    }
+
+    // No operation
+    fn nop(&mut self) {}
+
+    // Return from interrupt, the program bank is pulled as well in native mode
+    fn rti(&mut self) {
+        let status = self.pull();
+        self.registers.status = Status::from_bits_truncate(status);
+        self.update_widths();
+
+        self.registers.pc = self.pull16();
+        if !self.registers.emulation {
+            self.registers.pbr = self.pull();
+        }
+    }
+
+    // Reserved for future expansion, skips its operand
+    fn wdm(&mut self) {
+        self.fetch8();
+    }
+
+    // Wait for interrupt
+    fn wai(&mut self) {
+        self.control.waiting = true;
+    }
+
+    // Stop the processor until reset
+    fn stp(&mut self) {
+        self.control.halted = true;
+    }
+}
+
+// Mask of an 8 or 16-bit value
+fn mask(wide: bool) -> u16 {
+    if wide {
+        0xFFFF
+    } else {
+        0x00FF
+    }
+}
+
+// Sign bit of an 8 or 16-bit value
+fn sign(wide: bool) -> u16 {
+    if wide {
+        0x8000
+    } else {
+        0x0080
+    }
+}
+
+#[cfg(test)]
+// Functions for unit testing
+impl Processor65C816 {
+    // Get a mutable reference to the registers
+    pub fn set_register(&mut self) -> &mut Registers65C816 {
+        &mut self.registers
+    }
+
+    // Get an immutable reference to the registers
+    pub fn get_registers(&self) -> &Registers65C816 {
+        &self.registers
+    }
+
+    // Write a byte to the given address
+    pub fn set_mem(&mut self, address: u32, byte: u8) {
+        self.device_mapper.write(address, byte).unwrap();
+    }
+
+    // Read a byte from the given address
+    pub fn get_mem(&mut self, address: u32) -> u8 {
+        self.device_mapper.read(address).unwrap()
+    }
+}
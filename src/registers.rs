@@ -1,5 +1,6 @@
 // Imports
 use bitflags::bitflags;
+use std::fmt;
 
 // Structs
 
@@ -78,9 +79,108 @@ impl Status {
         // Return the status
         status
     }
+
+    // Get the flags by name
+    pub fn negative(&self) -> bool {
+        self.contains(Status::NEGATIVE)
+    }
+
+    pub fn overflow(&self) -> bool {
+        self.contains(Status::OVERFLOW)
+    }
+
+    pub fn break_command(&self) -> bool {
+        self.contains(Status::BREAK)
+    }
+
+    pub fn decimal_mode(&self) -> bool {
+        self.contains(Status::DECIMAL)
+    }
+
+    pub fn interrupt(&self) -> bool {
+        self.contains(Status::INTERRUPT)
+    }
+
+    pub fn zero(&self) -> bool {
+        self.contains(Status::ZERO)
+    }
+
+    pub fn carry(&self) -> bool {
+        self.contains(Status::CARRY)
+    }
+
+    // Set or clear the flags by name
+    pub fn set_negative(&mut self, value: bool) {
+        self.set(Status::NEGATIVE, value);
+    }
+
+    pub fn set_overflow(&mut self, value: bool) {
+        self.set(Status::OVERFLOW, value);
+    }
+
+    pub fn set_break_command(&mut self, value: bool) {
+        self.set(Status::BREAK, value);
+    }
+
+    pub fn set_decimal_mode(&mut self, value: bool) {
+        self.set(Status::DECIMAL, value);
+    }
+
+    pub fn set_interrupt(&mut self, value: bool) {
+        self.set(Status::INTERRUPT, value);
+    }
+
+    pub fn set_zero(&mut self, value: bool) {
+        self.set(Status::ZERO, value);
+    }
+
+    pub fn set_carry(&mut self, value: bool) {
+        self.set(Status::CARRY, value);
+    }
+}
+
+impl Status {
+    // Write the flags upper case when set and lower case when clear, with the given
+    // names for the unused and break bits
+    pub(crate) fn write_flags(
+        &self,
+        f: &mut fmt::Formatter,
+        unused: char,
+        brk: char,
+    ) -> fmt::Result {
+        let flags = [
+            (Status::NEGATIVE, 'n'),
+            (Status::OVERFLOW, 'v'),
+            (Status::UNUSED, unused),
+            (Status::BREAK, brk),
+            (Status::DECIMAL, 'd'),
+            (Status::INTERRUPT, 'i'),
+            (Status::ZERO, 'z'),
+            (Status::CARRY, 'c'),
+        ];
+
+        for (flag, name) in flags {
+            if self.contains(flag) {
+                write!(f, "{}", name.to_ascii_uppercase())?;
+            } else {
+                write!(f, "{}", name)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+// Show the flags as NV-BDIZC, upper case when set and lower case when clear
+// The unused bit is always shown as -
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.write_flags(f, '-', 'b')
+    }
 }
 
 // Struct for the registers
+#[derive(Clone, Copy)]
 pub struct Registers {
     pub pc: u16,        // Program counter
     pub sp: u8,         // Stack pointer
@@ -103,3 +203,36 @@ impl Registers {
         }
     }
 }
+
+// Struct for the registers of the 65C816
+#[derive(Clone, Copy)]
+pub struct Registers65C816 {
+    pub pc: u16,         // Program counter
+    pub pbr: u8,         // Program bank register
+    pub dbr: u8,         // Data bank register
+    pub dp: u16,         // Direct page register
+    pub sp: u16,         // Stack pointer
+    pub acc: u16,        // Accumulator, B in the high byte when it is 8 bits wide
+    pub x: u16,          // X register
+    pub y: u16,          // Y register
+    pub status: Status,  // Status register
+    pub emulation: bool, // Emulation flag
+}
+
+// Implement the Registers65C816 struct
+impl Registers65C816 {
+    pub fn new() -> Self {
+        Self {
+            pc: 0x8000,
+            pbr: 0x00,
+            dbr: 0x00,
+            dp: 0x0000,
+            sp: 0x0100,
+            acc: 0x0000,
+            x: 0x0000,
+            y: 0x0000,
+            status: Status::default(),
+            emulation: true,
+        }
+    }
+}
@@ -1,7 +1,6 @@
 // NOTE: I only test 1 address mode for each opcode
 // Because the implementation is the same for all address modes. The only difference is getting the value from memory.
 
-pub use processor::*;
 mod processor {
     use crate::{processor::Processor, registers::Status};
     // Get status register
@@ -21,7 +20,6 @@ mod processor {
     }
 
     // Load/store
-    pub use load_store::*;
     mod load_store {
         use super::*;
         use crate::{
@@ -40,7 +38,7 @@ mod processor {
             ]);
 
             // Execute instruction
-            processor.step();
+            processor.step().unwrap();
 
             // Check processor state
             let acc = processor.get_registers().acc;
@@ -50,7 +48,7 @@ mod processor {
             assert!(!negative);
 
             // Execute instruction
-            processor.step();
+            processor.step().unwrap();
 
             // Check processor state
             let acc = processor.get_registers().acc;
@@ -60,7 +58,7 @@ mod processor {
             assert!(!negative);
 
             // Execute instruction
-            processor.step();
+            processor.step().unwrap();
 
             // Check processor state
             let acc = processor.get_registers().acc;
@@ -81,7 +79,7 @@ mod processor {
             ]);
 
             // Execute instruction
-            processor.step();
+            processor.step().unwrap();
 
             // Check processor state
             let x = processor.get_registers().x;
@@ -91,7 +89,7 @@ mod processor {
             assert!(!negative);
 
             // Execute instruction
-            processor.step();
+            processor.step().unwrap();
 
             // Check processor state
             let x = processor.get_registers().x;
@@ -101,7 +99,7 @@ mod processor {
             assert!(!negative);
 
             // Execute instruction
-            processor.step();
+            processor.step().unwrap();
 
             // Check processor state
             let x = processor.get_registers().x;
@@ -122,7 +120,7 @@ mod processor {
             ]);
 
             // Execute instruction
-            processor.step();
+            processor.step().unwrap();
 
             // Check processor state
             let y = processor.get_registers().y;
@@ -132,7 +130,7 @@ mod processor {
             assert!(!negative);
 
             // Execute instruction
-            processor.step();
+            processor.step().unwrap();
 
             // Check processor state
             let y = processor.get_registers().y;
@@ -142,7 +140,7 @@ mod processor {
             assert!(!negative);
 
             // Execute instruction
-            processor.step();
+            processor.step().unwrap();
 
             // Check processor state
             let y = processor.get_registers().y;
@@ -164,7 +162,7 @@ mod processor {
             processor.set_register().acc = 0x42;
 
             // Execute instruction
-            processor.step();
+            processor.step().unwrap();
 
             // Check processor state
             let acc = processor.get_registers().acc;
@@ -183,7 +181,7 @@ mod processor {
             processor.set_register().x = 0x42;
 
             // Execute instruction
-            processor.step();
+            processor.step().unwrap();
 
             // Check processor state
             let x = processor.get_registers().x;
@@ -202,7 +200,7 @@ mod processor {
             processor.set_register().y = 0x42;
 
             // Execute instruction
-            processor.step();
+            processor.step().unwrap();
 
             // Check processor state
             let y = processor.get_registers().y;
@@ -211,7 +209,6 @@ mod processor {
     }
 
     // Transfer
-    pub use transfer::*;
     mod transfer {
         use super::*;
         use crate::{
@@ -233,7 +230,7 @@ mod processor {
             processor.set_register().acc = 0x42;
 
             // Execute instruction
-            processor.step();
+            processor.step().unwrap();
 
             // Check processor state
             let acc = processor.get_registers().acc;
@@ -248,7 +245,7 @@ mod processor {
             processor.set_register().acc = 0x00;
 
             // Execute instruction
-            processor.step();
+            processor.step().unwrap();
 
             // Check processor state
             let acc = processor.get_registers().acc;
@@ -263,7 +260,7 @@ mod processor {
             processor.set_register().acc = 0x80;
 
             // Execute instruction
-            processor.step();
+            processor.step().unwrap();
 
             // Check processor state
             let acc = processor.get_registers().acc;
@@ -289,7 +286,7 @@ mod processor {
             processor.set_register().acc = 0x42;
 
             // Execute instruction
-            processor.step();
+            processor.step().unwrap();
 
             // Check processor state
             let acc = processor.get_registers().acc;
@@ -304,7 +301,7 @@ mod processor {
             processor.set_register().acc = 0x00;
 
             // Execute instruction
-            processor.step();
+            processor.step().unwrap();
 
             // Check processor state
             let acc = processor.get_registers().acc;
@@ -319,7 +316,7 @@ mod processor {
             processor.set_register().acc = 0x80;
 
             // Execute instruction
-            processor.step();
+            processor.step().unwrap();
 
             // Check processor state
             let acc = processor.get_registers().acc;
@@ -345,7 +342,7 @@ mod processor {
             processor.set_register().x = 0x42;
 
             // Execute instruction
-            processor.step();
+            processor.step().unwrap();
 
             // Check processor state
             let acc = processor.get_registers().acc;
@@ -360,7 +357,7 @@ mod processor {
             processor.set_register().x = 0x00;
 
             // Execute instruction
-            processor.step();
+            processor.step().unwrap();
 
             // Check processor state
             let acc = processor.get_registers().acc;
@@ -375,7 +372,7 @@ mod processor {
             processor.set_register().x = 0x80;
 
             // Execute instruction
-            processor.step();
+            processor.step().unwrap();
 
             // Check processor state
             let acc = processor.get_registers().acc;
@@ -401,7 +398,7 @@ mod processor {
             processor.set_register().y = 0x42;
 
             // Execute instruction
-            processor.step();
+            processor.step().unwrap();
 
             // Check processor state
             let acc = processor.get_registers().acc;
@@ -416,7 +413,7 @@ mod processor {
             processor.set_register().y = 0x00;
 
             // Execute instruction
-            processor.step();
+            processor.step().unwrap();
 
             // Check processor state
             let acc = processor.get_registers().acc;
@@ -431,7 +428,7 @@ mod processor {
             processor.set_register().y = 0x80;
 
             // Execute instruction
-            processor.step();
+            processor.step().unwrap();
 
             // Check processor state
             let acc = processor.get_registers().acc;
@@ -457,7 +454,7 @@ mod processor {
             processor.set_register().sp = 0x42;
 
             // Execute instruction
-            processor.step();
+            processor.step().unwrap();
 
             // Check processor state
             let sp = processor.get_registers().sp;
@@ -472,7 +469,7 @@ mod processor {
             processor.set_register().sp = 0x00;
 
             // Execute instruction
-            processor.step();
+            processor.step().unwrap();
 
             // Check processor state
             let sp = processor.get_registers().sp;
@@ -487,7 +484,7 @@ mod processor {
             processor.set_register().sp = 0x80;
 
             // Execute instruction
-            processor.step();
+            processor.step().unwrap();
 
             // Check processor state
             let sp = processor.get_registers().sp;
@@ -511,7 +508,7 @@ mod processor {
             processor.set_register().x = 0x42;
 
             // Execute instruction
-            processor.step();
+            processor.step().unwrap();
 
             // Check processor state
             let sp = processor.get_registers().sp;
@@ -521,7 +518,6 @@ mod processor {
         }
     }
 
-    pub use stack::*;
     mod stack {
         use super::*;
         use crate::{
@@ -534,7 +530,7 @@ mod processor {
         fn test_pha() {
             // Create a new processor
             let mut processor = Processor::new(vec![
-                PHA, // PHA ; Not initialized
+                PHA, // PHA ; After reset
                 PHA, // PHA ; Initialized
             ]);
 
@@ -542,20 +538,20 @@ mod processor {
             processor.set_register().acc = 0x42;
 
             // Execute instruction
-            processor.step();
+            processor.step().unwrap();
 
             // Check processor state
             let acc = processor.get_registers().acc;
             let sp = processor.get_registers().sp;
             assert_eq!(acc, 0x42);
-            assert_eq!(sp, 0xFF);
+            assert_eq!(sp, 0xFC);
 
             // Set state of processor
             processor.set_register().acc = 0x43;
             processor.set_register().sp = 0xFE;
 
             // Execute instruction
-            processor.step();
+            processor.step().unwrap();
 
             // Check processor state
             let acc = processor.get_registers().acc;
@@ -569,7 +565,7 @@ mod processor {
         fn test_php() {
             // Create a new processor
             let mut processor = Processor::new(vec![
-                PHP, // PHP ; Not initialized
+                PHP, // PHP ; After reset
                 PHP, // PHP ; Initialized
             ]);
 
@@ -577,26 +573,28 @@ mod processor {
             processor.set_register().status = Status::from_bits_truncate(0x42);
 
             // Execute instruction
-            processor.step();
+            processor.step().unwrap();
 
-            // Check processor state
+            // Check processor state, the break flag and bit 5 are set on the stack only
             let status = processor.get_registers().status.bits();
             let sp = processor.get_registers().sp;
             assert_eq!(status, 0x42);
-            assert_eq!(sp, 0xFF);
+            assert_eq!(sp, 0xFC);
+            assert_eq!(processor.get_mem(0x01FD), 0x72);
 
             // Set state of processor
             processor.set_register().status = Status::from_bits_truncate(0x43);
             processor.set_register().sp = 0xFE;
 
             // Execute instruction
-            processor.step();
+            processor.step().unwrap();
 
             // Check processor state
             let status = processor.get_registers().status.bits();
             let sp = processor.get_registers().sp;
             assert_eq!(status, 0x43);
             assert_eq!(sp, 0xFD);
+            assert_eq!(processor.get_mem(0x01FE), 0x73);
         }
 
         #[test]
@@ -604,28 +602,28 @@ mod processor {
         fn test_pla() {
             // Create a new processor
             let mut processor = Processor::new(vec![
-                PLA, // PLA ; Not initialized
+                PLA, // PLA ; After reset
                 PLA, // PLA ; Initialized
             ]);
 
             // Set state of processor
-            processor.set_mem(0x0100, 0x42);
+            processor.set_mem(0x01FE, 0x42);
 
             // Execute instruction
-            processor.step();
+            processor.step().unwrap();
 
             // Check processor state
             let acc = processor.get_registers().acc;
             let sp = processor.get_registers().sp;
             assert_eq!(acc, 0x42);
-            assert_eq!(sp, 0x01);
+            assert_eq!(sp, 0xFE);
 
             // Set state of processor
             processor.set_register().sp = 0xFE;
-            processor.set_mem(0x01FE, 0x43);
+            processor.set_mem(0x01FF, 0x43);
 
             // Execute instruction
-            processor.step();
+            processor.step().unwrap();
 
             // Check processor state
             let acc = processor.get_registers().acc;
@@ -639,39 +637,64 @@ mod processor {
         fn test_plp() {
             // Create a new processor
             let mut processor = Processor::new(vec![
-                PLP, // PLP ; Not initialized
+                PLP, // PLP ; After reset
                 PLP, // PLP ; Initialized
             ]);
 
             // Set state of processor
-            processor.set_mem(0x0100, 0x42);
+            processor.set_mem(0x01FE, 0x42);
 
             // Execute instruction
-            processor.step();
+            processor.step().unwrap();
 
-            // Check processor state
+            // Check processor state, the break and unused bits are kept
             let status = processor.get_registers().status.bits();
             let sp = processor.get_registers().sp;
-            assert_eq!(status, 0x42);
-            assert_eq!(sp, 0x01);
+            assert_eq!(status, 0x62);
+            assert_eq!(sp, 0xFE);
 
             // Set state of processor
             processor.set_register().sp = 0xFE;
-            processor.set_mem(0x01FE, 0x43);
+            processor.set_mem(0x01FF, 0x43);
 
             // Execute instruction
-            processor.step();
+            processor.step().unwrap();
 
             // Check processor state
             let status = processor.get_registers().status.bits();
             let sp = processor.get_registers().sp;
-            assert_eq!(status, 0x43);
+            assert_eq!(status, 0x63);
             assert_eq!(sp, 0xFF);
         }
+
+        #[test]
+        // PHP, PLP - The break flag is set on the stack only
+        fn test_php_plp() {
+            // Create a new processor
+            let mut processor = Processor::new(vec![
+                PHP, // PHP
+                PLP, // PLP
+            ]);
+
+            // Set state of processor
+            processor.set_register().status = Status::NEGATIVE | Status::UNUSED | Status::CARRY;
+
+            // Execute instructions
+            processor.step().unwrap();
+            processor.step().unwrap();
+
+            // Check processor state
+            let (zero, negative, carry, _, _, _, break_command, unused) = get_status(&processor);
+            assert_eq!(processor.get_mem(0x01FD), 0xB1);
+            assert!(!zero);
+            assert!(negative);
+            assert!(carry);
+            assert!(!break_command);
+            assert!(unused);
+        }
     }
 
     // Logical
-    pub use logical::*;
     mod logical {
         use super::*;
         use crate::{
@@ -693,7 +716,7 @@ mod processor {
             processor.set_register().acc = 0x42;
 
             // Execute instruction
-            processor.step();
+            processor.step().unwrap();
 
             // Check processor state
             let acc = processor.get_registers().acc;
@@ -706,7 +729,7 @@ mod processor {
             processor.set_register().acc = 0x00;
 
             // Execute instruction
-            processor.step();
+            processor.step().unwrap();
 
             // Check processor state
             let acc = processor.get_registers().acc;
@@ -719,7 +742,7 @@ mod processor {
             processor.set_register().acc = 0x80;
 
             // Execute instruction
-            processor.step();
+            processor.step().unwrap();
 
             // Check processor state
             let acc = processor.get_registers().acc;
@@ -743,7 +766,7 @@ mod processor {
             processor.set_register().acc = 0x43;
 
             // Execute instruction
-            processor.step();
+            processor.step().unwrap();
 
             // Check processor state
             let acc = processor.get_registers().acc;
@@ -756,7 +779,7 @@ mod processor {
             processor.set_register().acc = 0x00;
 
             // Execute instruction
-            processor.step();
+            processor.step().unwrap();
 
             // Check processor state
             let acc = processor.get_registers().acc;
@@ -769,7 +792,7 @@ mod processor {
             processor.set_register().acc = 0x00;
 
             // Execute instruction
-            processor.step();
+            processor.step().unwrap();
 
             // Check processor state
             let acc = processor.get_registers().acc;
@@ -793,7 +816,7 @@ mod processor {
             processor.set_register().acc = 0x42;
 
             // Execute instruction
-            processor.step();
+            processor.step().unwrap();
 
             // Check processor state
             let acc = processor.get_registers().acc;
@@ -806,7 +829,7 @@ mod processor {
             processor.set_register().acc = 0x00;
 
             // Execute instruction
-            processor.step();
+            processor.step().unwrap();
 
             // Check processor state
             let acc = processor.get_registers().acc;
@@ -819,7 +842,7 @@ mod processor {
             processor.set_register().acc = 0x00;
 
             // Execute instruction
-            processor.step();
+            processor.step().unwrap();
 
             // Check processor state
             let acc = processor.get_registers().acc;
@@ -851,19 +874,19 @@ mod processor {
             processor.set_register().acc = 0x00;
 
             // Execute instruction
-            processor.step();
+            processor.step().unwrap();
 
-            // Check processor state
+            // Check processor state, overflow is bit 6 of the operand
             let (zero, negative, _carry, overflow, ..) = get_status(&processor);
             assert!(zero);
             assert!(!negative);
-            assert!(!overflow);
+            assert!(overflow);
 
             // Set state of processor
             processor.set_register().acc = 0x00;
 
             // Execute instruction
-            processor.step();
+            processor.step().unwrap();
 
             // Check processor state
             let (zero, negative, _carry, overflow, ..) = get_status(&processor);
@@ -875,7 +898,7 @@ mod processor {
             processor.set_register().acc = 0x80;
 
             // Execute instruction
-            processor.step();
+            processor.step().unwrap();
 
             // Check processor state
             let (zero, negative, _carry, overflow, ..) = get_status(&processor);
@@ -887,7 +910,7 @@ mod processor {
             processor.set_register().acc = 0x40;
 
             // Execute instruction
-            processor.step();
+            processor.step().unwrap();
 
             // Check processor state
             let (zero, negative, _carry, overflow, ..) = get_status(&processor);
@@ -898,10 +921,12 @@ mod processor {
     }
 
     // Arithmetic
-    pub use arithmetic::*;
     mod arithmetic {
         use super::*;
-        use crate::{opcodes::ADC_IM, processor::Processor};
+        use crate::{
+            opcodes::{ADC_IM, CMP_IM, CPX_IM, CPY_IM, SBC_IM, SED},
+            processor::{Processor, Variant},
+        };
 
         #[test]
         // ADC - Add with Carry
@@ -918,7 +943,7 @@ mod processor {
             processor.set_register().acc = 0x27;
 
             // Execute instruction
-            processor.step();
+            processor.step().unwrap();
 
             // Check processor state
             let acc = processor.get_registers().acc;
@@ -934,7 +959,7 @@ mod processor {
             processor.set_register().acc = 0x00;
 
             // Execute instruction
-            processor.step();
+            processor.step().unwrap();
 
             // Check processor state
             let acc = processor.get_registers().acc;
@@ -950,7 +975,7 @@ mod processor {
             processor.set_register().acc = 0x05;
 
             // Execute instruction
-            processor.step();
+            processor.step().unwrap();
 
             // Check processor state
             let acc = processor.get_registers().acc;
@@ -966,7 +991,7 @@ mod processor {
             processor.set_register().acc = 0x7F;
 
             // Execute instruction
-            processor.step();
+            processor.step().unwrap();
 
             // Check processor state
             let acc = processor.get_registers().acc;
@@ -982,7 +1007,7 @@ mod processor {
             processor.set_register().acc = 0x7F;
 
             // Execute instruction
-            processor.step();
+            processor.step().unwrap();
 
             // Check processor state
             let acc = processor.get_registers().acc;
@@ -994,5 +1019,5144 @@ mod processor {
             assert!(carry);
             assert!(!overflow);
         }
+
+        #[test]
+        // SBC - Subtract with Carry
+        fn sbc_test() {
+            let mut processor = Processor::new(vec![
+                SBC_IM, 0x20, // SBC #$20 ; No flags
+                SBC_IM, 0x42, // SBC #$42 ; Zero
+                SBC_IM, 0x01, // SBC #$01 ; Negative, borrow
+                SBC_IM, 0x01, // SBC #$01 ; Overflow
+                SBC_IM, 0x00, // SBC #$00 ; Borrow in
+            ]);
+
+            // Set state of processor
+            processor.set_register().acc = 0x50;
+            processor.set_register().status.insert(Status::CARRY);
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let acc = processor.get_registers().acc;
+            let (zero, negative, carry, overflow, ..) = get_status(&processor);
+            // 0x50 - 0x20 = 0x30
+            assert_eq!(acc, 0x30);
+            assert!(!zero);
+            assert!(!negative);
+            assert!(carry);
+            assert!(!overflow);
+
+            // Set state of processor
+            processor.set_register().acc = 0x42;
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let acc = processor.get_registers().acc;
+            let (zero, negative, carry, overflow, ..) = get_status(&processor);
+            // 0x42 - 0x42 = 0x00
+            assert_eq!(acc, 0x00);
+            assert!(zero);
+            assert!(!negative);
+            assert!(carry);
+            assert!(!overflow);
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let acc = processor.get_registers().acc;
+            let (zero, negative, carry, overflow, ..) = get_status(&processor);
+            // 0x00 - 0x01 = 0xFF
+            assert_eq!(acc, 0xFF);
+            assert!(!zero);
+            assert!(negative);
+            assert!(!carry);
+            assert!(!overflow);
+
+            // Set state of processor
+            processor.set_register().acc = 0x80;
+            processor.set_register().status.insert(Status::CARRY);
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let acc = processor.get_registers().acc;
+            let (zero, negative, carry, overflow, ..) = get_status(&processor);
+            // 0x80 - 0x01 = 0x7F
+            assert_eq!(acc, 0x7F);
+            assert!(!zero);
+            assert!(!negative);
+            assert!(carry);
+            assert!(overflow);
+
+            // Set state of processor
+            processor.set_register().acc = 0x42;
+            processor.set_register().status.remove(Status::CARRY);
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let acc = processor.get_registers().acc;
+            let (zero, negative, carry, overflow, ..) = get_status(&processor);
+            // 0x42 - 0x00 - 1 = 0x41
+            assert_eq!(acc, 0x41);
+            assert!(!zero);
+            assert!(!negative);
+            assert!(carry);
+            assert!(!overflow);
+        }
+
+        #[test]
+        // ADC - Add with Carry in decimal mode
+        fn adc_decimal_test() {
+            let mut processor = Processor::new(vec![
+                ADC_IM, 0x27, // ADC #$27 ; No flags
+                ADC_IM, 0x01, // ADC #$01 ; Zero, carry
+                ADC_IM, 0x29, // ADC #$29 ; Carry in
+            ]);
+
+            // Set state of processor
+            processor.set_register().acc = 0x15;
+            processor.set_register().status.insert(Status::DECIMAL);
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let acc = processor.get_registers().acc;
+            let (zero, _negative, carry, ..) = get_status(&processor);
+            // 15 + 27 = 42
+            assert_eq!(acc, 0x42);
+            assert!(!zero);
+            assert!(!carry);
+
+            // Set state of processor
+            processor.set_register().acc = 0x99;
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let acc = processor.get_registers().acc;
+            let (zero, _negative, carry, ..) = get_status(&processor);
+            // 99 + 01 = 100, the Z flag follows the binary result ($9A) on the NMOS 6502
+            assert_eq!(acc, 0x00);
+            assert!(!zero);
+            assert!(carry);
+
+            // Set state of processor
+            processor.set_register().acc = 0x12;
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let acc = processor.get_registers().acc;
+            let (zero, _negative, carry, ..) = get_status(&processor);
+            // 12 + 29 + 1 = 42
+            assert_eq!(acc, 0x42);
+            assert!(!zero);
+            assert!(!carry);
+        }
+
+        #[test]
+        // SBC - Subtract with Carry in decimal mode
+        fn sbc_decimal_test() {
+            let mut processor = Processor::new(vec![
+                SBC_IM, 0x27, // SBC #$27 ; No borrow
+                SBC_IM, 0x01, // SBC #$01 ; Borrow
+                SBC_IM, 0x10, // SBC #$10 ; Borrow in
+            ]);
+
+            // Set state of processor
+            processor.set_register().acc = 0x42;
+            processor.set_register().status.insert(Status::DECIMAL);
+            processor.set_register().status.insert(Status::CARRY);
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let acc = processor.get_registers().acc;
+            let (_zero, _negative, carry, ..) = get_status(&processor);
+            // 42 - 27 = 15
+            assert_eq!(acc, 0x15);
+            assert!(carry);
+
+            // Set state of processor
+            processor.set_register().acc = 0x00;
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let acc = processor.get_registers().acc;
+            let (_zero, _negative, carry, ..) = get_status(&processor);
+            // 00 - 01 = 99
+            assert_eq!(acc, 0x99);
+            assert!(!carry);
+
+            // Set state of processor
+            processor.set_register().acc = 0x42;
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let acc = processor.get_registers().acc;
+            let (_zero, _negative, carry, ..) = get_status(&processor);
+            // 42 - 10 - 1 = 31
+            assert_eq!(acc, 0x31);
+            assert!(carry);
+        }
+
+        #[test]
+        // ADC/SBC - Binary arithmetic with the decimal flag set on the Ricoh 2A03
+        fn ricoh_decimal_test() {
+            let mut processor = Processor::new(vec![
+                SED, // SED
+                ADC_IM, 0x01, // ADC #$01
+                SBC_IM, 0x01, // SBC #$01
+            ])
+            .with_variant(Variant::Ricoh2A03);
+
+            // Set state of processor
+            processor.set_register().acc = 0x09;
+
+            // Execute instructions
+            processor.step().unwrap();
+            processor.step().unwrap();
+
+            // Check processor state
+            let acc = processor.get_registers().acc;
+            let (_zero, _negative, carry, _overflow, decimal, ..) = get_status(&processor);
+            // 09 + 01 = 0A, the decimal flag is still set
+            assert_eq!(acc, 0x0A);
+            assert!(!carry);
+            assert!(decimal);
+
+            // Set state of processor
+            processor.set_register().acc = 0x10;
+            processor.set_register().status.insert(Status::CARRY);
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let acc = processor.get_registers().acc;
+            let (_zero, _negative, carry, ..) = get_status(&processor);
+            // 10 - 01 = 0F
+            assert_eq!(acc, 0x0F);
+            assert!(carry);
+        }
+
+        // Binary add with carry, returns the result and the N, V and C flags
+        fn binary_adc(a: u8, b: u8, carry: bool) -> (u8, bool, bool, bool) {
+            let sum = a as u16 + b as u16 + carry as u16;
+            let result = sum as u8;
+            let overflow = (a ^ result) & (b ^ result) & 0x80 != 0;
+            (result, result & 0x80 != 0, overflow, sum > 0xFF)
+        }
+
+        // Binary subtract with carry, returns the result and the N, V and C flags
+        fn binary_sbc(a: u8, b: u8, carry: bool) -> (u8, bool, bool, bool) {
+            binary_adc(a, !b, carry)
+        }
+
+        // Accumulator and N, V, Z and C flags predicted for a decimal operation
+        type Prediction = (u8, bool, bool, bool, bool);
+
+        // Predicted decimal ADC on the NMOS 6502, returns the accumulator and the N, V, Z and C flags
+        // Transcribed from the ADD and A6502 routines of Bruce Clark's decimal mode test program
+        fn predict_adc(n1: u8, n2: u8, carry: bool) -> Prediction {
+            let n2h = [n2 & 0xF0, (n2 & 0xF0) | 0x0F];
+
+            // The Z flag is the one of the binary addition
+            let (binary, ..) = binary_adc(n1, n2, carry);
+
+            let (mut a, ..) = binary_adc(n1 & 0x0F, n2 & 0x0F, carry);
+            let mut x = 0;
+            let mut c = false;
+            if a >= 0x0A {
+                x = 1;
+                a = binary_adc(a, 0x05, true).0 & 0x0F;
+                c = true;
+            }
+            a |= n1 & 0xF0;
+
+            // The N and V flags are the ones of this addition
+            let (mut a, negative, overflow, c) = binary_adc(a, n2h[x], c);
+            let mut cf = false;
+            if c || a >= 0xA0 {
+                a = binary_adc(a, 0x5F, true).0;
+                cf = true;
+            }
+
+            (a, negative, overflow, binary == 0x00, cf)
+        }
+
+        // Predicted decimal SBC on the NMOS 6502, returns the accumulator and the N, V, Z and C flags
+        // Transcribed from the SUB1 and S6502 routines of Bruce Clark's decimal mode test program
+        fn predict_sbc(n1: u8, n2: u8, carry: bool) -> Prediction {
+            let n2h = [n2 & 0xF0, (n2 & 0xF0) | 0x0F];
+
+            // All flags are the ones of the binary subtraction
+            let (binary, negative, overflow, cf) = binary_sbc(n1, n2, carry);
+
+            let (mut a, _, _, mut c) = binary_sbc(n1 & 0x0F, n2 & 0x0F, carry);
+            let mut x = 0;
+            if !c {
+                x = 1;
+                a = binary_sbc(a, 0x05, false).0 & 0x0F;
+                c = false;
+            }
+            a |= n1 & 0xF0;
+
+            let (mut a, _, _, c) = binary_sbc(a, n2h[x], c);
+            if !c {
+                a = binary_sbc(a, 0x5F, false).0;
+            }
+
+            (a, negative, overflow, binary == 0x00, cf)
+        }
+
+        // Predicted decimal ADC on the 65C02, returns the accumulator and the N, V, Z and C flags
+        // Transcribed from the ADD and A65C02 routines of Bruce Clark's decimal mode test program
+        fn predict_adc_cmos(n1: u8, n2: u8, carry: bool) -> Prediction {
+            // Same as the NMOS 6502, except that N and Z follow the accumulator
+            let (a, _, overflow, _, cf) = predict_adc(n1, n2, carry);
+            (a, a & 0x80 != 0, overflow, a == 0x00, cf)
+        }
+
+        // Predicted decimal SBC on the 65C02, returns the accumulator and the N, V, Z and C flags
+        // Transcribed from the SUB2 and S65C02 routines of Bruce Clark's decimal mode test program
+        fn predict_sbc_cmos(n1: u8, n2: u8, carry: bool) -> Prediction {
+            let n2h = [n2 & 0xF0, (n2 & 0xF0) | 0x0F];
+
+            // The V and C flags are the ones of the binary subtraction
+            let (_, _, overflow, cf) = binary_sbc(n1, n2, carry);
+
+            let (mut a, _, _, mut c) = binary_sbc(n1 & 0x0F, n2 & 0x0F, carry);
+            let mut x = 0;
+            if !c {
+                x = 1;
+                a &= 0x0F;
+                c = false;
+            }
+            a |= n1 & 0xF0;
+
+            let (mut a, _, _, c) = binary_sbc(a, n2h[x], c);
+            if !c {
+                a = binary_sbc(a, 0x5F, false).0;
+            }
+            if x != 0 {
+                a = binary_sbc(a, 0x06, true).0;
+            }
+
+            (a, a & 0x80 != 0, overflow, a == 0x00, cf)
+        }
+
+        // Run ADC/SBC for every operand and carry combination in decimal mode
+        fn check_decimal_exhaustive(
+            variant: Variant,
+            opcode: u8,
+            predict: fn(u8, u8, bool) -> Prediction,
+        ) {
+            let mut processor = Processor::new(vec![
+                opcode, 0x00, // ADC/SBC #$00
+            ])
+            .with_variant(variant);
+
+            for n1 in 0..=0xFF {
+                for n2 in 0..=0xFF {
+                    for carry_in in [false, true] {
+                        // Set state of processor
+                        processor.set_register().pc = 0x0800;
+                        processor.set_register().acc = n1;
+                        processor.set_register().status = Status::UNUSED | Status::DECIMAL;
+                        processor.set_register().status.set(Status::CARRY, carry_in);
+                        processor.set_mem(0x0801, n2);
+
+                        // Execute instruction
+                        processor.step().unwrap();
+
+                        // Check processor state
+                        let acc = processor.get_registers().acc;
+                        let (zero, negative, carry, overflow, ..) = get_status(&processor);
+                        assert_eq!(
+                            (acc, negative, overflow, zero, carry),
+                            predict(n1, n2, carry_in),
+                            "{:#04X} {:#04X} {:#04X} carry {}",
+                            opcode,
+                            n1,
+                            n2,
+                            carry_in
+                        );
+                    }
+                }
+            }
+        }
+
+        #[test]
+        // ADC/SBC - Every operand and carry combination in decimal mode
+        fn decimal_exhaustive_test() {
+            check_decimal_exhaustive(Variant::Nmos6502, ADC_IM, predict_adc);
+            check_decimal_exhaustive(Variant::Nmos6502, SBC_IM, predict_sbc);
+        }
+
+        #[test]
+        // ADC/SBC - Every operand and carry combination in decimal mode on the 65C02
+        fn decimal_exhaustive_cmos_test() {
+            check_decimal_exhaustive(Variant::Wdc65C02, ADC_IM, predict_adc_cmos);
+            check_decimal_exhaustive(Variant::Wdc65C02, SBC_IM, predict_sbc_cmos);
+        }
+
+        #[test]
+        // CMP - Compare Accumulator
+        fn cmp_test() {
+            let mut processor = Processor::new(vec![
+                CMP_IM, 0x20, // CMP #$20 ; Carry
+                CMP_IM, 0x42, // CMP #$42 ; Zero, carry
+                CMP_IM, 0x43, // CMP #$43 ; Negative
+                CMP_IM, 0x01, // CMP #$01 ; Negative, carry
+            ]);
+
+            // Set state of processor
+            processor.set_register().acc = 0x42;
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let acc = processor.get_registers().acc;
+            let (zero, negative, carry, ..) = get_status(&processor);
+            // 0x42 - 0x20 = 0x22
+            assert_eq!(acc, 0x42);
+            assert!(!zero);
+            assert!(!negative);
+            assert!(carry);
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let (zero, negative, carry, ..) = get_status(&processor);
+            // 0x42 - 0x42 = 0x00
+            assert!(zero);
+            assert!(!negative);
+            assert!(carry);
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let (zero, negative, carry, ..) = get_status(&processor);
+            // 0x42 - 0x43 = 0xFF
+            assert!(!zero);
+            assert!(negative);
+            assert!(!carry);
+
+            // Set state of processor
+            processor.set_register().acc = 0x90;
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let (zero, negative, carry, ..) = get_status(&processor);
+            // 0x90 - 0x01 = 0x8F
+            assert!(!zero);
+            assert!(negative);
+            assert!(carry);
+        }
+
+        #[test]
+        // CPX - Compare X Register
+        fn cpx_test() {
+            let mut processor = Processor::new(vec![
+                CPX_IM, 0x20, // CPX #$20 ; Carry
+                CPX_IM, 0x42, // CPX #$42 ; Zero, carry
+                CPX_IM, 0x43, // CPX #$43 ; Negative
+                CPX_IM, 0x01, // CPX #$01 ; Negative, carry
+            ]);
+
+            // Set state of processor
+            processor.set_register().x = 0x42;
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let x = processor.get_registers().x;
+            let (zero, negative, carry, ..) = get_status(&processor);
+            // 0x42 - 0x20 = 0x22
+            assert_eq!(x, 0x42);
+            assert!(!zero);
+            assert!(!negative);
+            assert!(carry);
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let (zero, negative, carry, ..) = get_status(&processor);
+            // 0x42 - 0x42 = 0x00
+            assert!(zero);
+            assert!(!negative);
+            assert!(carry);
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let (zero, negative, carry, ..) = get_status(&processor);
+            // 0x42 - 0x43 = 0xFF
+            assert!(!zero);
+            assert!(negative);
+            assert!(!carry);
+
+            // Set state of processor
+            processor.set_register().x = 0x90;
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let (zero, negative, carry, ..) = get_status(&processor);
+            // 0x90 - 0x01 = 0x8F
+            assert!(!zero);
+            assert!(negative);
+            assert!(carry);
+        }
+
+        #[test]
+        // CPY - Compare Y Register
+        fn cpy_test() {
+            let mut processor = Processor::new(vec![
+                CPY_IM, 0x20, // CPY #$20 ; Carry
+                CPY_IM, 0x42, // CPY #$42 ; Zero, carry
+                CPY_IM, 0x43, // CPY #$43 ; Negative
+                CPY_IM, 0x01, // CPY #$01 ; Negative, carry
+            ]);
+
+            // Set state of processor
+            processor.set_register().y = 0x42;
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let y = processor.get_registers().y;
+            let (zero, negative, carry, ..) = get_status(&processor);
+            // 0x42 - 0x20 = 0x22
+            assert_eq!(y, 0x42);
+            assert!(!zero);
+            assert!(!negative);
+            assert!(carry);
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let (zero, negative, carry, ..) = get_status(&processor);
+            // 0x42 - 0x42 = 0x00
+            assert!(zero);
+            assert!(!negative);
+            assert!(carry);
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let (zero, negative, carry, ..) = get_status(&processor);
+            // 0x42 - 0x43 = 0xFF
+            assert!(!zero);
+            assert!(negative);
+            assert!(!carry);
+
+            // Set state of processor
+            processor.set_register().y = 0x90;
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let (zero, negative, carry, ..) = get_status(&processor);
+            // 0x90 - 0x01 = 0x8F
+            assert!(!zero);
+            assert!(negative);
+            assert!(carry);
+        }
+    }
+
+    // Increments/decrements
+    mod increments {
+        use super::*;
+        use crate::{
+            device::Device,
+            opcodes::{DEC_ZP, DEX, DEY, INC_ABS, INC_ZP, INX, INY},
+            processor::Processor,
+        };
+        use std::{cell::RefCell, rc::Rc};
+
+        // Device that records every write made to it
+        struct Recorder {
+            writes: Rc<RefCell<Vec<(u32, u8)>>>,
+        }
+
+        impl Device for Recorder {
+            fn read_type(&self) -> String {
+                "Recorder".to_string()
+            }
+
+            fn read(&self, _address: u32) -> u8 {
+                0x41
+            }
+
+            fn write(&mut self, address: u32, data: u8) {
+                self.writes.borrow_mut().push((address, data));
+            }
+        }
+
+        #[test]
+        // INC - Increment Memory
+        fn test_inc() {
+            // Create a new processor
+            let mut processor = Processor::new(vec![
+                INC_ZP, 0x10, // INC $10 ; No flags
+                INC_ZP, 0x11, // INC $11 ; Zero
+                INC_ZP, 0x12, // INC $12 ; Negative
+            ]);
+
+            // Set state of processor
+            processor.set_mem(0x0010, 0x41);
+            processor.set_mem(0x0011, 0xFF);
+            processor.set_mem(0x0012, 0x7F);
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let value = processor.get_mem(0x0010);
+            let (zero, negative, ..) = get_status(&processor);
+            assert_eq!(value, 0x42);
+            assert!(!zero);
+            assert!(!negative);
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let value = processor.get_mem(0x0011);
+            let (zero, negative, ..) = get_status(&processor);
+            assert_eq!(value, 0x00);
+            assert!(zero);
+            assert!(!negative);
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let value = processor.get_mem(0x0012);
+            let (zero, negative, ..) = get_status(&processor);
+            assert_eq!(value, 0x80);
+            assert!(!zero);
+            assert!(negative);
+        }
+
+        #[test]
+        // INX - Increment X Register
+        fn test_inx() {
+            // Create a new processor
+            let mut processor = Processor::new(vec![
+                INX, // INX ; No flags
+                INX, // INX ; Zero
+                INX, // INX ; Negative
+            ]);
+
+            // Set state of processor
+            processor.set_register().x = 0x41;
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let x = processor.get_registers().x;
+            let (zero, negative, ..) = get_status(&processor);
+            assert_eq!(x, 0x42);
+            assert!(!zero);
+            assert!(!negative);
+
+            // Set state of processor
+            processor.set_register().x = 0xFF;
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let x = processor.get_registers().x;
+            let (zero, negative, ..) = get_status(&processor);
+            assert_eq!(x, 0x00);
+            assert!(zero);
+            assert!(!negative);
+
+            // Set state of processor
+            processor.set_register().x = 0x7F;
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let x = processor.get_registers().x;
+            let (zero, negative, ..) = get_status(&processor);
+            assert_eq!(x, 0x80);
+            assert!(!zero);
+            assert!(negative);
+        }
+
+        #[test]
+        // INY - Increment Y Register
+        fn test_iny() {
+            // Create a new processor
+            let mut processor = Processor::new(vec![
+                INY, // INY ; No flags
+                INY, // INY ; Zero
+                INY, // INY ; Negative
+            ]);
+
+            // Set state of processor
+            processor.set_register().y = 0x41;
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let y = processor.get_registers().y;
+            let (zero, negative, ..) = get_status(&processor);
+            assert_eq!(y, 0x42);
+            assert!(!zero);
+            assert!(!negative);
+
+            // Set state of processor
+            processor.set_register().y = 0xFF;
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let y = processor.get_registers().y;
+            let (zero, negative, ..) = get_status(&processor);
+            assert_eq!(y, 0x00);
+            assert!(zero);
+            assert!(!negative);
+
+            // Set state of processor
+            processor.set_register().y = 0x7F;
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let y = processor.get_registers().y;
+            let (zero, negative, ..) = get_status(&processor);
+            assert_eq!(y, 0x80);
+            assert!(!zero);
+            assert!(negative);
+        }
+
+        #[test]
+        // DEC - Decrement Memory
+        fn test_dec() {
+            // Create a new processor
+            let mut processor = Processor::new(vec![
+                DEC_ZP, 0x10, // DEC $10 ; No flags
+                DEC_ZP, 0x11, // DEC $11 ; Zero
+                DEC_ZP, 0x12, // DEC $12 ; Negative
+            ]);
+
+            // Set state of processor
+            processor.set_mem(0x0010, 0x43);
+            processor.set_mem(0x0011, 0x01);
+            processor.set_mem(0x0012, 0x00);
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let value = processor.get_mem(0x0010);
+            let (zero, negative, ..) = get_status(&processor);
+            assert_eq!(value, 0x42);
+            assert!(!zero);
+            assert!(!negative);
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let value = processor.get_mem(0x0011);
+            let (zero, negative, ..) = get_status(&processor);
+            assert_eq!(value, 0x00);
+            assert!(zero);
+            assert!(!negative);
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let value = processor.get_mem(0x0012);
+            let (zero, negative, ..) = get_status(&processor);
+            assert_eq!(value, 0xFF);
+            assert!(!zero);
+            assert!(negative);
+        }
+
+        #[test]
+        // DEX - Decrement X Register
+        fn test_dex() {
+            // Create a new processor
+            let mut processor = Processor::new(vec![
+                DEX, // DEX ; No flags
+                DEX, // DEX ; Zero
+                DEX, // DEX ; Negative
+            ]);
+
+            // Set state of processor
+            processor.set_register().x = 0x43;
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let x = processor.get_registers().x;
+            let (zero, negative, ..) = get_status(&processor);
+            assert_eq!(x, 0x42);
+            assert!(!zero);
+            assert!(!negative);
+
+            // Set state of processor
+            processor.set_register().x = 0x01;
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let x = processor.get_registers().x;
+            let (zero, negative, ..) = get_status(&processor);
+            assert_eq!(x, 0x00);
+            assert!(zero);
+            assert!(!negative);
+
+            // Set state of processor
+            processor.set_register().x = 0x00;
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let x = processor.get_registers().x;
+            let (zero, negative, ..) = get_status(&processor);
+            assert_eq!(x, 0xFF);
+            assert!(!zero);
+            assert!(negative);
+        }
+
+        #[test]
+        // DEY - Decrement Y Register
+        fn test_dey() {
+            // Create a new processor
+            let mut processor = Processor::new(vec![
+                DEY, // DEY ; No flags
+                DEY, // DEY ; Zero
+                DEY, // DEY ; Negative
+            ]);
+
+            // Set state of processor
+            processor.set_register().y = 0x43;
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let y = processor.get_registers().y;
+            let (zero, negative, ..) = get_status(&processor);
+            assert_eq!(y, 0x42);
+            assert!(!zero);
+            assert!(!negative);
+
+            // Set state of processor
+            processor.set_register().y = 0x01;
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let y = processor.get_registers().y;
+            let (zero, negative, ..) = get_status(&processor);
+            assert_eq!(y, 0x00);
+            assert!(zero);
+            assert!(!negative);
+
+            // Set state of processor
+            processor.set_register().y = 0x00;
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let y = processor.get_registers().y;
+            let (zero, negative, ..) = get_status(&processor);
+            assert_eq!(y, 0xFF);
+            assert!(!zero);
+            assert!(negative);
+        }
+
+        #[test]
+        // INC - Increment Memory on a mapped device
+        fn test_inc_device() {
+            // Create a new processor
+            let mut processor = Processor::new(vec![
+                INC_ABS, 0x02, 0x40, // INC $4002
+            ]);
+
+            // Set state of processor
+            let writes = Rc::new(RefCell::new(Vec::new()));
+            processor
+                .map(
+                    0x4000,
+                    0x40FF,
+                    Box::new(Recorder {
+                        writes: writes.clone(),
+                    }),
+                )
+                .unwrap();
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check device state
+            assert_eq!(writes.borrow().last(), Some(&(0x0002, 0x42)));
+        }
+    }
+
+    // Shifts
+    mod shifts {
+        use super::*;
+        use crate::{
+            opcodes::{ASL_ACC, ASL_ZP, LSR_ACC, LSR_ZP, ROL_ACC, ROL_ZP, ROR_ACC, ROR_ZP},
+            processor::Processor,
+        };
+
+        #[test]
+        // ASL - Arithmetic Shift Left
+        fn test_asl() {
+            // Create a new processor
+            let mut processor = Processor::new(vec![
+                ASL_ACC, // ASL A ; No flags
+                ASL_ACC, // ASL A ; Zero, carry
+                ASL_ACC, // ASL A ; Negative
+                ASL_ZP, 0x10, // ASL $10 ; Negative, carry
+            ]);
+
+            // Set state of processor
+            processor.set_register().acc = 0x21;
+            processor.set_register().status.set(Status::CARRY, false);
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let acc = processor.get_registers().acc;
+            let (zero, negative, carry, ..) = get_status(&processor);
+            assert_eq!(acc, 0x42);
+            assert!(!zero);
+            assert!(!negative);
+            assert!(!carry);
+
+            // Set state of processor
+            processor.set_register().acc = 0x80;
+            processor.set_register().status.set(Status::CARRY, false);
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let acc = processor.get_registers().acc;
+            let (zero, negative, carry, ..) = get_status(&processor);
+            assert_eq!(acc, 0x00);
+            assert!(zero);
+            assert!(!negative);
+            assert!(carry);
+
+            // Set state of processor
+            processor.set_register().acc = 0x41;
+            processor.set_register().status.set(Status::CARRY, false);
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let acc = processor.get_registers().acc;
+            let (zero, negative, carry, ..) = get_status(&processor);
+            assert_eq!(acc, 0x82);
+            assert!(!zero);
+            assert!(negative);
+            assert!(!carry);
+
+            // Set state of processor
+            processor.set_mem(0x0010, 0xC1);
+            processor.set_register().status.set(Status::CARRY, false);
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let value = processor.get_mem(0x0010);
+            let (zero, negative, carry, ..) = get_status(&processor);
+            assert_eq!(value, 0x82);
+            assert!(!zero);
+            assert!(negative);
+            assert!(carry);
+        }
+
+        #[test]
+        // LSR - Logical Shift Right
+        fn test_lsr() {
+            // Create a new processor
+            let mut processor = Processor::new(vec![
+                LSR_ACC, // LSR A ; No flags
+                LSR_ACC, // LSR A ; Zero, carry
+                LSR_ACC, // LSR A ; Carry
+                LSR_ZP, 0x10, // LSR $10 ; Carry
+            ]);
+
+            // Set state of processor
+            processor.set_register().acc = 0x84;
+            processor.set_register().status.set(Status::CARRY, false);
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let acc = processor.get_registers().acc;
+            let (zero, negative, carry, ..) = get_status(&processor);
+            assert_eq!(acc, 0x42);
+            assert!(!zero);
+            assert!(!negative);
+            assert!(!carry);
+
+            // Set state of processor
+            processor.set_register().acc = 0x01;
+            processor.set_register().status.set(Status::CARRY, false);
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let acc = processor.get_registers().acc;
+            let (zero, negative, carry, ..) = get_status(&processor);
+            assert_eq!(acc, 0x00);
+            assert!(zero);
+            assert!(!negative);
+            assert!(carry);
+
+            // Set state of processor
+            processor.set_register().acc = 0xFF;
+            processor.set_register().status.set(Status::CARRY, true);
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let acc = processor.get_registers().acc;
+            let (zero, negative, carry, ..) = get_status(&processor);
+            assert_eq!(acc, 0x7F);
+            assert!(!zero);
+            assert!(!negative);
+            assert!(carry);
+
+            // Set state of processor
+            processor.set_mem(0x0010, 0x85);
+            processor.set_register().status.set(Status::CARRY, false);
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let value = processor.get_mem(0x0010);
+            let (zero, negative, carry, ..) = get_status(&processor);
+            assert_eq!(value, 0x42);
+            assert!(!zero);
+            assert!(!negative);
+            assert!(carry);
+        }
+
+        #[test]
+        // ROL - Rotate Left
+        fn test_rol() {
+            // Create a new processor
+            let mut processor = Processor::new(vec![
+                ROL_ACC, // ROL A ; No flags
+                ROL_ACC, // ROL A ; Zero, carry
+                ROL_ACC, // ROL A ; Negative
+                ROL_ZP, 0x10, // ROL $10 ; Carry
+            ]);
+
+            // Set state of processor
+            processor.set_register().acc = 0x21;
+            processor.set_register().status.set(Status::CARRY, false);
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let acc = processor.get_registers().acc;
+            let (zero, negative, carry, ..) = get_status(&processor);
+            assert_eq!(acc, 0x42);
+            assert!(!zero);
+            assert!(!negative);
+            assert!(!carry);
+
+            // Set state of processor
+            processor.set_register().acc = 0x80;
+            processor.set_register().status.set(Status::CARRY, false);
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let acc = processor.get_registers().acc;
+            let (zero, negative, carry, ..) = get_status(&processor);
+            assert_eq!(acc, 0x00);
+            assert!(zero);
+            assert!(!negative);
+            assert!(carry);
+
+            // Set state of processor
+            processor.set_register().acc = 0x40;
+            processor.set_register().status.set(Status::CARRY, true);
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let acc = processor.get_registers().acc;
+            let (zero, negative, carry, ..) = get_status(&processor);
+            assert_eq!(acc, 0x81);
+            assert!(!zero);
+            assert!(negative);
+            assert!(!carry);
+
+            // Set state of processor
+            processor.set_mem(0x0010, 0x80);
+            processor.set_register().status.set(Status::CARRY, true);
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let value = processor.get_mem(0x0010);
+            let (zero, negative, carry, ..) = get_status(&processor);
+            assert_eq!(value, 0x01);
+            assert!(!zero);
+            assert!(!negative);
+            assert!(carry);
+        }
+
+        #[test]
+        // ROR - Rotate Right
+        fn test_ror() {
+            // Create a new processor
+            let mut processor = Processor::new(vec![
+                ROR_ACC, // ROR A ; No flags
+                ROR_ACC, // ROR A ; Zero, carry
+                ROR_ACC, // ROR A ; Negative
+                ROR_ZP, 0x10, // ROR $10 ; Negative, carry
+            ]);
+
+            // Set state of processor
+            processor.set_register().acc = 0x84;
+            processor.set_register().status.set(Status::CARRY, false);
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let acc = processor.get_registers().acc;
+            let (zero, negative, carry, ..) = get_status(&processor);
+            assert_eq!(acc, 0x42);
+            assert!(!zero);
+            assert!(!negative);
+            assert!(!carry);
+
+            // Set state of processor
+            processor.set_register().acc = 0x01;
+            processor.set_register().status.set(Status::CARRY, false);
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let acc = processor.get_registers().acc;
+            let (zero, negative, carry, ..) = get_status(&processor);
+            assert_eq!(acc, 0x00);
+            assert!(zero);
+            assert!(!negative);
+            assert!(carry);
+
+            // Set state of processor
+            processor.set_register().acc = 0x02;
+            processor.set_register().status.set(Status::CARRY, true);
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let acc = processor.get_registers().acc;
+            let (zero, negative, carry, ..) = get_status(&processor);
+            assert_eq!(acc, 0x81);
+            assert!(!zero);
+            assert!(negative);
+            assert!(!carry);
+
+            // Set state of processor
+            processor.set_mem(0x0010, 0x01);
+            processor.set_register().status.set(Status::CARRY, true);
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let value = processor.get_mem(0x0010);
+            let (zero, negative, carry, ..) = get_status(&processor);
+            assert_eq!(value, 0x80);
+            assert!(!zero);
+            assert!(negative);
+            assert!(carry);
+        }
+    }
+
+    // Jumps
+    mod jumps {
+        use crate::{
+            opcodes::{JMP_ABS, JMP_IND, JSR, RTS},
+            processor::Processor,
+        };
+
+        #[test]
+        // JMP - Jump absolute
+        fn test_jmp_absolute() {
+            // Create a new processor
+            let mut processor = Processor::new(vec![
+                JMP_ABS, 0x34, 0x12, // JMP $1234
+            ]);
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let pc = processor.get_registers().pc;
+            assert_eq!(pc, 0x1234);
+        }
+
+        #[test]
+        // JMP - Jump indirect
+        fn test_jmp_indirect() {
+            // Create a new processor
+            let mut processor = Processor::new(vec![
+                JMP_IND, 0x20, 0x01, // JMP ($0120)
+            ]);
+
+            // Set state of processor
+            processor.set_mem(0x0120, 0x34);
+            processor.set_mem(0x0121, 0x12);
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let pc = processor.get_registers().pc;
+            assert_eq!(pc, 0x1234);
+        }
+
+        #[test]
+        // JMP - Jump indirect with the pointer on a page boundary (NMOS bug)
+        fn test_jmp_indirect_page_boundary() {
+            // Create a new processor
+            let mut processor = Processor::new(vec![
+                JMP_IND, 0xFF, 0x02, // JMP ($02FF)
+            ]);
+
+            // Set state of processor
+            processor.set_mem(0x02FF, 0x34);
+            processor.set_mem(0x0300, 0x56);
+            processor.set_mem(0x0200, 0x12);
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let pc = processor.get_registers().pc;
+            assert_eq!(pc, 0x1234);
+        }
+
+        #[test]
+        // JMP - Jump indirect with the pointer on a page boundary (bug disabled)
+        fn test_jmp_indirect_page_boundary_fixed() {
+            // Create a new processor
+            let mut processor = Processor::new(vec![
+                JMP_IND, 0xFF, 0x02, // JMP ($02FF)
+            ]);
+
+            // Set state of processor
+            processor.set_jmp_indirect_bug(false);
+            processor.set_mem(0x02FF, 0x34);
+            processor.set_mem(0x0300, 0x56);
+            processor.set_mem(0x0200, 0x12);
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let pc = processor.get_registers().pc;
+            assert_eq!(pc, 0x5634);
+        }
+
+        #[test]
+        // JSR - Jump to Subroutine
+        fn test_jsr() {
+            // Create a new processor
+            let mut processor = Processor::new(vec![
+                JSR, 0x34, 0x12, // JSR $1234
+            ]);
+
+            // Set state of processor
+            processor.set_register().sp = 0xFF;
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let pc = processor.get_registers().pc;
+            let sp = processor.get_registers().sp;
+            assert_eq!(pc, 0x1234);
+            assert_eq!(sp, 0xFD);
+            assert_eq!(processor.get_mem(0x01FF), 0x08);
+            assert_eq!(processor.get_mem(0x01FE), 0x02);
+        }
+
+        #[test]
+        // RTS - Return from Subroutine
+        fn test_rts() {
+            // Create a new processor
+            let mut processor = Processor::new(vec![
+                RTS, // RTS
+            ]);
+
+            // Set state of processor
+            processor.set_register().sp = 0xFD;
+            processor.set_mem(0x01FF, 0x12);
+            processor.set_mem(0x01FE, 0x33);
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let pc = processor.get_registers().pc;
+            let sp = processor.get_registers().sp;
+            assert_eq!(pc, 0x1234);
+            assert_eq!(sp, 0xFF);
+        }
+
+        #[test]
+        // JSR/RTS - Nested subroutine calls
+        fn test_jsr_rts_nested() {
+            // Create a new processor
+            let mut processor = Processor::new(vec![
+                JSR, 0x10, 0x08, // JSR $0810
+            ]);
+
+            // Set state of processor
+            processor.set_register().sp = 0xFF;
+            processor.set_mem(0x0810, JSR); // JSR $0820
+            processor.set_mem(0x0811, 0x20);
+            processor.set_mem(0x0812, 0x08);
+            processor.set_mem(0x0813, RTS); // RTS
+            processor.set_mem(0x0820, RTS); // RTS
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let pc = processor.get_registers().pc;
+            let sp = processor.get_registers().sp;
+            assert_eq!(pc, 0x0810);
+            assert_eq!(sp, 0xFD);
+            assert_eq!(processor.get_mem(0x01FF), 0x08);
+            assert_eq!(processor.get_mem(0x01FE), 0x02);
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let pc = processor.get_registers().pc;
+            let sp = processor.get_registers().sp;
+            assert_eq!(pc, 0x0820);
+            assert_eq!(sp, 0xFB);
+            assert_eq!(processor.get_mem(0x01FD), 0x08);
+            assert_eq!(processor.get_mem(0x01FC), 0x12);
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let pc = processor.get_registers().pc;
+            let sp = processor.get_registers().sp;
+            assert_eq!(pc, 0x0813);
+            assert_eq!(sp, 0xFD);
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let pc = processor.get_registers().pc;
+            let sp = processor.get_registers().sp;
+            assert_eq!(pc, 0x0803);
+            assert_eq!(sp, 0xFF);
+        }
+    }
+
+    // Branches
+    mod branches {
+        use super::*;
+        use crate::{
+            opcodes::{BCC, BCS, BEQ, BMI, BNE, BPL, BVC, BVS},
+            processor::{Branch, Processor},
+        };
+
+        #[test]
+        // BPL - Branch if Positive
+        fn test_bpl() {
+            // Create a new processor
+            let mut processor = Processor::new(vec![
+                BPL, 0x10, // BPL $0812 ; Not taken
+                BPL, 0x10, // BPL $0814 ; Taken
+            ]);
+
+            // Set state of processor
+            processor.set_register().status.set(Status::NEGATIVE, true);
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let pc = processor.get_registers().pc;
+            assert_eq!(pc, 0x0802);
+            assert_eq!(processor.last_branch(), Some(Branch::NotTaken));
+
+            // Set state of processor
+            processor.set_register().status.set(Status::NEGATIVE, false);
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let pc = processor.get_registers().pc;
+            assert_eq!(pc, 0x0814);
+            assert_eq!(processor.last_branch(), Some(Branch::Taken));
+        }
+
+        #[test]
+        // BMI - Branch if Minus
+        fn test_bmi() {
+            // Create a new processor
+            let mut processor = Processor::new(vec![
+                BMI, 0x10, // BMI $0812 ; Not taken
+                BMI, 0x10, // BMI $0814 ; Taken
+            ]);
+
+            // Set state of processor
+            processor.set_register().status.set(Status::NEGATIVE, false);
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let pc = processor.get_registers().pc;
+            assert_eq!(pc, 0x0802);
+            assert_eq!(processor.last_branch(), Some(Branch::NotTaken));
+
+            // Set state of processor
+            processor.set_register().status.set(Status::NEGATIVE, true);
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let pc = processor.get_registers().pc;
+            assert_eq!(pc, 0x0814);
+            assert_eq!(processor.last_branch(), Some(Branch::Taken));
+        }
+
+        #[test]
+        // BVC - Branch if Overflow Clear
+        fn test_bvc() {
+            // Create a new processor
+            let mut processor = Processor::new(vec![
+                BVC, 0x10, // BVC $0812 ; Not taken
+                BVC, 0x10, // BVC $0814 ; Taken
+            ]);
+
+            // Set state of processor
+            processor.set_register().status.set(Status::OVERFLOW, true);
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let pc = processor.get_registers().pc;
+            assert_eq!(pc, 0x0802);
+            assert_eq!(processor.last_branch(), Some(Branch::NotTaken));
+
+            // Set state of processor
+            processor.set_register().status.set(Status::OVERFLOW, false);
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let pc = processor.get_registers().pc;
+            assert_eq!(pc, 0x0814);
+            assert_eq!(processor.last_branch(), Some(Branch::Taken));
+        }
+
+        #[test]
+        // BVS - Branch if Overflow Set
+        fn test_bvs() {
+            // Create a new processor
+            let mut processor = Processor::new(vec![
+                BVS, 0x10, // BVS $0812 ; Not taken
+                BVS, 0x10, // BVS $0814 ; Taken
+            ]);
+
+            // Set state of processor
+            processor.set_register().status.set(Status::OVERFLOW, false);
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let pc = processor.get_registers().pc;
+            assert_eq!(pc, 0x0802);
+            assert_eq!(processor.last_branch(), Some(Branch::NotTaken));
+
+            // Set state of processor
+            processor.set_register().status.set(Status::OVERFLOW, true);
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let pc = processor.get_registers().pc;
+            assert_eq!(pc, 0x0814);
+            assert_eq!(processor.last_branch(), Some(Branch::Taken));
+        }
+
+        #[test]
+        // BCC - Branch if Carry Clear
+        fn test_bcc() {
+            // Create a new processor
+            let mut processor = Processor::new(vec![
+                BCC, 0x10, // BCC $0812 ; Not taken
+                BCC, 0x10, // BCC $0814 ; Taken
+            ]);
+
+            // Set state of processor
+            processor.set_register().status.set(Status::CARRY, true);
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let pc = processor.get_registers().pc;
+            assert_eq!(pc, 0x0802);
+            assert_eq!(processor.last_branch(), Some(Branch::NotTaken));
+
+            // Set state of processor
+            processor.set_register().status.set(Status::CARRY, false);
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let pc = processor.get_registers().pc;
+            assert_eq!(pc, 0x0814);
+            assert_eq!(processor.last_branch(), Some(Branch::Taken));
+        }
+
+        #[test]
+        // BCS - Branch if Carry Set
+        fn test_bcs() {
+            // Create a new processor
+            let mut processor = Processor::new(vec![
+                BCS, 0x10, // BCS $0812 ; Not taken
+                BCS, 0x10, // BCS $0814 ; Taken
+            ]);
+
+            // Set state of processor
+            processor.set_register().status.set(Status::CARRY, false);
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let pc = processor.get_registers().pc;
+            assert_eq!(pc, 0x0802);
+            assert_eq!(processor.last_branch(), Some(Branch::NotTaken));
+
+            // Set state of processor
+            processor.set_register().status.set(Status::CARRY, true);
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let pc = processor.get_registers().pc;
+            assert_eq!(pc, 0x0814);
+            assert_eq!(processor.last_branch(), Some(Branch::Taken));
+        }
+
+        #[test]
+        // BNE - Branch if Not Equal
+        fn test_bne() {
+            // Create a new processor
+            let mut processor = Processor::new(vec![
+                BNE, 0x10, // BNE $0812 ; Not taken
+                BNE, 0x10, // BNE $0814 ; Taken
+            ]);
+
+            // Set state of processor
+            processor.set_register().status.set(Status::ZERO, true);
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let pc = processor.get_registers().pc;
+            assert_eq!(pc, 0x0802);
+            assert_eq!(processor.last_branch(), Some(Branch::NotTaken));
+
+            // Set state of processor
+            processor.set_register().status.set(Status::ZERO, false);
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let pc = processor.get_registers().pc;
+            assert_eq!(pc, 0x0814);
+            assert_eq!(processor.last_branch(), Some(Branch::Taken));
+        }
+
+        #[test]
+        // BEQ - Branch if Equal
+        fn test_beq() {
+            // Create a new processor
+            let mut processor = Processor::new(vec![
+                BEQ, 0x10, // BEQ $0812 ; Not taken
+                BEQ, 0x10, // BEQ $0814 ; Taken
+            ]);
+
+            // Set state of processor
+            processor.set_register().status.set(Status::ZERO, false);
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let pc = processor.get_registers().pc;
+            assert_eq!(pc, 0x0802);
+            assert_eq!(processor.last_branch(), Some(Branch::NotTaken));
+
+            // Set state of processor
+            processor.set_register().status.set(Status::ZERO, true);
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let pc = processor.get_registers().pc;
+            assert_eq!(pc, 0x0814);
+            assert_eq!(processor.last_branch(), Some(Branch::Taken));
+        }
+
+        #[test]
+        // Branch backwards with a negative offset
+        fn test_branch_backward() {
+            // Create a new processor
+            let mut processor = Processor::new(vec![
+                BNE, 0xFE, // BNE $0800
+            ]);
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let pc = processor.get_registers().pc;
+            assert_eq!(pc, 0x0800);
+            assert_eq!(processor.last_branch(), Some(Branch::Taken));
+        }
+
+        #[test]
+        // Branch to another page
+        fn test_branch_page_crossed() {
+            // Create a new processor
+            let mut processor = Processor::new(vec![
+                BNE, 0x80, // BNE $0782
+            ]);
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let pc = processor.get_registers().pc;
+            assert_eq!(pc, 0x0782);
+            assert_eq!(processor.last_branch(), Some(Branch::PageCrossed));
+        }
+
+        #[test]
+        // Branch across the bottom of the address space
+        fn test_branch_wraparound() {
+            // Create a new processor
+            let mut processor = Processor::new(vec![]);
+
+            // Set state of processor
+            processor.set_register().pc = 0x0000;
+            processor.set_mem(0x0000, BNE); // BNE $FFFE
+            processor.set_mem(0x0001, 0xFC);
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let pc = processor.get_registers().pc;
+            assert_eq!(pc, 0xFFFE);
+            assert_eq!(processor.last_branch(), Some(Branch::PageCrossed));
+        }
+    }
+
+    // Status flags
+    mod flags {
+        use super::*;
+        use crate::{
+            opcodes::{CLC, CLD, CLI, CLV, SEC, SED, SEI},
+            processor::Processor,
+        };
+
+        #[test]
+        // CLC - Clear Carry Flag
+        fn test_clc() {
+            // Create a new processor
+            let mut processor = Processor::new(vec![
+                CLC, // CLC
+            ]);
+
+            // Set state of processor
+            processor.set_register().status.set(Status::CARRY, true);
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let (_zero, _negative, carry, ..) = get_status(&processor);
+            assert!(!carry);
+        }
+
+        #[test]
+        // CLD - Clear Decimal Mode Flag
+        fn test_cld() {
+            // Create a new processor
+            let mut processor = Processor::new(vec![
+                CLD, // CLD
+            ]);
+
+            // Set state of processor
+            processor.set_register().status.set(Status::DECIMAL, true);
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let (_zero, _negative, _carry, _overflow, decimal, ..) = get_status(&processor);
+            assert!(!decimal);
+        }
+
+        #[test]
+        // CLI - Clear Interrupt Disable Flag
+        fn test_cli() {
+            // Create a new processor
+            let mut processor = Processor::new(vec![
+                CLI, // CLI
+            ]);
+
+            // Set state of processor
+            processor.set_register().status.set(Status::INTERRUPT, true);
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let (_zero, _negative, _carry, _overflow, _decimal, interrupt, ..) =
+                get_status(&processor);
+            assert!(!interrupt);
+        }
+
+        #[test]
+        // CLV - Clear Overflow Flag
+        fn test_clv() {
+            // Create a new processor
+            let mut processor = Processor::new(vec![
+                CLV, // CLV
+            ]);
+
+            // Set state of processor
+            processor.set_register().status.set(Status::OVERFLOW, true);
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let (_zero, _negative, _carry, overflow, ..) = get_status(&processor);
+            assert!(!overflow);
+        }
+
+        #[test]
+        // SEC - Set Carry Flag
+        fn test_sec() {
+            // Create a new processor
+            let mut processor = Processor::new(vec![
+                SEC, // SEC
+            ]);
+
+            // Set state of processor
+            processor.set_register().status.set(Status::CARRY, false);
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let (_zero, _negative, carry, ..) = get_status(&processor);
+            assert!(carry);
+        }
+
+        #[test]
+        // SED - Set Decimal Mode Flag
+        fn test_sed() {
+            // Create a new processor
+            let mut processor = Processor::new(vec![
+                SED, // SED
+            ]);
+
+            // Set state of processor
+            processor.set_register().status.set(Status::DECIMAL, false);
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let (_zero, _negative, _carry, _overflow, decimal, ..) = get_status(&processor);
+            assert!(decimal);
+        }
+
+        #[test]
+        // SEI - Set Interrupt Disable Flag
+        fn test_sei() {
+            // Create a new processor
+            let mut processor = Processor::new(vec![
+                SEI, // SEI
+            ]);
+
+            // Set state of processor
+            processor
+                .set_register()
+                .status
+                .set(Status::INTERRUPT, false);
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let (_zero, _negative, _carry, _overflow, _decimal, interrupt, ..) =
+                get_status(&processor);
+            assert!(interrupt);
+        }
+    }
+
+    // System
+    mod system {
+        use super::*;
+        use crate::{
+            opcodes::{BRK, NOP, RTI},
+            processor::Processor,
+        };
+
+        #[test]
+        // BRK - Force Interrupt
+        fn test_brk() {
+            // Create a new processor
+            let mut processor = Processor::new(vec![
+                BRK, 0x00, // BRK
+            ]);
+
+            // Set state of processor
+            processor.set_register().sp = 0xFF;
+            processor.set_register().status = Status::CARRY;
+            processor.set_mem(0xFFFE, 0x34);
+            processor.set_mem(0xFFFF, 0x12);
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let pc = processor.get_registers().pc;
+            let sp = processor.get_registers().sp;
+            let (_zero, _negative, carry, _overflow, _decimal, interrupt, break_command, ..) =
+                get_status(&processor);
+            assert_eq!(pc, 0x1234);
+            assert_eq!(sp, 0xFC);
+            assert!(carry);
+            assert!(interrupt);
+            assert!(!break_command);
+
+            // Check stack
+            assert_eq!(processor.get_mem(0x01FF), 0x08);
+            assert_eq!(processor.get_mem(0x01FE), 0x02);
+            assert_eq!(processor.get_mem(0x01FD), 0b0011_0001);
+        }
+
+        #[test]
+        // BRK - Force Interrupt on zero-filled memory
+        fn test_brk_empty_memory() {
+            // Create a new processor
+            let mut processor = Processor::new(vec![]);
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let pc = processor.get_registers().pc;
+            assert_eq!(pc, 0x0000);
+        }
+
+        #[test]
+        // RTI - Return from Interrupt
+        fn test_rti() {
+            // Create a new processor
+            let mut processor = Processor::new(vec![
+                RTI, // RTI
+            ]);
+
+            // Set state of processor
+            processor.set_register().sp = 0xFC;
+            processor.set_register().status = Status::UNUSED | Status::INTERRUPT;
+            processor.set_mem(0x01FD, 0b1101_0011);
+            processor.set_mem(0x01FE, 0x34);
+            processor.set_mem(0x01FF, 0x12);
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let pc = processor.get_registers().pc;
+            let sp = processor.get_registers().sp;
+            let status = processor.get_registers().status.bits();
+            assert_eq!(pc, 0x1234);
+            assert_eq!(sp, 0xFF);
+            assert_eq!(status, 0b1110_0011);
+        }
+
+        #[test]
+        // BRK/RTI - Return to the instruction after BRK and its padding byte
+        fn test_brk_rti() {
+            // Create a new processor
+            let mut processor = Processor::new(vec![
+                BRK, 0x00, // BRK
+                NOP,  // NOP
+            ]);
+
+            // Set state of processor
+            processor.set_register().sp = 0xFF;
+            processor.set_mem(0xFFFE, 0x00);
+            processor.set_mem(0xFFFF, 0x90);
+            processor.set_mem(0x9000, RTI);
+            processor.set_register().status.remove(Status::INTERRUPT);
+
+            // Execute instructions
+            processor.step().unwrap();
+            processor.step().unwrap();
+
+            // Check processor state
+            let pc = processor.get_registers().pc;
+            let sp = processor.get_registers().sp;
+            let (.., interrupt, break_command, unused) = get_status(&processor);
+            assert_eq!(pc, 0x0802);
+            assert_eq!(sp, 0xFF);
+            assert!(!interrupt);
+            assert!(!break_command);
+            assert!(unused);
+        }
+
+        #[test]
+        // NOP - No Operation
+        fn test_nop() {
+            // Create a new processor
+            let mut processor = Processor::new(vec![
+                NOP, // NOP
+            ]);
+
+            // Set state of processor
+            let status = processor.get_registers().status;
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let pc = processor.get_registers().pc;
+            assert_eq!(pc, 0x0801);
+            assert_eq!(processor.get_registers().status, status);
+        }
+    }
+
+    // Interrupts
+    mod interrupts {
+        use super::*;
+        use crate::{
+            opcodes::{CLI, NOP, RTI},
+            processor::Processor,
+        };
+
+        // Create a processor with the interrupt vectors and a handler that returns
+        fn processor_with_vectors(program: Vec<u8>) -> Processor {
+            let mut processor = Processor::new(program);
+            processor.set_register().sp = 0xFF;
+            processor.set_register().status.remove(Status::INTERRUPT);
+            processor.set_mem(0xFFFA, 0x00); // NMI -> $9000
+            processor.set_mem(0xFFFB, 0x90);
+            processor.set_mem(0xFFFE, 0x00); // IRQ -> $A000
+            processor.set_mem(0xFFFF, 0xA0);
+            processor.set_mem(0x9000, RTI);
+            processor.set_mem(0xA000, RTI);
+            processor
+        }
+
+        #[test]
+        // IRQ - Serviced when interrupts are enabled
+        fn test_irq() {
+            // Create a new processor
+            let mut processor = processor_with_vectors(vec![
+                NOP, // NOP
+            ]);
+
+            // Set state of processor
+            processor.set_register().status = Status::UNUSED | Status::CARRY;
+            processor.set_irq(true);
+
+            // Service interrupt
+            processor.step().unwrap();
+
+            // Check processor state
+            let pc = processor.get_registers().pc;
+            let sp = processor.get_registers().sp;
+            let (.., interrupt, _break_command, _unused) = get_status(&processor);
+            assert_eq!(pc, 0xA000);
+            assert_eq!(sp, 0xFC);
+            assert!(interrupt);
+
+            // Check stack, the pushed status has the break flag clear
+            assert_eq!(processor.get_mem(0x01FF), 0x08);
+            assert_eq!(processor.get_mem(0x01FE), 0x00);
+            assert_eq!(processor.get_mem(0x01FD), 0b0010_0001);
+        }
+
+        #[test]
+        // IRQ - Masked by the interrupt disable flag
+        fn test_irq_masked() {
+            // Create a new processor
+            let mut processor = processor_with_vectors(vec![
+                NOP, // NOP ; Masked
+                CLI, // CLI
+                NOP, // NOP ; Serviced before this instruction
+            ]);
+
+            // Set state of processor
+            processor.set_register().status.insert(Status::INTERRUPT);
+            processor.set_irq(true);
+
+            // Execute instructions
+            processor.step().unwrap();
+            processor.step().unwrap();
+
+            // Check processor state
+            let pc = processor.get_registers().pc;
+            assert_eq!(pc, 0x0802);
+
+            // Service interrupt
+            processor.step().unwrap();
+
+            // Check processor state
+            let pc = processor.get_registers().pc;
+            assert_eq!(pc, 0xA000);
+        }
+
+        #[test]
+        // IRQ - Level triggered, serviced again while still asserted
+        fn test_irq_level_triggered() {
+            // Create a new processor
+            let mut processor = processor_with_vectors(vec![
+                NOP, // NOP
+            ]);
+
+            // Set state of processor
+            processor.set_irq(true);
+
+            // Service interrupt and return from it
+            processor.step().unwrap();
+            processor.step().unwrap();
+
+            // Check processor state
+            let pc = processor.get_registers().pc;
+            assert_eq!(pc, 0x0800);
+
+            // Service interrupt again
+            processor.step().unwrap();
+
+            // Check processor state
+            let pc = processor.get_registers().pc;
+            assert_eq!(pc, 0xA000);
+
+            // Return from interrupt with the line deasserted
+            processor.set_irq(false);
+            processor.step().unwrap();
+            processor.step().unwrap();
+
+            // Check processor state
+            let pc = processor.get_registers().pc;
+            assert_eq!(pc, 0x0801);
+        }
+
+        #[test]
+        // NMI - Edge triggered and not masked by the interrupt disable flag
+        fn test_nmi() {
+            // Create a new processor
+            let mut processor = processor_with_vectors(vec![
+                NOP, // NOP
+            ]);
+
+            // Set state of processor
+            processor.set_register().status.insert(Status::INTERRUPT);
+            processor.set_nmi(true);
+
+            // Service interrupt
+            processor.step().unwrap();
+
+            // Check processor state
+            let pc = processor.get_registers().pc;
+            assert_eq!(pc, 0x9000);
+            assert_eq!(processor.get_mem(0x01FD) & 0b0001_0000, 0);
+
+            // Return from interrupt with the line still asserted
+            processor.step().unwrap();
+            processor.step().unwrap();
+
+            // Check processor state
+            let pc = processor.get_registers().pc;
+            assert_eq!(pc, 0x0801);
+
+            // A new edge triggers another interrupt
+            processor.set_nmi(false);
+            processor.set_nmi(true);
+            processor.step().unwrap();
+
+            // Check processor state
+            let pc = processor.get_registers().pc;
+            assert_eq!(pc, 0x9000);
+        }
+
+        #[test]
+        // NMI - Takes priority over IRQ
+        fn test_nmi_priority() {
+            // Create a new processor
+            let mut processor = processor_with_vectors(vec![
+                NOP, // NOP
+            ]);
+
+            // Set state of processor
+            processor.set_irq(true);
+            processor.set_nmi(true);
+
+            // Service interrupt
+            processor.step().unwrap();
+
+            // Check processor state
+            let pc = processor.get_registers().pc;
+            assert_eq!(pc, 0x9000);
+        }
+    }
+
+    // Reset
+    mod reset {
+        use super::*;
+        use crate::{opcodes::NOP, processor::Processor};
+
+        #[test]
+        // Reset - Load the program counter from the reset vector
+        fn test_reset() {
+            // Create a new processor
+            let mut processor = Processor::new(vec![
+                NOP, // NOP
+            ]);
+
+            // Check processor state after power on
+            let pc = processor.get_registers().pc;
+            let sp = processor.get_registers().sp;
+            let (.., interrupt, _break_command, _unused) = get_status(&processor);
+            assert_eq!(pc, 0x0800);
+            assert_eq!(sp, 0xFD);
+            assert!(interrupt);
+
+            // Set state of processor
+            processor.set_register().status.remove(Status::INTERRUPT);
+            processor.set_mem(0xFFFC, 0x34);
+            processor.set_mem(0xFFFD, 0x12);
+
+            // Reset processor
+            processor.reset();
+
+            // Check processor state
+            let pc = processor.get_registers().pc;
+            let sp = processor.get_registers().sp;
+            let (.., interrupt, _break_command, _unused) = get_status(&processor);
+            assert_eq!(pc, 0x1234);
+            assert_eq!(sp, 0xFA);
+            assert!(interrupt);
+        }
+
+        #[test]
+        // Reset - Boot a memory image through its own reset vector
+        fn test_with_image() {
+            // Create a ROM image for $F000-$FFFF
+            let mut image = vec![0x00; 0x1000];
+            image[0x0100] = NOP; // $F100: NOP
+            image[0x0FFC] = 0x00; // Reset vector -> $F100
+            image[0x0FFD] = 0xF1;
+
+            // Create a new processor
+            let mut processor = Processor::with_image(image, 0xF000);
+
+            // Check processor state
+            let pc = processor.get_registers().pc;
+            let sp = processor.get_registers().sp;
+            assert_eq!(pc, 0xF100);
+            assert_eq!(sp, 0xFD);
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let pc = processor.get_registers().pc;
+            assert_eq!(pc, 0xF101);
+        }
+    }
+
+    // Undocumented opcodes
+    mod illegal {
+        use super::*;
+        use crate::{
+            error::Error,
+            opcodes::{
+                illegal::{
+                    ALR_IM, ANC_IM_0B, ARR_IM, DCP_ZP, ISC_ZP, JAM_02, JAM_F2, LAX_ZP, NOP_1A,
+                    NOP_ABSX_1C, NOP_ABS_0C, NOP_IM_80, NOP_ZPX_14, NOP_ZP_04, RLA_ZP, RRA_ZP,
+                    SAX_ZP, SBX_IM, SLO_ZP, SRE_ZP,
+                },
+                NOP,
+            },
+            processor::Processor,
+        };
+
+        // Create a processor with the undocumented opcodes enabled
+        fn processor_with_illegal(program: Vec<u8>) -> Processor {
+            let mut processor = Processor::new(program);
+            processor.set_illegal_opcodes(true);
+            processor
+        }
+
+        #[test]
+        // Undocumented opcodes are unknown unless enabled
+        fn test_illegal_disabled() {
+            // Create a new processor
+            let mut processor = Processor::new(vec![
+                LAX_ZP, 0x10, // LAX $10
+            ]);
+
+            // Execute instruction
+            let error = Error::UnknownOpcode {
+                opcode: LAX_ZP,
+                pc: 0x0800,
+            };
+            assert_eq!(processor.step(), Err(error));
+
+            // Check processor state
+            let pc = processor.get_registers().pc;
+            assert_eq!(pc, 0x0800);
+        }
+
+        #[test]
+        // LAX - Load Accumulator and X Register
+        fn test_lax() {
+            // Create a new processor
+            let mut processor = processor_with_illegal(vec![
+                LAX_ZP, 0x10, // LAX $10 ; Negative
+            ]);
+
+            // Set state of processor
+            processor.set_mem(0x0010, 0x80);
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let acc = processor.get_registers().acc;
+            let x = processor.get_registers().x;
+            let (zero, negative, ..) = get_status(&processor);
+            assert_eq!(acc, 0x80);
+            assert_eq!(x, 0x80);
+            assert!(!zero);
+            assert!(negative);
+        }
+
+        #[test]
+        // SAX - Store Accumulator AND X Register
+        fn test_sax() {
+            // Create a new processor
+            let mut processor = processor_with_illegal(vec![
+                SAX_ZP, 0x10, // SAX $10
+            ]);
+
+            // Set state of processor
+            processor.set_register().acc = 0b1100_1100;
+            processor.set_register().x = 0b1010_1010;
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check memory
+            assert_eq!(processor.get_mem(0x0010), 0b1000_1000);
+        }
+
+        #[test]
+        // DCP - Decrement Memory and Compare
+        fn test_dcp() {
+            // Create a new processor
+            let mut processor = processor_with_illegal(vec![
+                DCP_ZP, 0x10, // DCP $10 ; Zero, carry
+            ]);
+
+            // Set state of processor
+            processor.set_register().acc = 0x42;
+            processor.set_mem(0x0010, 0x43);
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let value = processor.get_mem(0x0010);
+            let (zero, negative, carry, ..) = get_status(&processor);
+            assert_eq!(value, 0x42);
+            assert!(zero);
+            assert!(!negative);
+            assert!(carry);
+        }
+
+        #[test]
+        // ISC - Increment Memory and Subtract with Carry
+        fn test_isc() {
+            // Create a new processor
+            let mut processor = processor_with_illegal(vec![
+                ISC_ZP, 0x10, // ISC $10
+            ]);
+
+            // Set state of processor
+            processor.set_register().acc = 0x50;
+            processor.set_register().status.insert(Status::CARRY);
+            processor.set_mem(0x0010, 0x1F);
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let acc = processor.get_registers().acc;
+            let value = processor.get_mem(0x0010);
+            let (zero, negative, carry, ..) = get_status(&processor);
+            // 0x50 - 0x20 = 0x30
+            assert_eq!(value, 0x20);
+            assert_eq!(acc, 0x30);
+            assert!(!zero);
+            assert!(!negative);
+            assert!(carry);
+        }
+
+        #[test]
+        // SLO - Arithmetic Shift Left Memory and Logical ORA
+        fn test_slo() {
+            // Create a new processor
+            let mut processor = processor_with_illegal(vec![
+                SLO_ZP, 0x10, // SLO $10
+            ]);
+
+            // Set state of processor
+            processor.set_register().acc = 0x01;
+            processor.set_mem(0x0010, 0xA0);
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let acc = processor.get_registers().acc;
+            let value = processor.get_mem(0x0010);
+            let (zero, negative, carry, ..) = get_status(&processor);
+            assert_eq!(value, 0x40);
+            assert_eq!(acc, 0x41);
+            assert!(!zero);
+            assert!(!negative);
+            assert!(carry);
+        }
+
+        #[test]
+        // RLA - Rotate Left Memory and Logical AND
+        fn test_rla() {
+            // Create a new processor
+            let mut processor = processor_with_illegal(vec![
+                RLA_ZP, 0x10, // RLA $10
+            ]);
+
+            // Set state of processor
+            processor.set_register().acc = 0x0F;
+            processor.set_register().status.insert(Status::CARRY);
+            processor.set_mem(0x0010, 0x81);
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let acc = processor.get_registers().acc;
+            let value = processor.get_mem(0x0010);
+            let (zero, negative, carry, ..) = get_status(&processor);
+            assert_eq!(value, 0x03);
+            assert_eq!(acc, 0x03);
+            assert!(!zero);
+            assert!(!negative);
+            assert!(carry);
+        }
+
+        #[test]
+        // SRE - Logical Shift Right Memory and Logical EOR
+        fn test_sre() {
+            // Create a new processor
+            let mut processor = processor_with_illegal(vec![
+                SRE_ZP, 0x10, // SRE $10
+            ]);
+
+            // Set state of processor
+            processor.set_register().acc = 0xFF;
+            processor.set_mem(0x0010, 0x03);
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let acc = processor.get_registers().acc;
+            let value = processor.get_mem(0x0010);
+            let (zero, negative, carry, ..) = get_status(&processor);
+            assert_eq!(value, 0x01);
+            assert_eq!(acc, 0xFE);
+            assert!(!zero);
+            assert!(negative);
+            assert!(carry);
+        }
+
+        #[test]
+        // RRA - Rotate Right Memory and Add with Carry
+        fn test_rra() {
+            // Create a new processor
+            let mut processor = processor_with_illegal(vec![
+                RRA_ZP, 0x10, // RRA $10
+            ]);
+
+            // Set state of processor
+            processor.set_register().acc = 0x10;
+            processor.set_mem(0x0010, 0x05);
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let acc = processor.get_registers().acc;
+            let value = processor.get_mem(0x0010);
+            let (zero, negative, carry, ..) = get_status(&processor);
+            // The carry out of the rotate is added: 0x10 + 0x02 + 1 = 0x13
+            assert_eq!(value, 0x02);
+            assert_eq!(acc, 0x13);
+            assert!(!zero);
+            assert!(!negative);
+            assert!(!carry);
+        }
+
+        #[test]
+        // ANC - Logical AND and Copy Bit 7 to Carry
+        fn test_anc() {
+            // Create a new processor
+            let mut processor = processor_with_illegal(vec![
+                ANC_IM_0B, 0xF0, // ANC #$F0
+            ]);
+
+            // Set state of processor
+            processor.set_register().acc = 0x81;
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let acc = processor.get_registers().acc;
+            let (zero, negative, carry, ..) = get_status(&processor);
+            assert_eq!(acc, 0x80);
+            assert!(!zero);
+            assert!(negative);
+            assert!(carry);
+        }
+
+        #[test]
+        // ALR - Logical AND and Logical Shift Right
+        fn test_alr() {
+            // Create a new processor
+            let mut processor = processor_with_illegal(vec![
+                ALR_IM, 0x0F, // ALR #$0F
+            ]);
+
+            // Set state of processor
+            processor.set_register().acc = 0xFF;
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let acc = processor.get_registers().acc;
+            let (zero, negative, carry, ..) = get_status(&processor);
+            assert_eq!(acc, 0x07);
+            assert!(!zero);
+            assert!(!negative);
+            assert!(carry);
+        }
+
+        #[test]
+        // ARR - Logical AND and Rotate Right
+        fn test_arr() {
+            // Create a new processor
+            let mut processor = processor_with_illegal(vec![
+                ARR_IM, 0xC0, // ARR #$C0 ; Binary
+                ARR_IM, 0xFF, // ARR #$FF ; Decimal
+            ]);
+
+            // Set state of processor
+            processor.set_register().acc = 0xFF;
+            processor.set_register().status.insert(Status::CARRY);
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let acc = processor.get_registers().acc;
+            let (zero, negative, carry, overflow, ..) = get_status(&processor);
+            // C is bit 6 and V is bit 6 XOR bit 5 of the result
+            assert_eq!(acc, 0xE0);
+            assert!(!zero);
+            assert!(negative);
+            assert!(carry);
+            assert!(!overflow);
+
+            // Set state of processor
+            processor.set_register().acc = 0x65;
+            processor.set_register().status.remove(Status::CARRY);
+            processor.set_register().status.insert(Status::DECIMAL);
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let acc = processor.get_registers().acc;
+            let (zero, negative, carry, overflow, ..) = get_status(&processor);
+            // 0x65 >> 1 = 0x32, both digits are fixed up: 0x32 -> 0x38 -> 0x98
+            assert_eq!(acc, 0x98);
+            assert!(!zero);
+            assert!(!negative);
+            assert!(carry);
+            assert!(overflow);
+        }
+
+        #[test]
+        // SBX - Accumulator AND X Register minus Value into X Register
+        fn test_sbx() {
+            // Create a new processor
+            let mut processor = processor_with_illegal(vec![
+                SBX_IM, 0x02, // SBX #$02
+            ]);
+
+            // Set state of processor
+            processor.set_register().acc = 0x0F;
+            processor.set_register().x = 0xFC;
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let x = processor.get_registers().x;
+            let (zero, negative, carry, ..) = get_status(&processor);
+            // (0x0F & 0xFC) - 0x02 = 0x0A
+            assert_eq!(x, 0x0A);
+            assert!(!zero);
+            assert!(!negative);
+            assert!(carry);
+        }
+
+        #[test]
+        // JAM - Halt the processor until reset
+        fn test_jam() {
+            // Create a new processor
+            let mut processor = Processor::new(vec![
+                JAM_02, // JAM
+            ]);
+
+            // Set state of processor
+            processor.set_register().status.remove(Status::INTERRUPT);
+            processor.set_mem(0xFFFA, 0x00);
+            processor.set_mem(0xFFFB, 0x90);
+            processor.set_mem(0xFFFE, 0x00);
+            processor.set_mem(0xFFFF, 0xA0);
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let pc = processor.get_registers().pc;
+            assert!(processor.is_halted());
+            assert_eq!(pc, 0x0800);
+
+            // Interrupts do not wake up the processor
+            processor.set_irq(true);
+            processor.set_nmi(true);
+            assert_eq!(processor.step(), Err(Error::Halted));
+
+            // Check processor state
+            let pc = processor.get_registers().pc;
+            assert!(processor.is_halted());
+            assert_eq!(pc, 0x0800);
+
+            // Reset processor
+            processor.set_irq(false);
+            processor.set_mem(0x0800, NOP);
+            processor.reset();
+
+            // Check processor state
+            assert!(!processor.is_halted());
+        }
+
+        #[test]
+        // JAM - Stops run without panicking
+        fn test_jam_run() {
+            // Create a new processor
+            let mut processor = Processor::new(vec![
+                NOP,    // NOP
+                JAM_F2, // JAM
+            ]);
+
+            // Run processor
+            processor.run().unwrap();
+
+            // Check processor state
+            let pc = processor.get_registers().pc;
+            assert!(processor.is_halted());
+            assert_eq!(pc, 0x0801);
+        }
+
+        #[test]
+        // NOP - Undocumented No Operation, skips its operands
+        fn test_nop_illegal() {
+            // Create a new processor
+            let mut processor = processor_with_illegal(vec![
+                NOP_1A, // NOP
+                NOP_IM_80,
+                0x42, // NOP #$42
+                NOP_ZP_04,
+                0x42, // NOP $42
+                NOP_ZPX_14,
+                0x42, // NOP $42,X
+                NOP_ABS_0C,
+                0x34,
+                0x12, // NOP $1234
+                NOP_ABSX_1C,
+                0x34,
+                0x12, // NOP $1234,X
+            ]);
+
+            // Execute instructions and check the program counter
+            for pc in [0x0801, 0x0803, 0x0805, 0x0807, 0x080A, 0x080D] {
+                processor.step().unwrap();
+                assert_eq!(processor.get_registers().pc, pc);
+            }
+        }
+    }
+
+    // CMOS opcodes
+    mod cmos {
+        use super::*;
+        use crate::{
+            error::Error,
+            opcodes::{
+                cmos::{
+                    BBR0, BBS7, BIT_ABSX, BIT_IM, BIT_ZPX, BRA, DEC_ACC, INC_ACC, JMP_INDX,
+                    LDA_ZPI, PHX, PHY, PLX, PLY, RMB3, SMB6, STA_ZPI, STP, STZ_ABS, TRB_ZP, TSB_ZP,
+                    WAI,
+                },
+                illegal::{JAM_02, NOP_ABSX_DC, SLO_INDX},
+                BRK, JMP_IND, NOP, SED,
+            },
+            processor::{Branch, Processor, Variant},
+        };
+
+        // Create a 65C02 processor
+        fn processor_65c02(program: Vec<u8>) -> Processor {
+            Processor::new(program).with_variant(Variant::Wdc65C02)
+        }
+
+        #[test]
+        // LDA/STA - Load and Store Accumulator (zero page)
+        fn test_lda_sta_zero_page_indirect() {
+            // Create a new processor
+            let mut processor = processor_65c02(vec![
+                LDA_ZPI, 0x10, // LDA ($10)
+                STA_ZPI, 0x12, // STA ($12)
+            ]);
+
+            // Set state of processor
+            processor.set_mem(0x0010, 0x34);
+            processor.set_mem(0x0011, 0x12);
+            processor.set_mem(0x0012, 0x00);
+            processor.set_mem(0x0013, 0x20);
+            processor.set_mem(0x1234, 0x80);
+
+            // Execute instructions
+            processor.step().unwrap();
+            processor.step().unwrap();
+
+            // Check processor state
+            let acc = processor.get_registers().acc;
+            let (zero, negative, ..) = get_status(&processor);
+            assert_eq!(acc, 0x80);
+            assert!(!zero);
+            assert!(negative);
+            assert_eq!(processor.get_mem(0x2000), 0x80);
+        }
+
+        #[test]
+        // STZ - Store Zero
+        fn test_stz() {
+            // Create a new processor
+            let mut processor = processor_65c02(vec![
+                STZ_ABS, 0x34, 0x12, // STZ $1234
+            ]);
+
+            // Set state of processor
+            processor.set_mem(0x1234, 0x42);
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check memory
+            assert_eq!(processor.get_mem(0x1234), 0x00);
+        }
+
+        #[test]
+        // PHX/PHY/PLX/PLY - Push and Pull X and Y Registers
+        fn test_phx_phy_plx_ply() {
+            // Create a new processor
+            let mut processor = processor_65c02(vec![
+                PHX, // PHX
+                PHY, // PHY
+                PLX, // PLX
+                PLY, // PLY
+            ]);
+
+            // Set state of processor
+            processor.set_register().x = 0x42;
+            processor.set_register().y = 0x80;
+
+            // Execute instructions
+            processor.step().unwrap();
+            processor.step().unwrap();
+
+            // Check stack
+            let sp = processor.get_registers().sp;
+            assert_eq!(sp, 0xFB);
+            assert_eq!(processor.get_mem(0x01FD), 0x42);
+            assert_eq!(processor.get_mem(0x01FC), 0x80);
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let x = processor.get_registers().x;
+            let (zero, negative, ..) = get_status(&processor);
+            assert_eq!(x, 0x80);
+            assert!(!zero);
+            assert!(negative);
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let y = processor.get_registers().y;
+            let sp = processor.get_registers().sp;
+            let (zero, negative, ..) = get_status(&processor);
+            assert_eq!(y, 0x42);
+            assert_eq!(sp, 0xFD);
+            assert!(!zero);
+            assert!(!negative);
+        }
+
+        #[test]
+        // BIT - Bit Test immediate, only affects the zero flag
+        fn test_bit_immediate() {
+            // Create a new processor
+            let mut processor = processor_65c02(vec![
+                BIT_IM, 0xC0, // BIT #$C0
+            ]);
+
+            // Set state of processor
+            processor.set_register().acc = 0x3F;
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let (zero, negative, _carry, overflow, ..) = get_status(&processor);
+            assert!(zero);
+            assert!(!negative);
+            assert!(!overflow);
+        }
+
+        #[test]
+        // BIT - Indexed modes take negative and overflow from the operand
+        fn test_bit_indexed() {
+            // Create a new processor
+            let mut processor = processor_65c02(vec![
+                BIT_ZPX, 0x10, // BIT $10,X
+                BIT_ABSX, 0x00, 0x20, // BIT $2000,X
+            ]);
+
+            // Set state of processor
+            processor.set_register().acc = 0x3F;
+            processor.set_register().x = 0x01;
+            processor.set_mem(0x0011, 0xC0);
+            processor.set_mem(0x2001, 0xC1);
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let (zero, negative, _carry, overflow, ..) = get_status(&processor);
+            assert!(zero);
+            assert!(negative);
+            assert!(overflow);
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let (zero, negative, _carry, overflow, ..) = get_status(&processor);
+            assert!(!zero);
+            assert!(negative);
+            assert!(overflow);
+        }
+
+        #[test]
+        // TRB - Test and Reset Bits
+        fn test_trb() {
+            // Create a new processor
+            let mut processor = processor_65c02(vec![
+                TRB_ZP, 0x10, // TRB $10
+            ]);
+
+            // Set state of processor
+            processor.set_register().acc = 0x0F;
+            processor.set_mem(0x0010, 0x3C);
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let (zero, ..) = get_status(&processor);
+            assert_eq!(processor.get_mem(0x0010), 0x30);
+            assert!(!zero);
+        }
+
+        #[test]
+        // TSB - Test and Set Bits
+        fn test_tsb() {
+            // Create a new processor
+            let mut processor = processor_65c02(vec![
+                TSB_ZP, 0x10, // TSB $10
+            ]);
+
+            // Set state of processor
+            processor.set_register().acc = 0x0F;
+            processor.set_mem(0x0010, 0x30);
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let (zero, ..) = get_status(&processor);
+            assert_eq!(processor.get_mem(0x0010), 0x3F);
+            assert!(zero);
+        }
+
+        #[test]
+        // INC/DEC - Increment and Decrement Accumulator
+        fn test_inc_dec_accumulator() {
+            // Create a new processor
+            let mut processor = processor_65c02(vec![
+                INC_ACC, // INC A ; Zero
+                DEC_ACC, // DEC A ; Negative
+            ]);
+
+            // Set state of processor
+            processor.set_register().acc = 0xFF;
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let acc = processor.get_registers().acc;
+            let (zero, negative, ..) = get_status(&processor);
+            assert_eq!(acc, 0x00);
+            assert!(zero);
+            assert!(!negative);
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let acc = processor.get_registers().acc;
+            let (zero, negative, ..) = get_status(&processor);
+            assert_eq!(acc, 0xFF);
+            assert!(!zero);
+            assert!(negative);
+        }
+
+        #[test]
+        // JMP - Jump indexed indirect
+        fn test_jmp_indexed_indirect() {
+            // Create a new processor
+            let mut processor = processor_65c02(vec![
+                JMP_INDX, 0xFE, 0x02, // JMP ($02FE,X)
+            ]);
+
+            // Set state of processor
+            processor.set_register().x = 0x01;
+            processor.set_mem(0x02FF, 0x34);
+            processor.set_mem(0x0300, 0x12);
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let pc = processor.get_registers().pc;
+            assert_eq!(pc, 0x1234);
+        }
+
+        #[test]
+        // JMP - Jump indirect without the NMOS page boundary bug
+        fn test_jmp_indirect_fixed() {
+            // Create a new processor
+            let mut processor = processor_65c02(vec![
+                JMP_IND, 0xFF, 0x02, // JMP ($02FF)
+            ]);
+
+            // Set state of processor
+            processor.set_mem(0x02FF, 0x34);
+            processor.set_mem(0x0300, 0x12);
+            processor.set_mem(0x0200, 0x56);
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let pc = processor.get_registers().pc;
+            assert_eq!(pc, 0x1234);
+        }
+
+        #[test]
+        // BRA - Branch Always
+        fn test_bra() {
+            // Create a new processor
+            let mut processor = processor_65c02(vec![
+                BRA, 0x10, // BRA $0812
+            ]);
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let pc = processor.get_registers().pc;
+            assert_eq!(pc, 0x0812);
+        }
+
+        #[test]
+        // BRK - Clears decimal mode on the 65C02
+        fn test_brk_clears_decimal() {
+            // Create a new processor
+            let mut processor = processor_65c02(vec![
+                SED, // SED
+                BRK, 0x00, // BRK
+            ]);
+
+            // Execute instructions
+            processor.step().unwrap();
+            processor.step().unwrap();
+
+            // Check processor state
+            let (_zero, _negative, _carry, _overflow, decimal, ..) = get_status(&processor);
+            assert!(!decimal);
+            assert_eq!(processor.get_mem(0x01FB) & 0b0000_1000, 0b0000_1000);
+        }
+
+        #[test]
+        // RMB/SMB - Reset and Set Memory Bit
+        fn test_rmb_smb() {
+            // Create a new processor
+            let mut processor = processor_65c02(vec![
+                RMB3, 0x10, // RMB3 $10
+                SMB6, 0x10, // SMB6 $10
+            ]);
+
+            // Set state of processor
+            processor.set_mem(0x0010, 0xFF);
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check memory
+            assert_eq!(processor.get_mem(0x0010), 0b1111_0111);
+
+            // Execute instruction
+            processor.set_mem(0x0010, 0x00);
+            processor.step().unwrap();
+
+            // Check memory and program counter
+            let pc = processor.get_registers().pc;
+            assert_eq!(processor.get_mem(0x0010), 0b0100_0000);
+            assert_eq!(pc, 0x0804);
+        }
+
+        #[test]
+        // BBR/BBS - Branch on Bit Reset and Set
+        fn test_bbr_bbs() {
+            // Create a new processor
+            let mut processor = processor_65c02(vec![
+                BBR0, 0x10, 0x10, // BBR0 $10,$0813 ; Not taken
+                BBS7, 0x10, 0x10, // BBS7 $10,$0816 ; Taken
+            ]);
+
+            // Set state of processor
+            processor.set_mem(0x0010, 0x81);
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let pc = processor.get_registers().pc;
+            assert_eq!(pc, 0x0803);
+            assert_eq!(processor.last_branch(), Some(Branch::NotTaken));
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let pc = processor.get_registers().pc;
+            assert_eq!(pc, 0x0816);
+            assert_eq!(processor.last_branch(), Some(Branch::Taken));
+        }
+
+        #[test]
+        // WAI - Wait for Interrupt, woken up by an IRQ
+        fn test_wai_irq() {
+            // Create a new processor
+            let mut processor = processor_65c02(vec![
+                WAI, // WAI
+                NOP, // NOP
+            ]);
+
+            // Set state of processor
+            processor.set_register().status.remove(Status::INTERRUPT);
+            processor.set_mem(0xFFFE, 0x00); // IRQ -> $A000
+            processor.set_mem(0xFFFF, 0xA0);
+
+            // Execute instruction
+            processor.run().unwrap();
+
+            // Check processor state, nothing happens until the IRQ
+            assert!(processor.is_waiting());
+            processor.step().unwrap();
+            let pc = processor.get_registers().pc;
+            assert_eq!(pc, 0x0801);
+
+            // Service interrupt
+            processor.set_irq(true);
+            processor.step().unwrap();
+
+            // Check processor state
+            let pc = processor.get_registers().pc;
+            assert!(!processor.is_waiting());
+            assert_eq!(pc, 0xA000);
+            assert_eq!(processor.get_mem(0x01FD), 0x08);
+            assert_eq!(processor.get_mem(0x01FC), 0x01);
+        }
+
+        #[test]
+        // WAI - Wait for Interrupt, a masked IRQ continues after the WAI
+        fn test_wai_masked_irq() {
+            // Create a new processor
+            let mut processor = processor_65c02(vec![
+                WAI, // WAI
+                NOP, // NOP
+            ]);
+
+            // Execute instruction
+            processor.step().unwrap();
+            assert!(processor.is_waiting());
+
+            // Wake up with the IRQ masked
+            processor.set_irq(true);
+            processor.step().unwrap();
+
+            // Check processor state
+            let pc = processor.get_registers().pc;
+            assert!(!processor.is_waiting());
+            assert_eq!(pc, 0x0802);
+        }
+
+        #[test]
+        // WAI - Wait for Interrupt, woken up by an NMI
+        fn test_wai_nmi() {
+            // Create a new processor
+            let mut processor = processor_65c02(vec![
+                WAI, // WAI
+            ]);
+
+            // Set state of processor
+            processor.set_mem(0xFFFA, 0x00); // NMI -> $9000
+            processor.set_mem(0xFFFB, 0x90);
+
+            // Execute instruction
+            processor.step().unwrap();
+            assert!(processor.is_waiting());
+
+            // Service interrupt
+            processor.set_nmi(true);
+            processor.step().unwrap();
+
+            // Check processor state
+            let pc = processor.get_registers().pc;
+            assert!(!processor.is_waiting());
+            assert_eq!(pc, 0x9000);
+        }
+
+        #[test]
+        // STP - Stop the processor until reset
+        fn test_stp() {
+            // Create a new processor
+            let mut processor = processor_65c02(vec![
+                STP, // STP
+                NOP, // NOP
+            ]);
+
+            // Execute instruction
+            processor.run().unwrap();
+
+            // Check processor state, interrupts do not wake it up
+            processor.set_nmi(true);
+            assert_eq!(processor.step(), Err(Error::Halted));
+            let pc = processor.get_registers().pc;
+            assert!(processor.is_halted());
+            assert_eq!(pc, 0x0801);
+
+            // Reset the processor
+            processor.reset();
+            assert!(!processor.is_halted());
+            let pc = processor.get_registers().pc;
+            assert_eq!(pc, 0x0800);
+        }
+
+        #[test]
+        // WAI/STP - No operations on the Rockwell 65C02
+        fn test_rockwell_wai_stp() {
+            // Create a new processor
+            let mut processor = Processor::new(vec![
+                WAI, // NOP
+                STP, // NOP
+                RMB3, 0x10, // RMB3 $10
+            ])
+            .with_variant(Variant::Rockwell65C02);
+
+            // Set state of processor
+            processor.set_mem(0x0010, 0xFF);
+
+            // Execute instructions
+            for _ in 0..3 {
+                processor.step().unwrap();
+                assert!(!processor.is_waiting());
+                assert!(!processor.is_halted());
+            }
+
+            // Check processor state
+            let pc = processor.get_registers().pc;
+            assert_eq!(pc, 0x0804);
+            assert_eq!(processor.get_mem(0x0010), 0b1111_0111);
+        }
+
+        #[test]
+        // Unused opcodes are no operations that skip their operands
+        fn test_unused_nops() {
+            // Create a new processor
+            let mut processor = processor_65c02(vec![
+                JAM_02,
+                0x42,     // NOP #$42
+                SLO_INDX, // NOP
+                NOP_ABSX_DC,
+                0x34,
+                0x12, // NOP $1234
+            ]);
+
+            // Execute instructions and check the program counter
+            for pc in [0x0802, 0x0803, 0x0806] {
+                processor.step().unwrap();
+                assert_eq!(processor.get_registers().pc, pc);
+                assert!(!processor.is_halted());
+            }
+        }
+    }
+    // Cycle counting
+    mod cycles {
+        use crate::{
+            opcodes::{
+                cmos::BRA, ADC_IM, ASL_ABSX, BEQ, BNE, JMP_IND, LDA_ABS, LDA_ABSX, LDA_IM,
+                LDA_INDY, NOP, SED, STA_ABSX,
+            },
+            processor::{Processor, Variant},
+            registers::Status,
+        };
+
+        #[test]
+        // Reset and instructions add to the running total
+        fn test_cycles() {
+            // Create a new processor
+            let mut processor = Processor::new(vec![
+                LDA_IM, 0x42, // LDA #$42
+                LDA_ABS, 0x00, 0x20, // LDA $2000
+                NOP,  // NOP
+            ]);
+
+            // Check the reset sequence
+            assert_eq!(processor.cycles(), 7);
+
+            // Execute instructions
+            assert_eq!(processor.step().unwrap(), 2);
+            assert_eq!(processor.step().unwrap(), 4);
+            assert_eq!(processor.step().unwrap(), 2);
+
+            // Check the running total
+            assert_eq!(processor.cycles(), 15);
+        }
+
+        #[test]
+        // Indexed reads take an extra cycle when crossing a page, indexed writes always take it
+        fn test_page_crossing() {
+            // Create a new processor
+            let mut processor = Processor::new(vec![
+                LDA_ABSX, 0x00, 0x20, // LDA $2000,X
+                LDA_ABSX, 0xFF, 0x20, // LDA $20FF,X ; Page crossed
+                LDA_INDY, 0x10, // LDA ($10),Y ; Page crossed
+                STA_ABSX, 0x00, 0x20, // STA $2000,X
+            ]);
+
+            // Set state of processor
+            processor.set_register().x = 0x01;
+            processor.set_register().y = 0x01;
+            processor.set_mem(0x0010, 0xFF);
+            processor.set_mem(0x0011, 0x20);
+
+            // Execute instructions
+            assert_eq!(processor.step().unwrap(), 4);
+            assert_eq!(processor.step().unwrap(), 5);
+            assert_eq!(processor.step().unwrap(), 6);
+            assert_eq!(processor.step().unwrap(), 5);
+        }
+
+        #[test]
+        // Branches take an extra cycle when taken and another one when crossing a page
+        fn test_branches() {
+            // Create a new processor
+            let mut processor = Processor::new(vec![
+                BNE, 0x00, // BNE $0802 ; Not taken
+                BEQ, 0x00, // BEQ $0804 ; Taken
+                BEQ, 0x80, // BEQ $0786 ; Page crossed
+            ]);
+
+            // Set state of processor
+            processor.set_register().status.insert(Status::ZERO);
+
+            // Execute instructions
+            assert_eq!(processor.step().unwrap(), 2);
+            assert_eq!(processor.step().unwrap(), 3);
+            assert_eq!(processor.step().unwrap(), 4);
+        }
+
+        #[test]
+        // Servicing an interrupt takes 7 cycles
+        fn test_interrupts() {
+            // Create a new processor
+            let mut processor = Processor::new(vec![
+                NOP, // NOP
+            ]);
+
+            // Set state of processor
+            processor.set_nmi(true);
+
+            // Service interrupt
+            assert_eq!(processor.step().unwrap(), 7);
+        }
+
+        #[test]
+        // The 65C02 differs in a few timings
+        fn test_cmos_cycles() {
+            let program = vec![
+                SED, // SED
+                ADC_IM, 0x01, // ADC #$01 ; Decimal mode
+                ASL_ABSX, 0x00, 0x20, // ASL $2000,X
+                ASL_ABSX, 0xFF, 0x20, // ASL $20FF,X ; Page crossed
+                JMP_IND, 0x00, 0x30, // JMP ($3000)
+            ];
+
+            // Create an NMOS and a CMOS processor
+            let mut nmos = Processor::new(program.clone());
+            let mut cmos = Processor::new(program).with_variant(Variant::Wdc65C02);
+            nmos.set_register().x = 0x01;
+            cmos.set_register().x = 0x01;
+
+            // Execute instructions
+            let nmos_cycles: Vec<u8> = (0..5).map(|_| nmos.step().unwrap()).collect();
+            let cmos_cycles: Vec<u8> = (0..5).map(|_| cmos.step().unwrap()).collect();
+
+            // Check the cycles
+            assert_eq!(nmos_cycles, vec![2, 2, 7, 7, 5]);
+            assert_eq!(cmos_cycles, vec![2, 3, 6, 7, 6]);
+        }
+
+        #[test]
+        // BRA - Always takes the taken branch cycle
+        fn test_bra() {
+            // Create a new processor
+            let mut processor = Processor::new(vec![
+                BRA, 0x00, // BRA $0802
+            ])
+            .with_variant(Variant::Wdc65C02);
+
+            // Execute instruction
+            assert_eq!(processor.step().unwrap(), 3);
+        }
+    }
+
+    // Cycle-stepped bus
+    mod bus {
+        use crate::{
+            device::Device,
+            opcodes::{INC_ABS, LDA_ABS, LDA_ABSX, NOP, STA_ABSX},
+            processor::{Processor, Variant},
+            registers::Status,
+        };
+        use std::{cell::RefCell, rc::Rc};
+
+        // Access made on the bus
+        #[derive(Debug, PartialEq)]
+        enum Access {
+            Read(u32),
+            Write(u32, u8),
+        }
+
+        // Device that records every access made to it
+        struct Probe {
+            accesses: Rc<RefCell<Vec<Access>>>,
+        }
+
+        impl Device for Probe {
+            fn read_type(&self) -> String {
+                "Probe".to_string()
+            }
+
+            fn read(&self, address: u32) -> u8 {
+                self.accesses.borrow_mut().push(Access::Read(address));
+                0x41
+            }
+
+            fn write(&mut self, address: u32, data: u8) {
+                self.accesses
+                    .borrow_mut()
+                    .push(Access::Write(address, data));
+            }
+        }
+
+        // Create a processor with a probe mapped to $4000-$41FF
+        fn probed(program: Vec<u8>, variant: Variant) -> (Processor, Rc<RefCell<Vec<Access>>>) {
+            let accesses = Rc::new(RefCell::new(Vec::new()));
+            let mut processor = Processor::new(program).with_variant(variant);
+            processor
+                .map(
+                    0x4000,
+                    0x41FF,
+                    Box::new(Probe {
+                        accesses: accesses.clone(),
+                    }),
+                )
+                .unwrap();
+            (processor, accesses)
+        }
+
+        #[test]
+        // Read-modify-write instructions write the unmodified value back on the NMOS chips
+        fn test_modify_write() {
+            let program = vec![
+                INC_ABS, 0x10, 0x40, // INC $4010
+            ];
+
+            // Create an NMOS and a CMOS processor
+            let (mut nmos, nmos_accesses) = probed(program.clone(), Variant::Nmos6502);
+            let (mut cmos, cmos_accesses) = probed(program, Variant::Wdc65C02);
+
+            // Execute instruction
+            nmos.step().unwrap();
+            cmos.step().unwrap();
+
+            // Check bus accesses
+            assert_eq!(
+                *nmos_accesses.borrow(),
+                vec![
+                    Access::Read(0x0010),
+                    Access::Write(0x0010, 0x41),
+                    Access::Write(0x0010, 0x42),
+                ]
+            );
+            assert_eq!(
+                *cmos_accesses.borrow(),
+                vec![
+                    Access::Read(0x0010),
+                    Access::Read(0x0010),
+                    Access::Write(0x0010, 0x42),
+                ]
+            );
+        }
+
+        #[test]
+        // Indexing across a page reads the address before the high byte is fixed up
+        fn test_indexed_dummy_read() {
+            // Create a new processor
+            let (mut processor, accesses) = probed(
+                vec![
+                    LDA_ABSX, 0xFF, 0x40, // LDA $40FF,X ; Page crossed
+                    LDA_ABSX, 0x10, 0x40, // LDA $4010,X
+                    STA_ABSX, 0x10, 0x40, // STA $4010,X
+                ],
+                Variant::Nmos6502,
+            );
+
+            // Set state of processor
+            processor.set_register().x = 0x02;
+
+            // Execute instructions
+            processor.step().unwrap();
+            processor.step().unwrap();
+            processor.step().unwrap();
+
+            // Check bus accesses
+            assert_eq!(
+                *accesses.borrow(),
+                vec![
+                    Access::Read(0x0001),
+                    Access::Read(0x0101),
+                    Access::Read(0x0012),
+                    Access::Read(0x0012),
+                    Access::Write(0x0012, 0x41),
+                ]
+            );
+        }
+
+        #[test]
+        // Every cycle makes one access, at the same time as on the real chip
+        fn test_step_cycle() {
+            // Create a new processor
+            let (mut processor, accesses) = probed(
+                vec![
+                    INC_ABS, 0x10, 0x40, // INC $4010
+                    LDA_ABS, 0x00, 0x20, // LDA $2000
+                ],
+                Variant::Nmos6502,
+            );
+
+            // Opcode and operand fetches
+            assert!(!processor.step_cycle().unwrap());
+            assert!(!processor.step_cycle().unwrap());
+            assert!(!processor.step_cycle().unwrap());
+            assert!(accesses.borrow().is_empty());
+
+            // Read and writes
+            assert!(!processor.step_cycle().unwrap());
+            assert_eq!(*accesses.borrow(), vec![Access::Read(0x0010)]);
+            assert!(!processor.step_cycle().unwrap());
+            assert_eq!(accesses.borrow().len(), 2);
+            assert!(processor.step_cycle().unwrap());
+            assert_eq!(accesses.borrow().len(), 3);
+            assert_eq!(processor.cycles(), 13);
+
+            // Finish the next instruction with step
+            assert!(!processor.step_cycle().unwrap());
+            assert_eq!(processor.step().unwrap(), 4);
+            assert_eq!(processor.get_registers().pc, 0x0806);
+            assert_eq!(processor.cycles(), 17);
+        }
+
+        #[test]
+        // Interrupts are cycle-stepped like instructions
+        fn test_step_cycle_interrupt() {
+            // Create a new processor
+            let mut processor = Processor::new(vec![
+                NOP, // NOP
+            ]);
+
+            // Set state of processor
+            processor.set_mem(0xFFFA, 0x00);
+            processor.set_mem(0xFFFB, 0x30);
+            processor.set_nmi(true);
+
+            // Service interrupt
+            let cycles = (1..).find(|_| processor.step_cycle().unwrap()).unwrap();
+            assert_eq!(cycles, 7);
+            assert_eq!(processor.get_registers().pc, 0x3000);
+        }
+
+        #[test]
+        // Every opcode takes as many cycles with step_cycle as with step
+        fn test_cycle_accesses() {
+            // The opcodes the NMOS variants do not know
+            let unknown = [0x8B, 0x93, 0x9B, 0x9C, 0x9E, 0x9F, 0xAB, 0xBB];
+            let variants = [
+                Variant::Nmos6502,
+                Variant::Ricoh2A03,
+                Variant::Rockwell65C02,
+                Variant::Wdc65C02,
+            ];
+
+            for variant in variants {
+                let nmos = matches!(variant, Variant::Nmos6502 | Variant::Ricoh2A03);
+                for opcode in 0..=0xFF {
+                    if nmos && unknown.contains(&opcode) {
+                        continue;
+                    }
+
+                    // Go through page crossings, taken branches and decimal mode
+                    for (low, status) in [(0x00, 0x00), (0xFF, 0x00), (0x00, 0xFF), (0xFF, 0xFF)] {
+                        let create = || {
+                            let mut processor =
+                                Processor::new(vec![opcode, low, 0x20]).with_variant(variant);
+                            processor.set_illegal_opcodes(true);
+                            processor.set_register().x = 0x01;
+                            processor.set_register().y = 0x01;
+                            processor.set_register().status = Status::from_bits_truncate(status);
+                            processor.set_mem(0x00FF, 0xFF);
+                            processor
+                        };
+                        let mut stepped = create();
+                        let mut cycled = create();
+
+                        // The JAM opcodes take no cycles
+                        let cycles = stepped.step().unwrap() as u64;
+                        if cycles == 0 {
+                            continue;
+                        }
+                        let cycled_cycles = (1..).find(|_| cycled.step_cycle().unwrap()).unwrap();
+
+                        assert_eq!(
+                            cycled_cycles, cycles,
+                            "{:?} opcode {:#04X} low {:#04X} status {:#04X}",
+                            variant, opcode, low, status
+                        );
+                        assert_eq!(cycled.cycles(), stepped.cycles());
+                        assert_eq!(cycled.get_registers().pc, stepped.get_registers().pc);
+                        assert_eq!(cycled.get_registers().acc, stepped.get_registers().acc);
+                    }
+                }
+            }
+        }
+    }
+
+    // Errors of the emulation
+    mod errors {
+        use crate::{
+            device::Ram,
+            error::Error,
+            opcodes::{cmos::WAI, illegal::LAX_ZP, LDA_IM, STA_ABS},
+            processor::{Processor, Variant},
+        };
+
+        // Create a processor with RAM only in $0000-$0FFF and the given program at $0800
+        fn processor_with_hole(program: &[u8]) -> Processor {
+            let mut processor = Processor::new(vec![]);
+            processor.unmap(0x0000, 0xFFFF).unwrap();
+            processor
+                .map(0x0000, 0x0FFF, Box::new(Ram::new(0x1000)))
+                .unwrap();
+            for (i, byte) in program.iter().enumerate() {
+                processor.set_mem(0x0800 + i as u16, *byte);
+            }
+            processor
+        }
+
+        #[test]
+        // Run stops at an unknown opcode and reports where it is
+        fn test_run_unknown_opcode() {
+            // Create a new processor
+            let mut processor = Processor::new(vec![
+                LDA_IM, 0x42, // LDA #$42
+                LAX_ZP, 0x10, // LAX $10
+            ]);
+
+            // Run processor
+            let error = Error::UnknownOpcode {
+                opcode: LAX_ZP,
+                pc: 0x0802,
+            };
+            assert_eq!(processor.run(), Err(error));
+
+            // Check processor state
+            let pc = processor.get_registers().pc;
+            let acc = processor.get_registers().acc;
+            assert_eq!(pc, 0x0802);
+            assert_eq!(acc, 0x42);
+            assert_eq!(processor.cycles(), 9);
+        }
+
+        #[test]
+        // An access to an unmapped address fails the instruction
+        fn test_unmapped_access() {
+            // Create a new processor
+            let mut processor = processor_with_hole(&[
+                STA_ABS, 0x00, 0x40, // STA $4000
+            ]);
+
+            // Execute instruction
+            assert_eq!(processor.step(), Err(Error::UnmappedAccess(0x4000)));
+
+            // Check processor state
+            let pc = processor.get_registers().pc;
+            assert_eq!(pc, 0x0800);
+
+            // Execute instruction one cycle at a time
+            assert_eq!(processor.step_cycle(), Ok(false));
+            assert_eq!(processor.step_cycle(), Ok(false));
+            assert_eq!(processor.step_cycle(), Ok(false));
+            assert_eq!(processor.step_cycle(), Err(Error::UnmappedAccess(0x4000)));
+
+            // Check processor state
+            let pc = processor.get_registers().pc;
+            assert_eq!(pc, 0x0800);
+        }
+
+        #[test]
+        // Fetching from an unmapped address fails the instruction
+        fn test_unmapped_fetch() {
+            // Create a new processor
+            let mut processor = processor_with_hole(&[]);
+            processor.set_register().pc = 0x2000;
+
+            // Execute instruction
+            assert_eq!(processor.step(), Err(Error::UnmappedAccess(0x2000)));
+        }
+
+        #[test]
+        // A failed interrupt leaves the NMI pending and the processor waiting
+        fn test_failed_nmi() {
+            // Create a new processor
+            let mut processor = processor_with_hole(&[
+                WAI, // WAI
+            ])
+            .with_variant(Variant::Wdc65C02);
+
+            // Execute instruction
+            processor.step().unwrap();
+            assert!(processor.is_waiting());
+
+            // Service interrupt through an unmapped vector
+            processor.set_nmi(true);
+            assert_eq!(processor.step(), Err(Error::UnmappedAccess(0xFFFA)));
+
+            // Check processor state
+            let pc = processor.get_registers().pc;
+            let sp = processor.get_registers().sp;
+            assert!(processor.is_waiting());
+            assert_eq!(pc, 0x0801);
+            assert_eq!(sp, 0xFD);
+
+            // Service interrupt one cycle at a time
+            let error = (0..7).find_map(|_| processor.step_cycle().err());
+            assert_eq!(error, Some(Error::UnmappedAccess(0xFFFA)));
+            assert!(processor.is_waiting());
+
+            // Map the vector and service interrupt
+            processor
+                .map(0xF000, 0xFFFF, Box::new(Ram::new(0x1000)))
+                .unwrap();
+            processor.write_memory(0xFFFA, 0x00).unwrap();
+            processor.write_memory(0xFFFB, 0x09).unwrap();
+            processor.step().unwrap();
+
+            // Check processor state
+            let pc = processor.get_registers().pc;
+            assert!(!processor.is_waiting());
+            assert_eq!(pc, 0x0900);
+        }
+
+        #[test]
+        // Regions that are empty or not mapped are rejected
+        fn test_bad_region() {
+            // Create a new processor
+            let mut processor = Processor::new(vec![]);
+
+            // Check mapping
+            let error = Error::BadRegion {
+                start: 0x4000,
+                end: 0x3FFF,
+            };
+            let result = processor.map(0x4000, 0x3FFF, Box::new(Ram::new(0x100)));
+            assert_eq!(result, Err(error));
+
+            // Check unmapping
+            let error = Error::BadRegion {
+                start: 0x4000,
+                end: 0x40FF,
+            };
+            assert_eq!(processor.unmap(0x4000, 0x40FF), Err(error));
+        }
+
+        #[test]
+        // Errors describe what went wrong
+        fn test_display() {
+            let error = Error::UnknownOpcode {
+                opcode: 0x02,
+                pc: 0x0800,
+            };
+            assert_eq!(error.to_string(), "Unknown opcode 0x02 at 0x0800");
+            let error = Error::UnmappedAccess(0x4000);
+            assert_eq!(error.to_string(), "Unmapped access at 0x4000");
+            assert_eq!(Error::Halted.to_string(), "Processor is halted");
+        }
+    }
+
+    // Runs that stop on a budget, a breakpoint or a condition
+    mod run_control {
+        use crate::{
+            error::Error,
+            opcodes::{
+                cmos::{STP, WAI},
+                illegal::{JAM_02, LAX_ZP},
+                BNE, DEX, JMP_ABS, LDX_IM, NOP,
+            },
+            processor::{Branch, Processor, StopReason, Variant},
+        };
+
+        // Create a processor running an endless loop of a NOP and a JMP
+        fn processor_loop() -> Processor {
+            Processor::new(vec![
+                NOP, // NOP
+                JMP_ABS, 0x00, 0x08, // JMP $0800
+            ])
+        }
+
+        #[test]
+        // Run for a number of cycles, the last instruction is not split
+        fn test_run_for_cycles() {
+            // Create a new processor
+            let mut processor = processor_loop();
+
+            // Run processor
+            let reason = processor.run_for_cycles(10);
+
+            // Check processor state
+            let pc = processor.get_registers().pc;
+            assert_eq!(reason, StopReason::BudgetExhausted);
+            assert_eq!(processor.cycles(), 7 + 10);
+            assert_eq!(pc, 0x0800);
+
+            // Run processor past the budget
+            let reason = processor.run_for_cycles(1);
+
+            // Check processor state
+            assert_eq!(reason, StopReason::BudgetExhausted);
+            assert_eq!(processor.cycles(), 7 + 12);
+        }
+
+        #[test]
+        // Run for a number of instructions
+        fn test_run_for_instructions() {
+            // Create a new processor
+            let mut processor = processor_loop();
+
+            // Run processor
+            let reason = processor.run_for_instructions(3);
+
+            // Check processor state
+            let pc = processor.get_registers().pc;
+            assert_eq!(reason, StopReason::BudgetExhausted);
+            assert_eq!(processor.cycles(), 7 + 7);
+            assert_eq!(pc, 0x0801);
+
+            // Run processor for no instruction
+            assert_eq!(
+                processor.run_for_instructions(0),
+                StopReason::BudgetExhausted
+            );
+            let pc = processor.get_registers().pc;
+            assert_eq!(pc, 0x0801);
+        }
+
+        #[test]
+        // Run until the instruction at an address is about to run
+        fn test_run_until_pc() {
+            // Create a new processor
+            let mut processor = Processor::new(vec![
+                LDX_IM, 0x03, // LDX #$03
+                DEX,  // DEX
+                BNE, 0xFD, // BNE $0802
+                NOP,  // NOP
+            ]);
+
+            // Run processor
+            let reason = processor.run_until_pc(0x0805);
+
+            // Check processor state
+            let pc = processor.get_registers().pc;
+            let x = processor.get_registers().x;
+            assert_eq!(reason, StopReason::Breakpoint);
+            assert_eq!(pc, 0x0805);
+            assert_eq!(x, 0x00);
+
+            // Run processor, already at the breakpoint
+            assert_eq!(processor.run_until_pc(0x0805), StopReason::Breakpoint);
+            let pc = processor.get_registers().pc;
+            assert_eq!(pc, 0x0805);
+        }
+
+        #[test]
+        // Run until a condition holds
+        fn test_run_until() {
+            // Create a new processor
+            let mut processor = Processor::new(vec![
+                LDX_IM, 0x03, // LDX #$03
+                DEX,  // DEX
+                BNE, 0xFD, // BNE $0802
+                NOP,  // NOP
+            ]);
+
+            // Run processor
+            let mut taken = 0;
+            let reason = processor.run_until(|cpu| {
+                if cpu.last_branch() == Some(Branch::Taken) {
+                    taken += 1;
+                }
+                cpu.last_branch() == Some(Branch::NotTaken)
+            });
+
+            // Check processor state
+            let pc = processor.get_registers().pc;
+            assert_eq!(reason, StopReason::Breakpoint);
+            assert_eq!(pc, 0x0805);
+            assert_eq!(taken, 2);
+        }
+
+        #[test]
+        // Runs stop when the processor is halted or waiting
+        fn test_run_halted_waiting() {
+            // Create a new processor
+            let mut processor = Processor::new(vec![
+                NOP,    // NOP
+                JAM_02, // JAM
+            ]);
+
+            // Run processor
+            assert_eq!(processor.run_for_cycles(100), StopReason::Halted);
+            assert_eq!(processor.run_until_pc(0x0900), StopReason::Halted);
+
+            // Run processor without a budget
+            processor.reset();
+            assert_eq!(processor.run_for_cycles(u64::MAX), StopReason::Halted);
+            let pc = processor.get_registers().pc;
+            assert_eq!(pc, 0x0801);
+
+            // Create a new processor
+            let mut processor = Processor::new(vec![
+                WAI, // WAI
+                STP, // STP
+            ])
+            .with_variant(Variant::Wdc65C02);
+
+            // Run processor
+            assert_eq!(processor.run_for_instructions(100), StopReason::Waiting);
+
+            // Wake up processor and run it to the end
+            processor.set_irq(true);
+            assert_eq!(processor.run_for_instructions(100), StopReason::Halted);
+        }
+
+        #[test]
+        // Runs stop at the first instruction that fails
+        fn test_run_error() {
+            // Create a new processor
+            let mut processor = Processor::new(vec![
+                NOP, // NOP
+                LAX_ZP, 0x10, // LAX $10
+            ]);
+
+            // Run processor
+            let error = Error::UnknownOpcode {
+                opcode: LAX_ZP,
+                pc: 0x0801,
+            };
+            assert_eq!(processor.run_for_cycles(100), StopReason::Error(error));
+            let pc = processor.get_registers().pc;
+            assert_eq!(pc, 0x0801);
+        }
+    }
+
+    // Inspecting and changing the processor from the outside
+    mod state {
+        use crate::{
+            device::Ram,
+            error::Error,
+            opcodes::{ADC_IM, LDA_IM, NOP},
+            processor::{CpuState, Processor},
+            Status,
+        };
+
+        #[test]
+        // The state follows the registers
+        fn test_state() {
+            // Create a new processor
+            let mut processor = Processor::new(vec![
+                LDA_IM, 0x80, // LDA #$80
+            ]);
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let state = processor.state();
+            assert_eq!(state.acc, 0x80);
+            assert_eq!(state.pc, 0x0802);
+            assert_eq!(state.sp, 0xFD);
+            assert!(state.status.negative());
+            assert!(!state.status.zero());
+            assert!(state.status.interrupt());
+        }
+
+        #[test]
+        // Setting the state changes the registers for the next instruction
+        fn test_set_state() {
+            // Create a new processor
+            let mut processor = Processor::new(vec![
+                NOP, // NOP
+                ADC_IM, 0x01, // ADC #$01
+            ]);
+
+            // Set state of processor
+            let mut state = processor.state();
+            state.pc = 0x0801;
+            state.acc = 0x41;
+            state.status.set_carry(true);
+            processor.set_state(state);
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let state = processor.state();
+            assert_eq!(state.acc, 0x43);
+            assert_eq!(state.pc, 0x0803);
+            assert!(!state.status.carry());
+        }
+
+        #[test]
+        // Setting the state drops an instruction started by step_cycle
+        fn test_set_state_in_flight() {
+            // Create a new processor
+            let mut processor = Processor::new(vec![
+                LDA_IM, 0x42, // LDA #$42
+                NOP,  // NOP
+            ]);
+
+            // Start instruction and set state of processor
+            assert_eq!(processor.step_cycle(), Ok(false));
+            let mut state = processor.state();
+            state.pc = 0x0802;
+            processor.set_state(state);
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let state = processor.state();
+            assert_eq!(state.acc, 0x00);
+            assert_eq!(state.pc, 0x0803);
+        }
+
+        #[test]
+        // Flags by name
+        fn test_status_flags() {
+            let mut status = Status::empty();
+            status.set_negative(true);
+            status.set_overflow(true);
+            status.set_break_command(true);
+            status.set_decimal_mode(true);
+            status.set_interrupt(true);
+            status.set_zero(true);
+            status.set_carry(true);
+            assert_eq!(status, Status::all() - Status::UNUSED);
+
+            status.set_overflow(false);
+            status.set_zero(false);
+            assert!(status.negative());
+            assert!(!status.overflow());
+            assert!(status.break_command());
+            assert!(status.decimal_mode());
+            assert!(status.interrupt());
+            assert!(!status.zero());
+            assert!(status.carry());
+        }
+
+        #[test]
+        // The state is shown on one line
+        fn test_display() {
+            let state = CpuState {
+                pc: 0x0800,
+                sp: 0xFD,
+                acc: 0x42,
+                x: 0x00,
+                y: 0x00,
+                status: Status::UNUSED | Status::INTERRUPT,
+            };
+            assert_eq!(state.to_string(), "A=42 X=00 Y=00 SP=FD P=nv-bdIzc PC=0800");
+
+            let status = Status::NEGATIVE | Status::DECIMAL | Status::CARRY;
+            assert_eq!(status.to_string(), "Nv-bDizC");
+        }
+
+        #[test]
+        // Memory is read and written without running cycles
+        fn test_memory() {
+            // Create a new processor
+            let mut processor = Processor::new(vec![]);
+
+            // Write and read memory
+            processor.write_memory(0x1234, 0x42).unwrap();
+            assert_eq!(processor.read_memory(0x1234), Ok(0x42));
+            assert_eq!(processor.cycles(), 7);
+
+            // Unmapped memory
+            processor.unmap(0x0000, 0xFFFF).unwrap();
+            processor
+                .map(0x0000, 0x0FFF, Box::new(Ram::new(0x1000)))
+                .unwrap();
+            let error = Error::UnmappedAccess(0x1234);
+            assert_eq!(processor.read_memory(0x1234), Err(error));
+            assert_eq!(processor.write_memory(0x1234, 0x42), Err(error));
+        }
+    }
+
+    // Decode tables
+    mod decode {
+        use crate::{
+            decode::{
+                disassemble, AddressingMode::*, Instruction, Operation::*, NMOS_TABLE,
+                NMOS_UNDOCUMENTED_TABLE, ROCKWELL_TABLE, WDC_TABLE,
+            },
+            opcodes::{
+                cmos::{BBR3, SMB2, WAI},
+                illegal::{ANE_IM, LAS_ABSY, LAX_ZP, SHA_INDY, SHY_ABSX, USBC_IM},
+                BNE, LDA_ABSX, NOP, STA_ABSX,
+            },
+            processor::{Processor, Variant},
+            registers::Status,
+        };
+
+        // All variants of the processor
+        const VARIANTS: [Variant; 4] = [
+            Variant::Nmos6502,
+            Variant::Ricoh2A03,
+            Variant::Rockwell65C02,
+            Variant::Wdc65C02,
+        ];
+
+        #[test]
+        // Every variant decodes the NMOS opcodes the same way
+        fn test_documented() {
+            for table in [
+                &NMOS_TABLE,
+                &NMOS_UNDOCUMENTED_TABLE,
+                &ROCKWELL_TABLE,
+                &WDC_TABLE,
+            ] {
+                assert_eq!(table[LDA_ABSX as usize], (Lda, AbsoluteX));
+            }
+        }
+
+        #[test]
+        // The variants swap in their own opcodes
+        fn test_variants() {
+            // Undocumented opcodes are only decoded when enabled
+            assert_eq!(NMOS_TABLE[LAX_ZP as usize], (Unknown, Implied));
+            assert_eq!(NMOS_UNDOCUMENTED_TABLE[LAX_ZP as usize], (Lax, ZeroPage));
+
+            // The CMOS chips reuse the opcode
+            assert_eq!(ROCKWELL_TABLE[SMB2 as usize], (Smb(2), ZeroPage));
+
+            // WAI is only known by the WDC chips
+            assert_eq!(ROCKWELL_TABLE[WAI as usize], (NopSingle, Implied));
+            assert_eq!(WDC_TABLE[WAI as usize], (Wai, Implied));
+        }
+
+        #[test]
+        // Every opcode does something on the CMOS chips
+        fn test_cmos_complete() {
+            for table in [&ROCKWELL_TABLE, &WDC_TABLE] {
+                assert!(table.iter().all(|&(operation, _)| operation != Unknown));
+            }
+
+            // Only the unstable undocumented opcodes are unknown on the NMOS chips
+            let unknown = NMOS_UNDOCUMENTED_TABLE
+                .iter()
+                .filter(|&&(operation, _)| operation == Unknown)
+                .count();
+            assert_eq!(unknown, 8);
+        }
+
+        #[test]
+        // Look up the information of an opcode
+        fn test_instruction() {
+            // Documented opcode
+            let instruction = Instruction::decode(Variant::Nmos6502, LDA_ABSX);
+            assert_eq!(instruction.mnemonic, "LDA");
+            assert_eq!(instruction.mode, AbsoluteX);
+            assert_eq!(instruction.length, 3);
+            assert_eq!(instruction.cycles, 4);
+            assert!(instruction.page_cross_penalty);
+            assert!(instruction.documented);
+
+            // Writes always take the extra cycle
+            let instruction = Instruction::decode(Variant::Nmos6502, STA_ABSX);
+            assert_eq!(instruction.cycles, 5);
+            assert!(!instruction.page_cross_penalty);
+
+            // Undocumented opcodes
+            let instruction = Instruction::decode(Variant::Nmos6502, USBC_IM);
+            assert_eq!(instruction.mnemonic, "SBC");
+            assert!(!instruction.documented);
+            assert!(instruction.emulated);
+
+            // Unstable opcodes are described but not emulated
+            let instruction = Instruction::decode(Variant::Nmos6502, LAS_ABSY);
+            assert_eq!(instruction.mnemonic, "LAS");
+            assert_eq!(instruction.mode, AbsoluteY);
+            assert_eq!(instruction.length, 3);
+            assert_eq!(instruction.cycles, 4);
+            assert!(instruction.page_cross_penalty);
+            assert!(!instruction.documented);
+            assert!(!instruction.emulated);
+
+            // Opcodes of the variants
+            let instruction = Instruction::decode(Variant::Wdc65C02, BBR3);
+            assert_eq!(instruction.mnemonic, "BBR3");
+            assert_eq!(instruction.mode, ZeroPageRelative);
+            assert_eq!(instruction.length, 3);
+            let instruction = Instruction::decode(Variant::Rockwell65C02, WAI);
+            assert_eq!(instruction.mnemonic, "NOP");
+            assert_eq!(instruction.cycles, 1);
+            assert!(!instruction.documented);
+        }
+
+        #[test]
+        // Every byte is an instruction on every variant
+        fn test_instruction_complete() {
+            let unstable = [0x8B, 0x93, 0x9B, 0x9C, 0x9E, 0x9F, 0xAB, 0xBB];
+            for variant in VARIANTS {
+                let cmos = matches!(variant, Variant::Rockwell65C02 | Variant::Wdc65C02);
+                for opcode in 0..=0xFF {
+                    let instruction = Instruction::decode(variant, opcode);
+                    let message = format!("{:?} opcode {:#04X}", variant, opcode);
+                    assert_eq!(instruction.opcode, opcode, "{}", message);
+                    assert_ne!(instruction.mnemonic, "???", "{}", message);
+                    assert!((1..=3).contains(&instruction.length), "{}", message);
+                    if instruction.mnemonic != "JAM" {
+                        assert!(instruction.cycles > 0, "{}", message);
+                    }
+                    assert_eq!(
+                        instruction.emulated,
+                        cmos || !unstable.contains(&opcode),
+                        "{}",
+                        message
+                    );
+                    assert!(disassemble(variant, 0x0800, &[opcode, 0x00, 0x00]).is_some());
+                }
+            }
+
+            // The unstable opcodes
+            let variant = Variant::Nmos6502;
+            assert_eq!(
+                disassemble(variant, 0x0800, &[ANE_IM, 0x12]),
+                Some(("ANE #$12".to_string(), 2))
+            );
+            assert_eq!(
+                disassemble(variant, 0x0800, &[SHA_INDY, 0x12]),
+                Some(("SHA ($12),Y".to_string(), 2))
+            );
+            assert_eq!(
+                disassemble(variant, 0x0800, &[SHY_ABSX, 0x34, 0x12]),
+                Some(("SHY $1234,X".to_string(), 3))
+            );
+            let cycles: Vec<u8> = unstable
+                .iter()
+                .map(|&opcode| Instruction::decode(variant, opcode).cycles)
+                .collect();
+            assert_eq!(cycles, [2, 6, 5, 5, 5, 5, 2, 4]);
+        }
+
+        #[test]
+        // Disassemble single instructions
+        fn test_disassemble() {
+            let variant = Variant::Wdc65C02;
+            assert_eq!(
+                disassemble(variant, 0x0800, &[LDA_ABSX, 0x34, 0x12]),
+                Some(("LDA $1234,X".to_string(), 3))
+            );
+            assert_eq!(
+                disassemble(variant, 0x0800, &[BNE, 0xFE]),
+                Some(("BNE $0800".to_string(), 2))
+            );
+            assert_eq!(
+                disassemble(variant, 0x0800, &[BBR3, 0x12, 0x10]),
+                Some(("BBR3 $12,$0813".to_string(), 3))
+            );
+            assert_eq!(
+                disassemble(variant, 0x0800, &[NOP]),
+                Some(("NOP".to_string(), 1))
+            );
+
+            // The bytes end too early
+            assert_eq!(disassemble(variant, 0x0800, &[LDA_ABSX, 0x34]), None);
+            assert_eq!(disassemble(variant, 0x0800, &[]), None);
+        }
+
+        #[test]
+        // The processor advances by the length and takes the cycles of the instruction info
+        fn test_instructions_match_processor() {
+            for variant in VARIANTS {
+                for opcode in 0..=0xFF {
+                    let instruction = Instruction::decode(variant, opcode);
+
+                    // Skip the instructions that go somewhere else, halt or are not emulated
+                    let jumps = ["JMP", "JSR", "RTS", "RTI", "BRK", "JAM"];
+                    if !instruction.emulated
+                        || jumps.contains(&instruction.mnemonic)
+                        || matches!(instruction.mode, Relative | ZeroPageRelative)
+                    {
+                        continue;
+                    }
+
+                    // Once without and once with crossing a page
+                    for (operand, crossed) in [(0x00, false), (0xFF, true)] {
+                        // Create a new processor
+                        let mut processor =
+                            Processor::new(vec![opcode, operand, 0x20]).with_variant(variant);
+
+                        // Set state of processor
+                        processor.set_illegal_opcodes(true);
+                        processor.set_register().x = 0x01;
+                        processor.set_register().y = 0x01;
+                        processor.set_register().status = Status::empty();
+                        processor.set_mem(0x0000, 0x20);
+                        processor.set_mem(0x0001, 0x30);
+                        processor.set_mem(0x00FF, 0xFF);
+
+                        // Execute instruction
+                        let cycles = processor.step().unwrap();
+
+                        // Check processor state
+                        let penalty = crossed && instruction.page_cross_penalty;
+                        assert_eq!(
+                            cycles,
+                            instruction.cycles + penalty as u8,
+                            "{:?} opcode {:#04X}",
+                            variant,
+                            opcode
+                        );
+                        assert_eq!(
+                            processor.get_registers().pc,
+                            0x0800 + instruction.length as u16,
+                            "{:?} opcode {:#04X}",
+                            variant,
+                            opcode
+                        );
+                    }
+                }
+            }
+        }
+    }
+}
+
+mod processor65c816 {
+    use crate::{processor65c816::Processor65C816, registers::Status};
+
+    // Get the zero, negative and carry flags
+    fn get_flags(processor: &Processor65C816) -> (bool, bool, bool) {
+        let st = processor.get_registers().status;
+        (
+            st.contains(Status::ZERO),
+            st.contains(Status::NEGATIVE),
+            st.contains(Status::CARRY),
+        )
+    }
+
+    // Emulation mode and the switch to native mode
+    mod modes {
+        use super::*;
+        use crate::{
+            opcodes::{
+                w65c816::{REP, SEP, XBA, XCE},
+                CLC, LDA_IM, LDX_IM, NOP, SEC, TXS,
+            },
+            processor65c816::{INDEX, MEMORY},
+        };
+
+        #[test]
+        // Reset - Starts in emulation mode with 8-bit registers
+        fn test_reset() {
+            // Create a new processor
+            let processor = Processor65C816::new(vec![
+                NOP, // NOP
+            ]);
+
+            // Check processor state
+            let registers = processor.get_registers();
+            assert!(processor.is_emulation());
+            assert_eq!(registers.pc, 0x0800);
+            assert_eq!(registers.sp, 0x01FD);
+            assert_eq!(registers.pbr, 0x00);
+            assert_eq!(registers.dbr, 0x00);
+            assert_eq!(registers.dp, 0x0000);
+            assert!(registers.status.contains(MEMORY | INDEX));
+            assert!(registers.status.contains(Status::INTERRUPT));
+        }
+
+        #[test]
+        // XCE - Exchange Carry and Emulation flags
+        fn test_xce() {
+            // Create a new processor
+            let mut processor = Processor65C816::new(vec![
+                CLC, // CLC
+                XCE, // XCE ; Native mode
+                SEC, // SEC
+                XCE, // XCE ; Emulation mode
+            ]);
+
+            // Execute instructions
+            processor.step().unwrap();
+            processor.step().unwrap();
+
+            // Check processor state
+            let (_zero, _negative, carry) = get_flags(&processor);
+            assert!(!processor.is_emulation());
+            assert!(carry);
+
+            // Execute instructions
+            processor.set_register().sp = 0x1FF0;
+            processor.step().unwrap();
+            processor.step().unwrap();
+
+            // Check processor state, the stack is back in page 1
+            let (_zero, _negative, carry) = get_flags(&processor);
+            let sp = processor.get_registers().sp;
+            assert!(processor.is_emulation());
+            assert!(!carry);
+            assert_eq!(sp, 0x01F0);
+        }
+
+        #[test]
+        // REP/SEP - Select the register widths in native mode
+        fn test_register_widths() {
+            // Create a new processor
+            let mut processor = Processor65C816::new(vec![
+                CLC, // CLC
+                XCE, // XCE
+                REP, 0x30, // REP #$30 ; 16-bit registers
+                LDA_IM, 0x34, 0x12, // LDA #$1234
+                LDX_IM, 0x78, 0x56, // LDX #$5678
+                SEP, 0x30, // SEP #$30 ; 8-bit registers
+                LDA_IM, 0x80, // LDA #$80
+            ]);
+
+            // Execute instructions
+            for _ in 0..5 {
+                processor.step().unwrap();
+            }
+
+            // Check processor state
+            let registers = processor.get_registers();
+            assert_eq!(registers.acc, 0x1234);
+            assert_eq!(registers.x, 0x5678);
+            assert_eq!(registers.pc, 0x080A);
+
+            // Execute instructions
+            processor.step().unwrap();
+            processor.step().unwrap();
+
+            // Check processor state, B is kept but the high byte of X is lost
+            let registers = processor.get_registers();
+            let (zero, negative, _carry) = get_flags(&processor);
+            assert_eq!(registers.acc, 0x1280);
+            assert_eq!(registers.x, 0x0078);
+            assert_eq!(registers.pc, 0x080E);
+            assert!(!zero);
+            assert!(negative);
+        }
+
+        #[test]
+        // Emulation mode - The registers stay 8 bits wide and the stack stays in page 1
+        fn test_emulation_fallback() {
+            // Create a new processor
+            let mut processor = Processor65C816::new(vec![
+                REP, 0x30, // REP #$30
+                LDA_IM, 0x42, // LDA #$42
+                LDX_IM, 0x00, // LDX #$00
+                TXS,  // TXS
+            ]);
+
+            // Execute instructions
+            for _ in 0..4 {
+                processor.step().unwrap();
+            }
+
+            // Check processor state
+            let registers = processor.get_registers();
+            assert!(registers.status.contains(MEMORY | INDEX));
+            assert_eq!(registers.acc, 0x0042);
+            assert_eq!(registers.sp, 0x0100);
+            assert_eq!(registers.pc, 0x0807);
+        }
+
+        #[test]
+        // XBA - Exchange the B and A accumulators
+        fn test_xba() {
+            // Create a new processor
+            let mut processor = Processor65C816::new(vec![
+                XBA, // XBA
+            ]);
+
+            // Set state of processor
+            processor.set_register().acc = 0x8000;
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state, the flags follow the new A
+            let acc = processor.get_registers().acc;
+            let (zero, negative, _carry) = get_flags(&processor);
+            assert_eq!(acc, 0x0080);
+            assert!(!zero);
+            assert!(negative);
+        }
+    }
+
+    // Addressing with banks, the direct page and the stack
+    mod addressing {
+        use super::*;
+        use crate::{
+            device::{Device, Ram},
+            error::Error,
+            opcodes::{
+                w65c816::{LDA_ABSL, LDA_SR, PLB, STA_INDLY},
+                LDA_ABS, LDA_IM, LDA_ZP, PHA,
+            },
+        };
+
+        // Device that always reads the same value
+        struct Constant(u8);
+
+        impl Device for Constant {
+            fn read_type(&self) -> String {
+                "Constant".to_string()
+            }
+
+            fn read(&self, _address: u32) -> u8 {
+                self.0
+            }
+
+            fn write(&mut self, _address: u32, _data: u8) {}
+        }
+
+        #[test]
+        // LDA/STA - Long addressing
+        fn test_long() {
+            // Create a new processor
+            let mut processor = Processor65C816::new(vec![
+                LDA_ABSL, 0x56, 0x34, 0x12, // LDA $123456
+                STA_INDLY, 0x10, // STA [$10],Y
+            ]);
+
+            // Set state of processor
+            processor.set_register().y = 0x02;
+            processor.set_mem(0x0010, 0xFF);
+            processor.set_mem(0x0011, 0xFF);
+            processor.set_mem(0x0012, 0x04);
+            processor.set_mem(0x123456, 0x42);
+
+            // Execute instructions
+            processor.step().unwrap();
+            processor.step().unwrap();
+
+            // Check memory, indexing crosses into the next bank
+            let acc = processor.get_registers().acc;
+            assert_eq!(acc, 0x0042);
+            assert_eq!(processor.get_mem(0x050001), 0x42);
+        }
+
+        #[test]
+        // LDA - Direct page and data bank registers
+        fn test_direct_page_and_data_bank() {
+            // Create a new processor
+            let mut processor = Processor65C816::new(vec![
+                LDA_ZP, 0x10, // LDA $10
+                PLB,  // PLB
+                LDA_ABS, 0x00, 0x20, // LDA $2000
+            ]);
+
+            // Set state of processor
+            processor.set_register().dp = 0x1200;
+            processor.set_mem(0x1210, 0x42);
+            processor.set_mem(0x01FE, 0x7E);
+            processor.set_mem(0x7E2000, 0x99);
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let acc = processor.get_registers().acc;
+            assert_eq!(acc, 0x0042);
+
+            // Execute instructions
+            processor.step().unwrap();
+            processor.step().unwrap();
+
+            // Check processor state
+            let registers = processor.get_registers();
+            assert_eq!(registers.dbr, 0x7E);
+            assert_eq!(registers.acc, 0x0099);
+        }
+
+        #[test]
+        // LDA - Stack relative
+        fn test_stack_relative() {
+            // Create a new processor
+            let mut processor = Processor65C816::new(vec![
+                LDA_IM, 0x42, // LDA #$42
+                PHA,  // PHA
+                LDA_IM, 0x00, // LDA #$00
+                LDA_SR, 0x01, // LDA $01,S
+            ]);
+
+            // Execute instructions
+            for _ in 0..4 {
+                processor.step().unwrap();
+            }
+
+            // Check processor state
+            let acc = processor.get_registers().acc;
+            assert_eq!(acc, 0x0042);
+        }
+
+        #[test]
+        // Devices can be mapped anywhere in the 24-bit address space
+        fn test_map_device() {
+            // Create a new processor
+            let mut processor = Processor65C816::new(vec![
+                LDA_ABSL, 0x00, 0x00, 0x01, // LDA $010000
+                LDA_ABSL, 0x00, 0x00, 0x02, // LDA $020000
+            ]);
+
+            // Map a device to bank 1
+            processor
+                .map(0x010000, 0x01FFFF, Box::new(Constant(0x42)))
+                .unwrap();
+
+            // Execute instruction
+            processor.step().unwrap();
+            let acc = processor.get_registers().acc;
+            assert_eq!(acc, 0x0042);
+
+            // Execute instruction
+            processor.step().unwrap();
+            let acc = processor.get_registers().acc;
+            assert_eq!(acc, 0x0000);
+        }
+
+        #[test]
+        // Devices have to fit in the 24-bit address space, unmapped addresses fail the instruction
+        fn test_unmapped_device() {
+            // Create a new processor
+            let mut processor = Processor65C816::new(vec![
+                LDA_ABSL, 0x00, 0x00, 0x01, // LDA $010000
+            ]);
+
+            // Check mapping
+            let error = Error::BadRegion {
+                start: 0xFF0000,
+                end: 0x1000000,
+            };
+            let result = processor.map(0xFF0000, 0x1000000, Box::new(Constant(0x42)));
+            assert_eq!(result, Err(error));
+
+            // Unmap the RAM and map bank 0 back
+            processor.unmap(0x000000, 0xFFFFFF).unwrap();
+            processor
+                .map(0x000000, 0x00FFFF, Box::new(Ram::new(0x10000)))
+                .unwrap();
+            processor.set_mem(0x000800, LDA_ABSL);
+            processor.set_mem(0x000803, 0x01);
+
+            // Execute instruction
+            assert_eq!(processor.step(), Err(Error::UnmappedAccess(0x010000)));
+            let pc = processor.get_registers().pc;
+            assert_eq!(pc, 0x0800);
+        }
+    }
+
+    // Arithmetic in native mode
+    mod arithmetic {
+        use super::*;
+        use crate::opcodes::{
+            w65c816::{REP, XCE},
+            ADC_IM, CLC, CMP_IM, SBC_IM, SEC, SED,
+        };
+
+        // Create a processor in native mode with a 16-bit accumulator
+        fn processor_native(program: &[u8]) -> Processor65C816 {
+            let mut code = vec![
+                CLC, // CLC
+                XCE, // XCE
+                REP, 0x20, // REP #$20
+            ];
+            code.extend_from_slice(program);
+
+            let mut processor = Processor65C816::new(code);
+            for _ in 0..3 {
+                processor.step().unwrap();
+            }
+            processor
+        }
+
+        #[test]
+        // ADC/SBC - 16-bit binary arithmetic
+        fn test_binary() {
+            // Create a new processor
+            let mut processor = processor_native(&[
+                CLC, // CLC
+                ADC_IM, 0xCC, 0xED, // ADC #$EDCC ; Zero, carry
+                SEC,  // SEC
+                SBC_IM, 0x01, 0x00, // SBC #$0001 ; Negative
+            ]);
+
+            // Set state of processor
+            processor.set_register().acc = 0x1234;
+
+            // Execute instructions
+            processor.step().unwrap();
+            processor.step().unwrap();
+
+            // Check processor state
+            let acc = processor.get_registers().acc;
+            let (zero, negative, carry) = get_flags(&processor);
+            assert_eq!(acc, 0x0000);
+            assert!(zero);
+            assert!(!negative);
+            assert!(carry);
+
+            // Execute instructions
+            processor.step().unwrap();
+            processor.step().unwrap();
+
+            // Check processor state
+            let acc = processor.get_registers().acc;
+            let (zero, negative, carry) = get_flags(&processor);
+            assert_eq!(acc, 0xFFFF);
+            assert!(!zero);
+            assert!(negative);
+            assert!(!carry);
+        }
+
+        #[test]
+        // ADC/SBC - 16-bit decimal arithmetic
+        fn test_decimal() {
+            // Create a new processor
+            let mut processor = processor_native(&[
+                SED, // SED
+                CLC, // CLC
+                ADC_IM, 0x01, 0x00, // ADC #$0001
+                SEC,  // SEC
+                SBC_IM, 0x01, 0x00, // SBC #$0001
+            ]);
+
+            // Set state of processor
+            processor.set_register().acc = 0x1999;
+
+            // Execute instructions
+            for _ in 0..3 {
+                processor.step().unwrap();
+            }
+
+            // Check processor state
+            let acc = processor.get_registers().acc;
+            let (_zero, _negative, carry) = get_flags(&processor);
+            assert_eq!(acc, 0x2000);
+            assert!(!carry);
+
+            // Execute instructions
+            processor.step().unwrap();
+            processor.step().unwrap();
+
+            // Check processor state
+            let acc = processor.get_registers().acc;
+            let (_zero, _negative, carry) = get_flags(&processor);
+            assert_eq!(acc, 0x1999);
+            assert!(carry);
+        }
+
+        #[test]
+        // CMP - 16-bit compare
+        fn test_cmp() {
+            // Create a new processor
+            let mut processor = processor_native(&[
+                CMP_IM, 0x00, 0x12, // CMP #$1200
+            ]);
+
+            // Set state of processor
+            processor.set_register().acc = 0x1100;
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let (zero, negative, carry) = get_flags(&processor);
+            assert!(!zero);
+            assert!(negative);
+            assert!(!carry);
+        }
+    }
+
+    // Block moves
+    mod block_moves {
+        use super::*;
+        use crate::opcodes::w65c816::{MVN, MVP};
+
+        #[test]
+        // MVN - Block Move Next
+        fn test_mvn() {
+            // Create a new processor
+            let mut processor = Processor65C816::new(vec![
+                MVN, 0x02, 0x01, // MVN $01,$02
+            ]);
+
+            // Set state of processor
+            processor.set_register().acc = 0x0002;
+            processor.set_register().x = 0x10;
+            processor.set_register().y = 0x20;
+            for i in 0..3 {
+                processor.set_mem(0x010010 + i, 0x41 + i as u8);
+            }
+
+            // Execute instruction, once per byte
+            for _ in 0..3 {
+                assert_eq!(processor.get_registers().pc, 0x0800);
+                processor.step().unwrap();
+            }
+
+            // Check processor state
+            let registers = processor.get_registers();
+            assert_eq!(registers.pc, 0x0803);
+            assert_eq!(registers.acc, 0xFFFF);
+            assert_eq!(registers.x, 0x13);
+            assert_eq!(registers.y, 0x23);
+            assert_eq!(registers.dbr, 0x02);
+            for i in 0..3 {
+                assert_eq!(processor.get_mem(0x020020 + i), 0x41 + i as u8);
+            }
+        }
+
+        #[test]
+        // MVP - Block Move Previous
+        fn test_mvp() {
+            // Create a new processor
+            let mut processor = Processor65C816::new(vec![
+                MVP, 0x00, 0x00, // MVP $00,$00
+            ]);
+
+            // Set state of processor, overlapping move one byte up
+            processor.set_register().acc = 0x0001;
+            processor.set_register().x = 0x11;
+            processor.set_register().y = 0x12;
+            processor.set_mem(0x0010, 0x41);
+            processor.set_mem(0x0011, 0x42);
+
+            // Execute instruction
+            processor.step().unwrap();
+            processor.step().unwrap();
+
+            // Check memory
+            let registers = processor.get_registers();
+            assert_eq!(registers.pc, 0x0803);
+            assert_eq!(registers.x, 0x0F);
+            assert_eq!(registers.y, 0x10);
+            assert_eq!(processor.get_mem(0x0011), 0x41);
+            assert_eq!(processor.get_mem(0x0012), 0x42);
+        }
+    }
+
+    // Jumps, branches and the stack
+    mod jumps {
+        use super::*;
+        use crate::opcodes::{
+            w65c816::{BRL, JSL, PEA, PEI, PER, RTL},
+            PLA,
+        };
+
+        #[test]
+        // JSL/RTL - Jump to Subroutine Long and Return
+        fn test_jsl_rtl() {
+            // Create a new processor
+            let mut processor = Processor65C816::new(vec![
+                JSL, 0x00, 0x20, 0x01, // JSL $012000
+            ]);
+
+            // Set state of processor
+            processor.set_mem(0x012000, RTL);
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let registers = processor.get_registers();
+            assert_eq!(registers.pbr, 0x01);
+            assert_eq!(registers.pc, 0x2000);
+            assert_eq!(registers.sp, 0x01FA);
+            assert_eq!(processor.get_mem(0x01FD), 0x00);
+            assert_eq!(processor.get_mem(0x01FC), 0x08);
+            assert_eq!(processor.get_mem(0x01FB), 0x03);
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let registers = processor.get_registers();
+            assert_eq!(registers.pbr, 0x00);
+            assert_eq!(registers.pc, 0x0804);
+            assert_eq!(registers.sp, 0x01FD);
+        }
+
+        #[test]
+        // BRL - Branch Always Long
+        fn test_brl() {
+            // Create a new processor
+            let mut processor = Processor65C816::new(vec![
+                BRL, 0x00, 0xF8, // BRL $0003
+            ]);
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let pc = processor.get_registers().pc;
+            assert_eq!(pc, 0x0003);
+        }
+
+        #[test]
+        // PEA/PEI/PER - Push Effective Addresses
+        fn test_push_effective_address() {
+            // Create a new processor
+            let mut processor = Processor65C816::new(vec![
+                PEA, 0x34, 0x12, // PEA $1234
+                PEI, 0x10, // PEI ($10)
+                PER, 0x10, 0x00, // PER $0818
+                PLA,  // PLA
+            ]);
+
+            // Set state of processor
+            processor.set_mem(0x0010, 0x78);
+            processor.set_mem(0x0011, 0x56);
+
+            // Execute instructions
+            for _ in 0..3 {
+                processor.step().unwrap();
+            }
+
+            // Check stack
+            assert_eq!(processor.get_mem(0x01FD), 0x12);
+            assert_eq!(processor.get_mem(0x01FC), 0x34);
+            assert_eq!(processor.get_mem(0x01FB), 0x56);
+            assert_eq!(processor.get_mem(0x01FA), 0x78);
+            assert_eq!(processor.get_mem(0x01F9), 0x08);
+            assert_eq!(processor.get_mem(0x01F8), 0x18);
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let acc = processor.get_registers().acc;
+            assert_eq!(acc, 0x0018);
+        }
+    }
+
+    // Interrupts
+    mod interrupts {
+        use super::*;
+        use crate::opcodes::{
+            cmos::{STP, WAI},
+            w65c816::{JML, XCE},
+            CLC, CLI, NOP, RTI,
+        };
+
+        #[test]
+        // IRQ - Native mode pushes the program bank and uses the native vector
+        fn test_native_irq() {
+            // Create a new processor
+            let mut processor = Processor65C816::new(vec![
+                CLC, // CLC
+                XCE, // XCE
+                CLI, // CLI
+                JML, 0x00, 0x30, 0x02, // JML $023000
+            ]);
+
+            // Set state of processor
+            processor.set_mem(0x023000, NOP);
+            processor.set_mem(0xFFEE, 0x00); // IRQ -> $9000
+            processor.set_mem(0xFFEF, 0x90);
+            processor.set_mem(0x9000, RTI);
+
+            // Execute instructions
+            for _ in 0..5 {
+                processor.step().unwrap();
+            }
+
+            // Service interrupt
+            processor.set_irq(true);
+            processor.step().unwrap();
+            processor.set_irq(false);
+
+            // Check processor state
+            let registers = processor.get_registers();
+            assert_eq!(registers.pbr, 0x00);
+            assert_eq!(registers.pc, 0x9000);
+            assert!(registers.status.contains(Status::INTERRUPT));
+            assert_eq!(processor.get_mem(0x01FD), 0x02);
+            assert_eq!(processor.get_mem(0x01FC), 0x30);
+            assert_eq!(processor.get_mem(0x01FB), 0x01);
+
+            // Return from interrupt
+            processor.step().unwrap();
+
+            // Check processor state
+            let registers = processor.get_registers();
+            assert_eq!(registers.pbr, 0x02);
+            assert_eq!(registers.pc, 0x3001);
+            assert_eq!(registers.sp, 0x01FD);
+            assert!(!registers.status.contains(Status::INTERRUPT));
+        }
+
+        #[test]
+        // WAI/STP - Wait for an interrupt and stop the processor
+        fn test_wai_stp() {
+            // Create a new processor
+            let mut processor = Processor65C816::new(vec![
+                WAI, // WAI
+                STP, // STP
+            ]);
+
+            // Execute instruction
+            processor.run().unwrap();
+            assert!(processor.is_waiting());
+
+            // Wake up with the IRQ masked
+            processor.set_irq(true);
+            processor.run().unwrap();
+
+            // Check processor state
+            let pc = processor.get_registers().pc;
+            assert!(processor.is_halted());
+            assert_eq!(pc, 0x0802);
+        }
+    }
+
+    // State of the processor seen from the outside
+    mod state {
+        use crate::{
+            device::Ram,
+            error::Error,
+            opcodes::{w65c816::*, CLC, INX, LDA_IM},
+            processor65c816::{CpuState65C816, Processor65C816, INDEX, MEMORY},
+            Status,
+        };
+
+        #[test]
+        // The state follows the registers
+        fn test_state() {
+            // Create a new processor
+            let mut processor = Processor65C816::new(vec![
+                CLC, // CLC
+                XCE, // XCE
+                REP, 0x30, // REP #$30
+                LDA_IM, 0x34, 0x12, // LDA #$1234
+            ]);
+
+            // Execute instructions
+            for _ in 0..4 {
+                processor.step().unwrap();
+            }
+
+            // Check processor state
+            let state = processor.state();
+            assert_eq!(state.acc, 0x1234);
+            assert_eq!(state.pc, 0x0807);
+            assert_eq!(state.pbr, 0x00);
+            assert_eq!(state.sp, 0x01FD);
+            assert!(!state.emulation);
+            assert!(!state.status.intersects(MEMORY | INDEX));
+            assert!(state.status.interrupt());
+        }
+
+        #[test]
+        // Setting the state changes the registers for the next instruction
+        fn test_set_state() {
+            // Create a new processor
+            let mut processor = Processor65C816::new(vec![
+                INX, // INX
+            ]);
+
+            // Set state of processor
+            let mut state = processor.state();
+            state.emulation = false;
+            state.status.remove(INDEX);
+            state.x = 0x12FF;
+            processor.set_state(state);
+
+            // Execute instruction
+            processor.step().unwrap();
+
+            // Check processor state
+            let state = processor.state();
+            assert_eq!(state.x, 0x1300);
+            assert_eq!(state.pc, 0x0801);
+        }
+
+        #[test]
+        // Setting the state in emulation mode keeps the stack in page 1 and the registers 8 bits wide
+        fn test_set_state_emulation() {
+            // Create a new processor
+            let mut processor = Processor65C816::new(vec![]);
+
+            // Set state of processor
+            let mut state = processor.state();
+            state.emulation = true;
+            state.status = Status::empty();
+            state.sp = 0x1234;
+            state.x = 0x1234;
+            state.y = 0x5678;
+            processor.set_state(state);
+
+            // Check processor state
+            let state = processor.state();
+            assert_eq!(state.sp, 0x0134);
+            assert_eq!(state.x, 0x0034);
+            assert_eq!(state.y, 0x0078);
+            assert!(state.status.contains(MEMORY | INDEX));
+        }
+
+        #[test]
+        // The state is shown on one line
+        fn test_display() {
+            let state = CpuState65C816 {
+                pc: 0x0800,
+                pbr: 0x00,
+                dbr: 0x00,
+                dp: 0x0000,
+                sp: 0x01FD,
+                acc: 0x0042,
+                x: 0x0000,
+                y: 0x0000,
+                status: MEMORY | INDEX | Status::INTERRUPT,
+                emulation: true,
+            };
+            assert_eq!(
+                state.to_string(),
+                "A=0042 X=0000 Y=0000 SP=01FD D=0000 DB=00 P=nvMXdIzc E=1 PC=00:0800"
+            );
+        }
+
+        #[test]
+        // Memory is read and written without running instructions
+        fn test_memory() {
+            // Create a new processor
+            let mut processor = Processor65C816::new(vec![]);
+
+            // Write and read memory
+            processor.write_memory(0x123456, 0x42).unwrap();
+            assert_eq!(processor.read_memory(0x123456), Ok(0x42));
+            assert_eq!(processor.state().pc, 0x0800);
+
+            // Unmapped memory
+            processor.unmap(0x000000, 0xFFFFFF).unwrap();
+            processor
+                .map(0x000000, 0x00FFFF, Box::new(Ram::new(0x10000)))
+                .unwrap();
+            let error = Error::UnmappedAccess(0x123456);
+            assert_eq!(processor.read_memory(0x123456), Err(error));
+            assert_eq!(processor.write_memory(0x123456, 0x42), Err(error));
+        }
     }
 }